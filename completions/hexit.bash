@@ -8,7 +8,27 @@ _hexit()
             return
             ;;
 
-        -e|--expression|--limit|--prefix|--suffix|--separator|--verify-length|--verify-multiple)
+        --limit|--line-limit|--tab-width|--prefix|--suffix|--separator|--verify-length|--verify-multiple)
+            return
+            ;;
+
+        -e|--expression|--list-constants)
+            COMPREPLY=( $( compgen -W "${_hexit_constants-$( hexit --list-constants --names-only 2>/dev/null )}" -- "$cur" ) )
+            return
+            ;;
+
+        --errors)
+            COMPREPLY=( $( compgen -W 'human json' -- "$cur" ) )
+            return
+            ;;
+
+        --generate-completions)
+            COMPREPLY=( $( compgen -W 'bash zsh' -- "$cur" ) )
+            return
+            ;;
+
+        --append-checksum)
+            COMPREPLY=( $( compgen -W 'crc16 sum8' -- "$cur" ) )
             return
             ;;
     esac
@@ -1,19 +1,36 @@
 # Meta options
 complete -c hexit -s 'v' -l 'version'         -d "Show version of hexit"
 complete -c hexit -s '?' -l 'help'            -d "Show list of command-line options"
+complete -c hexit        -l 'list-constants'  -d "Print the list of available constants"
+complete -c hexit        -l 'names-only'      -d "Print only the names of constants when listing them"
+complete -c hexit        -l 'generate-completions' -d "Print a completion script for a shell" -x -a "bash zsh"
 
 # Input options
 complete -c hexit -s 'e' -l 'expression'      -d "Evaluate this string instead of reading a file"
 complete -c hexit -s 'c' -l 'check-syntax'    -d "Check syntax without generating any output"
+complete -c hexit        -l 'constants'       -d "Load extra constants from a file" -r
+complete -c hexit        -l 'semicolon-comments' -d "Treat ‘;’ as the start of a comment, like ‘#’"
+complete -c hexit        -l 'no-front-comments' -d "Do not treat text before a colon as a comment"
+complete -c hexit        -l 'warn-front-comments' -d "Warn about front comments that contain only values"
+complete -c hexit        -l 'lint'            -d "Warn about valid input that looks like a mistake"
+complete -c hexit        -l 'normalise'       -d "Print the program as canonical Hexit source"
+complete -c hexit        -l 'expand-constants' -d "Replace constants with their values when normalising"
+complete -c hexit        -l 'from-bytes'      -d "Turn raw input bytes back into a Hexit program"
 
 # Output options
 complete -c hexit        -l 'prefix'          -d "String to print before a pair of hex characters" -x
 complete -c hexit        -l 'suffix'          -d "String to print after a pair of hex characters" -x
 complete -c hexit        -l 'separator'       -d "String to print between successive pairs of hex characters" -x
 complete -c hexit -s 'l' -l 'lowercase'       -d "If you like your letters minuscule"
+complete -c hexit        -l 'no-newline'      -d "Do not print a newline after the output"
 complete -c hexit -s 'r' -l 'raw'             -d "Print bytes without any formatting at all"
 complete -c hexit -s 'o' -l 'output'          -d "Write output to the given file, rather than to stdout" -x
 complete -c hexit        -l 'limit'           -d "Limit the output from getting too large" -x
+complete -c hexit        -l 'line-limit'      -d "Limit the output of each expression from getting too large" -x
+complete -c hexit        -l 'append-checksum' -d "Write a checksum of everything before it" -x -a "crc16 sum8"
+complete -c hexit        -l 'stats'           -d "Print the output’s length and checksum to stderr"
+complete -c hexit        -l 'errors'          -d "How to print errors in the program" -x -a "human json"
+complete -c hexit        -l 'tab-width'       -d "Count tabs up to this width in error columns" -x
 
 # Verification options
 
@@ -0,0 +1,19 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use hexit_lang::{Program, constants};
+
+// Builds a bit form out of the low bit of each input byte, so forms of every
+// length get tried, including the ones either side of 8, 16, 32, and 64
+// bits. Reading and running them should never panic, whatever they’re
+// wrapped in.
+fuzz_target!(|data: &[u8]| {
+    let bits = data.iter().map(|b| if b & 1 == 1 { '1' } else { '0' }).collect::<String>();
+    let constants = constants::Table::builtin_set();
+
+    for function in &[ "", "be16", "le16", "be32", "le32", "be64", "le64" ] {
+        let line = format!("{}[b{}]", function, bits);
+        let _ = Program::read(&[line.as_str()]).map(|prog| {
+            let _ = prog.run(&constants, Some(131072), None);
+        });
+    }
+});
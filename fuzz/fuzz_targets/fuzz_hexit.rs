@@ -11,6 +11,6 @@ fuzz_target!(|data: &[u8]| {
 
     let _ = Program::read(&[string]).map(|prog| {
         let constants = constants::Table::builtin_set();
-        let _ = prog.run(&constants, Some(131072));
+        let _ = prog.run(&constants, Some(131072), None);
     });
 });
@@ -0,0 +1,32 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use hexit_lang::Program;
+use hexit_lang::constants::Table;
+
+
+/// Looks up every built-in constant by name, one after the other.
+fn lookup_every_constant(c: &mut Criterion) {
+    let table = Table::builtin_set();
+    let names = table.all().map(|(name, _)| name.to_owned()).collect::<Vec<_>>();
+
+    c.bench_function("lookup every constant", |b| b.iter(|| {
+        for name in &names {
+            black_box(table.lookup(black_box(name)));
+        }
+    }));
+}
+
+/// Runs a program that is made up of nothing but constant references.
+fn run_constant_heavy_program(c: &mut Criterion) {
+    let table = Table::builtin_set();
+    let line = "IP_TCP IP_UDP be16(DNS_A) be16(ETHERTYPE_IPV6) GZIP_DEFLATE ICMP_ECHO be16(TCP_SYN) BGP_OPEN ".repeat(20);
+    let lines = vec![ line; 100 ];
+
+    c.bench_function("run constant-heavy program", |b| b.iter(|| {
+        let program = Program::read(&lines).expect("Parsing failed");
+        black_box(program.run(&table, None).expect("Running failed"));
+    }));
+}
+
+criterion_group!(benches, lookup_every_constant, run_constant_heavy_program);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use hexit_lang::Program;
+
+
+/// Reads single lines of increasing numbers of tokens. The time taken per
+/// token should stay about the same as the line gets longer; if it grows,
+/// something is going over the line more than once.
+fn read_long_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read long line");
+    group.sample_size(20);
+
+    for &token_count in &[ 25_000, 50_000, 100_000 ] {
+        let line = "AB x2(CD) IP_UDP \"ef\" ".repeat(token_count / 4);
+        let lines = [ line ];
+
+        group.throughput(Throughput::Elements(token_count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(token_count), &lines, |b, lines| b.iter(|| {
+            black_box(Program::read(lines).expect("Parsing failed"));
+        }));
+    }
+
+    group.finish();
+}
+
+/// Reads single lines with a front comment that gets longer and longer, as
+/// the front comment is searched for and removed separately.
+fn read_long_front_comment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read long front comment");
+    group.sample_size(20);
+
+    for &token_count in &[ 25_000, 50_000, 100_000 ] {
+        let line = format!("{} AB", "label: ".repeat(token_count / 2));
+        let lines = [ line ];
+
+        group.throughput(Throughput::Elements(token_count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(token_count), &lines, |b, lines| b.iter(|| {
+            black_box(Program::read(lines).expect("Parsing failed"));
+        }));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, read_long_line, read_long_front_comment);
+criterion_main!(benches);
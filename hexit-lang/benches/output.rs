@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use hexit_lang::Program;
+use hexit_lang::constants::Table;
+
+
+/// Runs a program that produces a lot of output from many large repeats.
+fn run_repeat_heavy_program(c: &mut Criterion) {
+    let table = Table::builtin_set();
+    let lines = vec![ "x1000(AB CD EF) zero([4000]) x500(\"hexit\") fill([3000] 01 02)"; 200 ];
+
+    c.bench_function("run repeat-heavy program", |b| b.iter(|| {
+        let program = Program::read(&lines).expect("Parsing failed");
+        black_box(program.run(&table, None).expect("Running failed"));
+    }));
+}
+
+/// Runs a program with one repeat that produces a lot of output.
+fn run_large_repeat(c: &mut Criterion) {
+    let table = Table::builtin_set();
+    let lines = vec![ "x60000(x100(AB CD EF 01 02 03 04 05))" ];
+
+    c.bench_function("run large repeat", |b| b.iter(|| {
+        let program = Program::read(&lines).expect("Parsing failed");
+        black_box(program.run(&table, None).expect("Running failed"));
+    }));
+}
+
+criterion_group!(benches, run_repeat_heavy_program, run_large_repeat);
+criterion_main!(benches);
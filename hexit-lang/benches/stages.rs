@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use hexit_lang::{Program, ParseOptions};
+use hexit_lang::constants::Table;
+
+
+/// The inputs that each stage gets measured with, along with their names.
+/// Every input is made up of many copies of the same line, so the numbers
+/// stay comparable as the language changes.
+fn inputs() -> Vec<(&'static str, Vec<String>)> {
+    let byte_string = "00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F AABBCCDDEEFF ".repeat(20);
+    let constant_heavy = "IP_TCP IP_UDP be16(DNS_A) be16(ETHERTYPE_IPV6) GZIP_DEFLATE ICMP_ECHO be16(TCP_SYN) BGP_OPEN ".repeat(20);
+    let deep_repeat = String::from("x4(01 x4(02 x4(03 x4(04 x4(05 x4(06 x4(07 x4(08 FF))))))))");
+
+    vec![
+        ("long byte string",        vec![ byte_string; 100 ]),
+        ("constant-heavy program",  vec![ constant_heavy; 100 ]),
+        ("deep repeat",             vec![ deep_repeat; 10 ]),
+    ]
+}
+
+/// Measures lexing on its own. Reading the comments of a program lexes it
+/// without parsing it, so that gets used.
+fn lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+
+    for (name, lines) in inputs() {
+        group.bench_function(name, |b| b.iter(|| {
+            black_box(Program::read_comments(&lines, ParseOptions::default()).expect("Lexing failed"));
+        }));
+    }
+
+    group.finish();
+}
+
+/// Measures lexing and parsing together, which is how programs get read.
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+
+    for (name, lines) in inputs() {
+        group.bench_function(name, |b| b.iter(|| {
+            black_box(Program::read(&lines).expect("Parsing failed"));
+        }));
+    }
+
+    group.finish();
+}
+
+/// Measures evaluation on its own, reading each program beforehand.
+fn run(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+    let table = Table::builtin_set();
+
+    for (name, lines) in inputs() {
+        group.bench_function(name, |b| b.iter_batched(
+            || Program::read(&lines).expect("Parsing failed"),
+            |program| black_box(program.run(&table, None).expect("Running failed")),
+            BatchSize::SmallInput,
+        ));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, lex, read, run);
+criterion_main!(benches);
@@ -1,7 +1,10 @@
 //! The abstract syntax tree type, which gets produced by the parser and
 //! interpreted by the evaluator.
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::pos::Placed;
 
 
 /// An expression in a Hexit syntax tree.
@@ -18,8 +21,9 @@ pub enum Exp<'src> {
     /// A constant, referred to by its name.
     Constant {
 
-        /// The name of the constant.
-        name: &'src str,
+        /// The name of the constant, and where it appeared in the source, in
+        /// case it turns out not to exist.
+        name: Placed<&'src str>,
     },
 
     /// A function call.
@@ -28,6 +32,11 @@ pub enum Exp<'src> {
         /// The name of the function to call.
         name: FunctionName,
 
+        /// Where the function’s name appeared in the source, so that a
+        /// warning about a call nested inside another expression can point
+        /// at the call itself.
+        position: Placed<&'src str>,
+
         /// The arguments to pass to the function.
         args: Vec<Exp<'src>>,
     },
@@ -38,8 +47,9 @@ pub enum Exp<'src> {
         /// The bytes that make up the string.
         /// This is a reference to the original string’s bytes, unless the
         /// string features backslashes or escape characters, which need to be
-        /// processed before the bytes can be read.
-        chars: Cow<'src, str>,
+        /// processed before the bytes can be read. As an escape can produce
+        /// any byte, these are not necessarily valid UTF-8.
+        bytes: Cow<'src, [u8]>,
     },
 
     /// An IPv4 address.
@@ -52,8 +62,12 @@ pub enum Exp<'src> {
         bytes: [u8; 16],
     },
 
-    /// An ISO 8601 timestamp.
-    Timestamp(u32),
+    /// An ISO 8601 timestamp, as a number of seconds, milliseconds, or
+    /// nanoseconds since the Unix epoch.
+    Timestamp {
+        time: u64,
+        unit: TimeUnit,
+    },
 
     /// A floating point number.
     /// Like the `Dec` variant, this has also not yet been parsed, as we do
@@ -62,6 +76,17 @@ pub enum Exp<'src> {
 
     /// A series of individual bits.
     Bits(Vec<bool>),
+
+    /// A number with an explicit type, such as `[u16:256]`. As its width and
+    /// endianness are known, it has already been turned into bytes.
+    Typed {
+        bytes: Vec<u8>,
+    },
+
+    /// A group of expressions in parentheses with no function name before
+    /// them, such as `(AB CD)`. This evaluates to the bytes of each of its
+    /// expressions, one after another.
+    Group(Vec<Exp<'src>>),
 }
 
 /// The name of a function to call.
@@ -71,9 +96,35 @@ pub enum Exp<'src> {
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum FunctionName {
     MultiByte(MultiByteType),
+    FixedPoint(FixedPointType),
     Bitwise(BitwiseFold),
     BitwiseNot,
+    NibbleSwap,
+    Neg,
+    PopCount,
+    LeadingZeros,
+    TrailingZeros,
+    Fill,
+    Bits,
+    Zero,
+    Ones,
+    Gray,
+    Parity,
+    Varint,
+    SignedVarint,
+    Bcd,
     Repeat(RepeatAmount),
+
+    /// A repeat function whose amount is given as its first argument, rather
+    /// than as part of its name. Whether an amount of zero is allowed gets
+    /// decided when the program is read, so it’s carried along here.
+    RepeatDynamic { allow_zero: bool },
+
+    /// A repeat function that also takes a separator to put between each
+    /// repetition, with the amount as its first argument and the separator
+    /// as its second. Zero amounts are decided on in the same way as for
+    /// `RepeatDynamic`.
+    Joined { allow_zero: bool },
 }
 
 /// One of the multi-byte-type function names.
@@ -87,6 +138,31 @@ pub enum MultiByteType {
     Le64,
 }
 
+/// One of the fixed-point function names, which turn a decimal number into
+/// a signed Q-format number with a fixed number of fractional bits.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum FixedPointType {
+    Q8_8Be,
+    Q8_8Le,
+    Q16_16Be,
+    Q16_16Le,
+}
+
+/// The unit that a timestamp counts in, which gets chosen with a prefix in
+/// its form, such as `[ms:2017-12-31T21:36:45]`.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TimeUnit {
+
+    /// Seconds, when there’s no prefix.
+    Seconds,
+
+    /// Milliseconds, with the `ms:` prefix.
+    Millis,
+
+    /// Nanoseconds, with the `ns:` prefix.
+    Nanos,
+}
+
 /// One of the bitwise function names.
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum BitwiseFold {
@@ -1,13 +1,31 @@
-use std::collections::BTreeMap;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::iter;
 
 
-/// A constants table maps the names of constants to their values.
+/// The map used to look up constants by name. A hash map is faster for
+/// programs that use a lot of constants, but without the standard library,
+/// a B-tree map has to do.
+#[cfg(feature = "std")]
+type Map = std::collections::HashMap<Cow<'static, str>, Constant>;
+
+#[cfg(not(feature = "std"))]
+type Map = alloc::collections::BTreeMap<Cow<'static, str>, Constant>;
+
+/// A constants table maps the names of constants to their values. The
+/// built-in names are static, but names added later can be owned.
 pub struct Table {
-    map: BTreeMap<&'static str, Constant>,
+
+    /// The value of each constant, by name.
+    map: Map,
+
+    /// The name of every constant in the map, kept sorted, as the map itself
+    /// is in no particular order.
+    names: Vec<Cow<'static, str>>,
 }
 
 /// A constant in a table, which is of variable size.
-#[derive(Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Constant {
 
     /// A constant that’s one byte long.
@@ -15,19 +33,80 @@ pub enum Constant {
 
     /// A constant that’s two bytes long.
     Sixteen(u16),
+
+    /// A constant that’s four bytes long.
+    ThirtyTwo(u32),
+}
+
+impl Constant {
+
+    /// Returns the number of bits this constant takes up.
+    pub fn bit_width(self) -> u8 {
+        match self {
+            Self::Eight(_)      => 8,
+            Self::Sixteen(_)    => 16,
+            Self::ThirtyTwo(_)  => 32,
+        }
+    }
+
+    /// Returns the value of this constant as a 64-bit number, which every
+    /// width fits in.
+    pub fn value_u64(self) -> u64 {
+        match self {
+            Self::Eight(num)      => u64::from(num),
+            Self::Sixteen(num)    => u64::from(num),
+            Self::ThirtyTwo(num)  => u64::from(num),
+        }
+    }
 }
 
 impl Table {
 
     /// Looks up the value of a constant using its name, returning an error if
-    /// no such constant exists.
+    /// no such constant exists. The name can be namespaced, such as `dns.A`,
+    /// in which case it gets looked up as `DNS_A`.
     pub fn lookup(&self, name: &str) -> Option<Constant> {
-        self.map.get(name).copied()
+        match name.split_once('.') {
+            Some((namespace, rest)) => {
+                // compare against the flattened name a byte at a time,
+                // rather than building it up in a new string
+                let flat_name = || namespace.bytes().map(|b| b.to_ascii_uppercase())
+                                         .chain(iter::once(b'_'))
+                                         .chain(rest.bytes());
+
+                let index = self.names.binary_search_by(|n| n.bytes().cmp(flat_name())).ok()?;
+                self.map.get(&self.names[index]).copied()
+            }
+            None => {
+                self.map.get(name).copied()
+            }
+        }
+    }
+
+    /// Returns an iterator that yields the name of every constant with the
+    /// given value. Values are frequently shared between constants from
+    /// different protocols, so there can be more than one.
+    pub fn names_for_value(&self, value: Constant) -> impl Iterator<Item=&str> + '_ {
+        self.all().filter(move |&(_, v)| v == value).map(|(name, _)| name)
+    }
+
+    /// Returns an iterator that yields every known constant’s name and value,
+    /// sorted by name.
+    pub fn all(&self) -> impl Iterator<Item=(&str, Constant)> + '_ {
+        self.names.iter().map(move |name| (name.as_ref(), self.map[name]))
     }
 
-    /// Returns an iterator that yields every known constant’s name and value.
-    pub fn all(&self) -> impl Iterator<Item=(&'static str, Constant)> + '_ {
-        self.map.iter().map(|(&a, &b)| (a, b))
+    /// Adds a constant to the table, replacing any constant that already
+    /// has the same name. Returns the value of the constant that was
+    /// replaced, if there was one, so the caller can warn about it.
+    pub fn insert(&mut self, name: impl Into<Cow<'static, str>>, value: Constant) -> Option<Constant> {
+        let name = name.into();
+
+        if let Err(index) = self.names.binary_search(&name) {
+            self.names.insert(index, name.clone());
+        }
+
+        self.map.insert(name, value)
     }
 }
 
@@ -35,135 +114,296 @@ impl Table {
 
     /// Creates a new empty constants table.
     pub fn empty() -> Self {
-        Self { map: BTreeMap::new() }
+        Self { map: Map::new(), names: Vec::new() }
     }
 
     /// Creates a new constants table using the built-in set of data.
     pub fn builtin_set() -> Self {
-        let mut map = BTreeMap::new();
+        let mut map = Self::empty();
 
 
         // BGP stuff
         // https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml
 
         // BGP message types
-        map.insert("BGP_OPEN",          Constant::Eight(1));
-        map.insert("BGP_UPDATE",        Constant::Eight(2));
-        map.insert("BGP_NOTIFICATION",  Constant::Eight(3));
-        map.insert("BGP_KEEPALIVE",     Constant::Eight(4));
-        map.insert("BGP_ROUTE_REFRESH", Constant::Eight(5));
+        map.insert_builtin("BGP_OPEN",          Constant::Eight(1));
+        map.insert_builtin("BGP_UPDATE",        Constant::Eight(2));
+        map.insert_builtin("BGP_NOTIFICATION",  Constant::Eight(3));
+        map.insert_builtin("BGP_KEEPALIVE",     Constant::Eight(4));
+        map.insert_builtin("BGP_ROUTE_REFRESH", Constant::Eight(5));
 
 
         // DNS stuff
         // https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml
 
         // DNS classes
-        map.insert("DNS_IN",     Constant::Sixteen(1));
-        map.insert("DNS_CH",     Constant::Sixteen(3));
-        map.insert("DNS_HS",     Constant::Sixteen(4));
+        map.insert_builtin("DNS_IN",     Constant::Sixteen(1));
+        map.insert_builtin("DNS_CH",     Constant::Sixteen(3));
+        map.insert_builtin("DNS_HS",     Constant::Sixteen(4));
 
         // DNS record types
-        map.insert("DNS_A",           Constant::Sixteen(  1));
-        map.insert("DNS_NS",          Constant::Sixteen(  2));
-        map.insert("DNS_CNAME",       Constant::Sixteen(  5));
-        map.insert("DNS_SOA",         Constant::Sixteen(  6));
-        map.insert("DNS_PTR",         Constant::Sixteen( 12));
-        map.insert("DNS_HINFO",       Constant::Sixteen( 13));
-        map.insert("DNS_MINFO",       Constant::Sixteen( 14));
-        map.insert("DNS_MX",          Constant::Sixteen( 15));
-        map.insert("DNS_TXT",         Constant::Sixteen( 16));
-        map.insert("DNS_GPOS",        Constant::Sixteen( 27));
-        map.insert("DNS_AAAA",        Constant::Sixteen( 28));
-        map.insert("DNS_LOC",         Constant::Sixteen( 29));
-        map.insert("DNS_SRV",         Constant::Sixteen( 33));
-        map.insert("DNS_NAPTR",       Constant::Sixteen( 35));
-        map.insert("DNS_OPT",         Constant::Sixteen( 41));
-        map.insert("DNS_SSHFP",       Constant::Sixteen( 44));
-        map.insert("DNS_IPSECKEY",    Constant::Sixteen( 45));
-        map.insert("DNS_TLSA",        Constant::Sixteen( 52));
-        map.insert("DNS_OPENPGPKEY",  Constant::Sixteen( 61));
-        map.insert("DNS_EUI48",       Constant::Sixteen(108));
-        map.insert("DNS_EUI64",       Constant::Sixteen(109));
-        map.insert("DNS_ANY",         Constant::Sixteen(255));
-        map.insert("DNS_URI",         Constant::Sixteen(256));
-        map.insert("DNS_CAA",         Constant::Sixteen(257));
+        map.insert_builtin("DNS_A",           Constant::Sixteen(  1));
+        map.insert_builtin("DNS_NS",          Constant::Sixteen(  2));
+        map.insert_builtin("DNS_CNAME",       Constant::Sixteen(  5));
+        map.insert_builtin("DNS_SOA",         Constant::Sixteen(  6));
+        map.insert_builtin("DNS_PTR",         Constant::Sixteen( 12));
+        map.insert_builtin("DNS_HINFO",       Constant::Sixteen( 13));
+        map.insert_builtin("DNS_MINFO",       Constant::Sixteen( 14));
+        map.insert_builtin("DNS_MX",          Constant::Sixteen( 15));
+        map.insert_builtin("DNS_TXT",         Constant::Sixteen( 16));
+        map.insert_builtin("DNS_GPOS",        Constant::Sixteen( 27));
+        map.insert_builtin("DNS_AAAA",        Constant::Sixteen( 28));
+        map.insert_builtin("DNS_LOC",         Constant::Sixteen( 29));
+        map.insert_builtin("DNS_SRV",         Constant::Sixteen( 33));
+        map.insert_builtin("DNS_NAPTR",       Constant::Sixteen( 35));
+        map.insert_builtin("DNS_OPT",         Constant::Sixteen( 41));
+        map.insert_builtin("DNS_SSHFP",       Constant::Sixteen( 44));
+        map.insert_builtin("DNS_IPSECKEY",    Constant::Sixteen( 45));
+        map.insert_builtin("DNS_TLSA",        Constant::Sixteen( 52));
+        map.insert_builtin("DNS_OPENPGPKEY",  Constant::Sixteen( 61));
+        map.insert_builtin("DNS_EUI48",       Constant::Sixteen(108));
+        map.insert_builtin("DNS_EUI64",       Constant::Sixteen(109));
+        map.insert_builtin("DNS_ANY",         Constant::Sixteen(255));
+        map.insert_builtin("DNS_URI",         Constant::Sixteen(256));
+        map.insert_builtin("DNS_CAA",         Constant::Sixteen(257));
+
+
+        // ELF stuff
+        // https://refspecs.linuxfoundation.org/elf/gabi4+/ch4.eheader.html
+
+        // ELF file signature (0x7F then ‘ELF’)
+        map.insert_builtin("ELF_MAGIC",  Constant::ThirtyTwo(0x7F45_4C46));
 
 
         // Ethernet stuff
         // https://www.iana.org/assignments/ieee-802-numbers/ieee-802-numbers.xhtml
 
         // Ethernet types (EtherTypes)
-        map.insert("ETHERTYPE_IPv4",         Constant::Sixteen(0x0800));
-        map.insert("ETHERTYPE_ARP",          Constant::Sixteen(0x0806));
-        map.insert("ETHERTYPE_WAKE_ON_LAN",  Constant::Sixteen(0x0842));
-        map.insert("ETHERTYPE_IPV6",         Constant::Sixteen(0x86DD));
+        map.insert_builtin("ETHERTYPE_IPv4",         Constant::Sixteen(0x0800));
+        map.insert_builtin("ETHERTYPE_ARP",          Constant::Sixteen(0x0806));
+        map.insert_builtin("ETHERTYPE_WAKE_ON_LAN",  Constant::Sixteen(0x0842));
+        map.insert_builtin("ETHERTYPE_IPV6",         Constant::Sixteen(0x86DD));
 
 
         // Gzip stuff
         // http://www.gzip.org/format.txt
 
+        // Gzip file signature
+        map.insert_builtin("GZIP_ID1",      Constant::Eight(0x1F));
+        map.insert_builtin("GZIP_ID2",      Constant::Eight(0x8B));
+
         // Gzip compression methods
-        map.insert("GZIP_DEFLATE",  Constant::Eight(0x08));
+        map.insert_builtin("GZIP_DEFLATE",  Constant::Eight(0x08));
 
         // Gzip compression flags
-        map.insert("GZIP_SLOWEST",  Constant::Eight(0x02));
-        map.insert("GZIP_FASTEST",  Constant::Eight(0x04));
+        map.insert_builtin("GZIP_SLOWEST",  Constant::Eight(0x02));
+        map.insert_builtin("GZIP_FASTEST",  Constant::Eight(0x04));
 
         // Gzip flags
-        map.insert("GZIP_FTEXT",    Constant::Eight(0x01));
-        map.insert("GZIP_FHCRC",    Constant::Eight(0x02));
-        map.insert("GZIP_FEXTRA",   Constant::Eight(0x04));
-        map.insert("GZIP_FNAME",    Constant::Eight(0x08));
-        map.insert("GZIP_FCOMMENT", Constant::Eight(0x10));
+        map.insert_builtin("GZIP_FTEXT",    Constant::Eight(0x01));
+        map.insert_builtin("GZIP_FHCRC",    Constant::Eight(0x02));
+        map.insert_builtin("GZIP_FEXTRA",   Constant::Eight(0x04));
+        map.insert_builtin("GZIP_FNAME",    Constant::Eight(0x08));
+        map.insert_builtin("GZIP_FCOMMENT", Constant::Eight(0x10));
 
         // Gzip OSes
-        map.insert("GZIP_FAT",      Constant::Eight( 0));
-        map.insert("GZIP_UNIX",     Constant::Eight( 3));
-        map.insert("GZIP_NT",       Constant::Eight(11));
+        map.insert_builtin("GZIP_FAT",      Constant::Eight( 0));
+        map.insert_builtin("GZIP_UNIX",     Constant::Eight( 3));
+        map.insert_builtin("GZIP_NT",       Constant::Eight(11));
 
 
         // ICMP stuff
         // https://www.iana.org/assignments/icmp-parameters/icmp-parameters.xhtml
 
         // ICMP message types
-        map.insert("ICMP_ECHO_REPLY",               Constant::Eight( 0));
-        map.insert("ICMP_DESTINATION_UNREACHABLE",  Constant::Eight( 2));
-        map.insert("ICMP_REDIRECT",                 Constant::Eight( 5));
-        map.insert("ICMP_ECHO",                     Constant::Eight( 8));
-        map.insert("ICMP_ROUTER_ADVERTISEMENT",     Constant::Eight( 9));
-        map.insert("ICMP_ROUTER_SOLICITATION",      Constant::Eight(10));
-        map.insert("ICMP_TIME_EXCEEDED",            Constant::Eight(11));
-        map.insert("ICMP_PARAMETER_PROBLEM",        Constant::Eight(12));
-        map.insert("ICMP_TIMESTAMP_REQUEST",        Constant::Eight(13));
-        map.insert("ICMP_TIMESTAMP_REPLY",          Constant::Eight(14));
-        map.insert("ICMP_ADDRESS_MASK_REQUEST",     Constant::Eight(17));
-        map.insert("ICMP_ADDRESS_MASK_REPLY",       Constant::Eight(18));
+        map.insert_builtin("ICMP_ECHO_REPLY",               Constant::Eight( 0));
+        map.insert_builtin("ICMP_DESTINATION_UNREACHABLE",  Constant::Eight( 2));
+        map.insert_builtin("ICMP_REDIRECT",                 Constant::Eight( 5));
+        map.insert_builtin("ICMP_ECHO",                     Constant::Eight( 8));
+        map.insert_builtin("ICMP_ROUTER_ADVERTISEMENT",     Constant::Eight( 9));
+        map.insert_builtin("ICMP_ROUTER_SOLICITATION",      Constant::Eight(10));
+        map.insert_builtin("ICMP_TIME_EXCEEDED",            Constant::Eight(11));
+        map.insert_builtin("ICMP_PARAMETER_PROBLEM",        Constant::Eight(12));
+        map.insert_builtin("ICMP_TIMESTAMP_REQUEST",        Constant::Eight(13));
+        map.insert_builtin("ICMP_TIMESTAMP_REPLY",          Constant::Eight(14));
+        map.insert_builtin("ICMP_ADDRESS_MASK_REQUEST",     Constant::Eight(17));
+        map.insert_builtin("ICMP_ADDRESS_MASK_REPLY",       Constant::Eight(18));
 
 
         // IP stuff
         // https://www.iana.org/assignments/protocol-numbers/protocol-numbers.xhtml
 
         // IP protocols [/etc/protocols]
-        map.insert("IP_ICMP",  Constant::Eight(  1));
-        map.insert("IP_IGMP",  Constant::Eight(  2));
-        map.insert("IP_TCP",   Constant::Eight(  6));
-        map.insert("IP_UDP",   Constant::Eight( 17));
-        map.insert("IP_SCTP",  Constant::Eight(132));
+        map.insert_builtin("IP_ICMP",  Constant::Eight(  1));
+        map.insert_builtin("IP_IGMP",  Constant::Eight(  2));
+        map.insert_builtin("IP_TCP",   Constant::Eight(  6));
+        map.insert_builtin("IP_UDP",   Constant::Eight( 17));
+        map.insert_builtin("IP_SCTP",  Constant::Eight(132));
+
+
+        // MZ stuff
+        // https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+
+        // MS-DOS executable signature (‘MZ’), which PE files also start with
+        map.insert_builtin("MZ_MAGIC",  Constant::Sixteen(0x4D5A));
+
+
+        // PE stuff
+        // https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+
+        // PE signature (‘PE’ then two null bytes)
+        map.insert_builtin("PE_MAGIC",  Constant::ThirtyTwo(0x5045_0000));
+
+
+        // PNG stuff
+        // https://www.w3.org/TR/png/#5PNG-file-signature
+
+        // PNG file signature, which is eight bytes long, so it is split in two
+        map.insert_builtin("PNG_MAGIC",      Constant::ThirtyTwo(0x8950_4E47));
+        map.insert_builtin("PNG_MAGIC_END",  Constant::ThirtyTwo(0x0D0A_1A0A));
+
+
+        // Port stuff
+        // https://www.iana.org/assignments/service-names-port-numbers/service-names-port-numbers.xhtml
+
+        // Well-known TCP and UDP ports
+        map.insert_builtin("PORT_FTP",     Constant::Sixteen( 21));
+        map.insert_builtin("PORT_SSH",     Constant::Sixteen( 22));
+        map.insert_builtin("PORT_TELNET",  Constant::Sixteen( 23));
+        map.insert_builtin("PORT_SMTP",    Constant::Sixteen( 25));
+        map.insert_builtin("PORT_DNS",     Constant::Sixteen( 53));
+        map.insert_builtin("PORT_HTTP",    Constant::Sixteen( 80));
+        map.insert_builtin("PORT_POP3",    Constant::Sixteen(110));
+        map.insert_builtin("PORT_NTP",     Constant::Sixteen(123));
+        map.insert_builtin("PORT_IMAP",    Constant::Sixteen(143));
+        map.insert_builtin("PORT_SNMP",    Constant::Sixteen(161));
+        map.insert_builtin("PORT_BGP",     Constant::Sixteen(179));
+        map.insert_builtin("PORT_HTTPS",   Constant::Sixteen(443));
+        map.insert_builtin("PORT_IMAPS",   Constant::Sixteen(993));
+        map.insert_builtin("PORT_POP3S",   Constant::Sixteen(995));
 
 
         // TCP stuff
         // https://www.iana.org/assignments/tcp-parameters/tcp-parameters.xhtml
 
         // TCP flags
-        map.insert("TCP_FIN",  Constant::Sixteen(0x0001));
-        map.insert("TCP_SYN",  Constant::Sixteen(0x0002));
-        map.insert("TCP_RST",  Constant::Sixteen(0x0004));
-        map.insert("TCP_PSH",  Constant::Sixteen(0x0008));
-        map.insert("TCP_ACK",  Constant::Sixteen(0x0010));
-        map.insert("TCP_URG",  Constant::Sixteen(0x0020));
-        map.insert("TCP_ECN",  Constant::Sixteen(0x0040));
-        map.insert("TCP_CWR",  Constant::Sixteen(0x0080));
-
-        Self { map }
+        map.insert_builtin("TCP_FIN",  Constant::Sixteen(0x0001));
+        map.insert_builtin("TCP_SYN",  Constant::Sixteen(0x0002));
+        map.insert_builtin("TCP_RST",  Constant::Sixteen(0x0004));
+        map.insert_builtin("TCP_PSH",  Constant::Sixteen(0x0008));
+        map.insert_builtin("TCP_ACK",  Constant::Sixteen(0x0010));
+        map.insert_builtin("TCP_URG",  Constant::Sixteen(0x0020));
+        map.insert_builtin("TCP_ECN",  Constant::Sixteen(0x0040));
+        map.insert_builtin("TCP_CWR",  Constant::Sixteen(0x0080));
+
+        map
+    }
+
+    /// Adds a built-in constant to the table. Unlike constants added later,
+    /// the built-in names should never clash with one another, so this
+    /// checks that in debug builds rather than letting the last one win.
+    fn insert_builtin(&mut self, name: &'static str, value: Constant) {
+        let replaced = self.insert(name, value);
+        debug_assert!(replaced.is_none(), "Built-in constant {:?} is defined twice", name);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use alloc::vec;
+    use super::*;
+
+    #[test]
+    fn insert_new() {
+        let mut table = Table::empty();
+        assert_eq!(table.insert("MY_TYPE", Constant::Eight(7)), None);
+        assert_eq!(table.lookup("MY_TYPE"), Some(Constant::Eight(7)));
+    }
+
+    #[test]
+    fn eight_bit() {
+        assert_eq!(Constant::Eight(0xAB).bit_width(), 8);
+        assert_eq!(Constant::Eight(0xAB).value_u64(), 0xAB);
+    }
+
+    #[test]
+    fn sixteen_bit() {
+        assert_eq!(Constant::Sixteen(0xABCD).bit_width(), 16);
+        assert_eq!(Constant::Sixteen(0xABCD).value_u64(), 0xABCD);
+    }
+
+    #[test]
+    fn thirty_two_bit() {
+        assert_eq!(Constant::ThirtyTwo(0xFFFF_FFFF).bit_width(), 32);
+        assert_eq!(Constant::ThirtyTwo(0xFFFF_FFFF).value_u64(), 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn lookup_namespaced() {
+        let table = Table::builtin_set();
+        assert_eq!(table.lookup("dns.A"), Some(Constant::Sixteen(1)));
+        assert_eq!(table.lookup("png.MAGIC_END"), table.lookup("PNG_MAGIC_END"));
+        assert_eq!(table.lookup("dns.NOPE"), None);
+    }
+
+    #[test]
+    fn insert_over_builtin() {
+        let mut table = Table::builtin_set();
+        assert_eq!(table.insert(String::from("DNS_A"), Constant::Sixteen(99)), Some(Constant::Sixteen(1)));
+        assert_eq!(table.lookup("DNS_A"), Some(Constant::Sixteen(99)));
+        assert_eq!(table.all().filter(|(name, _)| *name == "DNS_A").count(), 1);
+    }
+
+    #[test]
+    fn builtins_sorted() {
+        let table = Table::builtin_set();
+        let names = table.all().map(|(name, _)| name).collect::<Vec<_>>();
+
+        let mut sorted_names = names.clone();
+        sorted_names.sort_unstable();
+        assert_eq!(names, sorted_names);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Built-in constant \"DNS_A\" is defined twice")]
+    fn builtin_defined_twice() {
+        let mut table = Table::builtin_set();
+        table.insert_builtin("DNS_A", Constant::Sixteen(1));
+    }
+
+    #[test]
+    fn builtin_widths_consistent() {
+        // a 16-bit constant can have a small value, such as ‘DNS_A’, because
+        // the field it goes in is 16 bits wide; but then every constant for
+        // that field should be 16 bits, so one that is the odd one out in its
+        // group has probably been given the wrong width
+        let table = Table::builtin_set();
+
+        for (name, value) in table.all() {
+            let group = name.split('_').next().unwrap();
+
+            for (other_name, other_value) in table.all().filter(|(n, _)| n.split('_').next() == Some(group)) {
+                assert_eq!(core::mem::discriminant(&value), core::mem::discriminant(&other_value),
+                           "Constants {:?} and {:?} have different widths", name, other_name);
+            }
+        }
+    }
+
+    #[test]
+    fn inserted_sorted() {
+        let mut table = Table::empty();
+        table.insert("MY_TYPE", Constant::Eight(7));
+        table.insert(String::from("ANOTHER_TYPE"), Constant::Eight(7));
+        table.insert("YET_ANOTHER_TYPE", Constant::Sixteen(8));
+        table.insert("MORE_TYPES", Constant::Eight(7));
+
+        assert_eq!(table.all().collect::<Vec<_>>(),
+                   vec![ ("ANOTHER_TYPE", Constant::Eight(7)), ("MORE_TYPES", Constant::Eight(7)),
+                         ("MY_TYPE", Constant::Eight(7)), ("YET_ANOTHER_TYPE", Constant::Sixteen(8)) ]);
+        assert_eq!(table.names_for_value(Constant::Eight(7)).collect::<Vec<_>>(),
+                   vec![ "ANOTHER_TYPE", "MORE_TYPES", "MY_TYPE" ]);
     }
 }
@@ -10,41 +10,262 @@
 //! amounts of output (such as `x999(x999(x999(FF)))`, there is a customisable
 //! limit for how long the complete output is allowed to get.
 
-use std::fmt;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter::{FusedIterator, Zip};
+use core::ops::Range;
+use core::slice;
 
 use log::*;
 
 use crate::ast::*;
 use crate::constants::{Table, Constant};
+use crate::pos::Placed;
+use crate::{read, Program, Spans};
 
 
 /// Evaluates all the expressions in the iterator into a vector of bytes,
-/// returning an error if one occurs without processing the rest.
-pub fn evaluate_exps<'src>(exps: impl IntoIterator<Item=Exp<'src>>, constants: &Table, limit: Option<usize>) -> Result<Vec<u8>, Error<'src>> {
-    let evaluator = Evaluator { constants, limit };
+/// returning an error if one occurs without processing the rest. As well as
+/// the overall limit, there can be a limit on the output of each top-level
+/// expression, so one runaway expression can be told apart from a program
+/// that is merely large.
+///
+/// Errors from this function do not say where they occurred, as the
+/// expressions have no positions; programs get run using an `Evaluator`
+/// instead, so this is only used in tests.
+#[cfg(test)]
+pub fn evaluate_exps<'src>(exps: impl IntoIterator<Item=Exp<'src>>, constants: &Table, limit: Option<usize>, line_limit: Option<usize>) -> Result<Vec<u8>, Error<'src>> {
+    let evaluator = Evaluator::new(constants, limit, line_limit);
+    let exps = exps.into_iter().collect::<Vec<_>>();
     let mut bytes = Vec::new();
 
-    for exp in exps {
-        let val = evaluator.evaluate_exp(exp)?;
-        bytes.extend(val.eval_to_bytes()?);
+    for exp in &exps {
+        evaluator.estimate_top_level(exp)?;
+    }
+
+    for exp in &exps {
+        bytes.extend(evaluator.evaluate_top_level(exp, &mut None)?);
     }
 
     Ok(bytes)
 }
 
-/// The internal “evaluation environment”, which holds the values that get
-/// looked up during evaluation.
-struct Evaluator<'consts> {
+/// The most space that gets reserved for the output up front, however much
+/// the expressions are estimated to produce. Anything larger than this gets
+/// allocated as it’s produced, in case an error occurs before then.
+const MAX_RESERVED_CAPACITY: usize = 64 * 1024 * 1024;
+
+/// The amount of output that a repeat has to produce before linting warns
+/// about it. This is far more than most programs need, so a repeat this big
+/// is more likely to be a typo in the count than something intended.
+const LARGE_REPEAT_THRESHOLD: usize = 1024 * 1024;
+
+/// The width of a fixed-width number, used when converting a raw number
+/// into bytes outside of a program. These are ordered narrowest first.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+pub enum Width {
+    Sixteen,
+    ThirtyTwo,
+    SixtyFour,
+}
+
+impl Width {
+
+    /// The number of bytes that a value of this width takes up.
+    fn byte_count(self) -> u8 {
+        match self {
+            Self::Sixteen    => 2,
+            Self::ThirtyTwo  => 4,
+            Self::SixtyFour  => 8,
+        }
+    }
+
+    /// Converts the given number, which must fit in this width, into a
+    /// value of this width.
+    #[allow(clippy::cast_possible_truncation)]
+    fn value_of(self, num: u64) -> MultiByteValue {
+        match self {
+            Self::Sixteen    => MultiByteValue::Sixteen(num as u16),
+            Self::ThirtyTwo  => MultiByteValue::ThirtyTwo(num as u32),
+            Self::SixtyFour  => MultiByteValue::SixtyFour(num),
+        }
+    }
+
+    /// Converts the given number, which must fit in this width, into a
+    /// vector of bytes in the given order.
+    fn bytes_of(self, num: u64, endianness: Endianness) -> Vec<u8> {
+        let count = usize::from(self.byte_count());
+
+        match endianness {
+            Endianness::Big     => num.to_be_bytes()[8 - count ..].to_vec(),
+            Endianness::Little  => num.to_le_bytes()[.. count].to_vec(),
+        }
+    }
+}
+
+/// The order that the bytes of a fixed-width number get written in.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// Splits the name of a multi-byte function into the width and endianness
+/// of the values it produces.
+fn width_and_endianness(mbt: MultiByteType) -> (Width, Endianness) {
+    match mbt {
+        MultiByteType::Be16  => (Width::Sixteen,   Endianness::Big),
+        MultiByteType::Be32  => (Width::ThirtyTwo, Endianness::Big),
+        MultiByteType::Be64  => (Width::SixtyFour, Endianness::Big),
+        MultiByteType::Le16  => (Width::Sixteen,   Endianness::Little),
+        MultiByteType::Le32  => (Width::ThirtyTwo, Endianness::Little),
+        MultiByteType::Le64  => (Width::SixtyFour, Endianness::Little),
+    }
+}
+
+/// Splits the name of a fixed-point function into the width and endianness
+/// of the values it produces, and how many of their bits are fractional.
+fn fixed_point_format(fpt: FixedPointType) -> (Width, Endianness, u32) {
+    match fpt {
+        FixedPointType::Q8_8Be    => (Width::Sixteen,   Endianness::Big,     8),
+        FixedPointType::Q8_8Le    => (Width::Sixteen,   Endianness::Little,  8),
+        FixedPointType::Q16_16Be  => (Width::ThirtyTwo, Endianness::Big,    16),
+        FixedPointType::Q16_16Le  => (Width::ThirtyTwo, Endianness::Little, 16),
+    }
+}
+
+/// Encodes a number as unsigned LEB128, seven bits at a time with the least
+/// significant group first, setting the top bit of every byte but the last.
+fn leb128(mut num: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let group = u8::try_from(num & 0x7F).unwrap();
+        num >>= 7;
+
+        if num == 0 {
+            bytes.push(group);
+            return bytes;
+        }
+
+        bytes.push(group | 0x80);
+    }
+}
+
+/// Maps a signed number onto an unsigned one so that numbers close to zero
+/// stay small, going 0, −1, 1, −2, 2, and so on, as Protocol Buffers does
+/// for its signed varints.
+fn zigzag(num: i64) -> u64 {
+    if num >= 0 {
+        u64::try_from(num).unwrap() * 2
+    }
+    else {
+        u64::try_from(-(num + 1)).unwrap() * 2 + 1
+    }
+}
+
+/// Converts a decimal number into bytes of the given width, returning both
+/// the big-endian and little-endian forms (in that order), or an error if
+/// the number does not fit. These are the same bytes that the `be` and `le`
+/// functions would produce, so tooling can show them side by side.
+pub fn number_to_both(s: &str, width: Width) -> Result<(Vec<u8>, Vec<u8>), Error<'_>> {
+    let num = parse_raw_number_at(s, width)?;
+    Ok((width.bytes_of(num, Endianness::Big), width.bytes_of(num, Endianness::Little)))
+}
+
+/// Parses a raw decimal number that must fit in the given width, returning
+/// it widened to 64 bits, or an error if it is too big.
+fn parse_raw_number_at(s: &str, width: Width) -> Result<u64, Error<'_>> {
+    match width {
+        Width::Sixteen    => parse_raw_number::<u16>(s).map(u64::from),
+        Width::ThirtyTwo  => parse_raw_number::<u32>(s).map(u64::from),
+        Width::SixtyFour  => parse_raw_number::<u64>(s),
+    }
+}
+
+/// Parses a raw decimal number into a number of the type required by the
+/// caller, returning an error if it is too big to fit.
+fn parse_raw_number<N>(s: &str) -> Result<N, Error<'_>>
+where N: core::str::FromStr,
+      N::Err: fmt::Display,
+{
+    s.parse().map_err(|e| {
+        warn!("Parse error: {}", e);
+        Error::TooBigDecimal(LargeNumber::FoundRawNumber(s))
+    })
+}
+
+/// Parses a raw floating-point number into a float of the type required by
+/// the caller, returning an error if it cannot be represented.
+fn parse_raw_float<F>(s: &str) -> Result<F, Error<'_>>
+where F: core::str::FromStr,
+      F::Err: fmt::Display,
+{
+    s.parse().map_err(|e| {
+        warn!("Parse error: {}", e);
+        Error::TooBigDecimal(LargeNumber::FoundRawFloat(s))
+    })
+}
+
+/// Something that happened while a program was being evaluated, which gets
+/// passed to the hook given to `Evaluator::evaluate_with_hook`.
+#[derive(PartialEq, Debug)]
+pub enum EvalEvent<'e, 'src> {
+
+    /// An expression is about to be evaluated. This happens for every
+    /// expression, including the ones nested inside functions and groups.
+    Expression(&'e Exp<'src>),
+
+    /// A function is about to be run with the given arguments, which have not
+    /// been evaluated yet.
+    Function {
+        name: FunctionName,
+        args: &'e [Exp<'src>],
+    },
+
+    /// A top-level expression has been evaluated, and its bytes are going to
+    /// take up the given range of the output.
+    Output {
+        position: Placed<&'src str>,
+        range: Range<usize>,
+    },
+}
+
+/// The function that gets told about each step of evaluation, if there is
+/// one. It gets passed around mutably, as it can keep its own state.
+type Hook<'h, 'src> = Option<&'h mut dyn FnMut(EvalEvent<'_, 'src>)>;
+
+/// Tells the hook about something that happened during evaluation, if there
+/// is a hook to tell.
+fn notify<'src>(hook: &mut Hook<'_, 'src>, event: EvalEvent<'_, 'src>) {
+    if let Some(hook) = hook {
+        hook(event);
+    }
+}
+
+/// The “evaluation environment”, which holds the constants that get looked
+/// up while running a program, along with the limits on its output. It
+/// only borrows the programs it runs, so the same program can be run more
+/// than once, and the same evaluator can run any number of programs.
+pub struct Evaluator<'consts> {
     constants: &'consts Table,
     limit: Option<usize>,
+    line_limit: Option<usize>,
 }
 
 /// A “value in flight”. Even though Hexit produces bytes as its output, it
 /// still has to deal with values before the width of the output type is
 /// known. This is similar to the `{integer}` or `{float}` types in Rust, as
 /// opposed to `u32` or `f64`.
+///
+/// Embedders can get at the values that a program’s top-level expressions
+/// evaluate to, before they get turned into bytes, with `Program::run_values`.
+/// More kinds of value may be added, so matches on it need a wildcard arm.
 #[derive(PartialEq, Debug)]
-enum Value<'src> {
+#[non_exhaustive]
+pub enum Value<'src> {
 
     /// A value that is known to be an individual byte. This can be printed
     /// directly, passed to a bitwise or repeat function, or made wider.
@@ -62,6 +283,12 @@ enum Value<'src> {
     /// wider.
     MultiByte(MultiByteValue),
 
+    /// A number that has been given a width and an endianness, by one of
+    /// the `be` or `le` functions. This acts like a series of bytes in that
+    /// order everywhere, except that the `gray` function can still get at the
+    /// number itself.
+    Ordered(MultiByteValue, Endianness),
+
     /// A numeric value where the width is not yet known. This cannot be
     /// printed directly (as the size and endianness is not known) nor passed
     /// to bitwise or repeat functions, but can be given a width and
@@ -84,17 +311,229 @@ pub enum MultiByteValue {
     SixtyFour(u64),
 }
 
+impl MultiByteValue {
+
+    /// Returns the width of this value.
+    pub fn width(self) -> Width {
+        match self {
+            Self::Sixteen(_)    => Width::Sixteen,
+            Self::ThirtyTwo(_)  => Width::ThirtyTwo,
+            Self::SixtyFour(_)  => Width::SixtyFour,
+        }
+    }
+
+    /// Returns this value as a 64-bit number, which every width fits in.
+    pub fn widened(self) -> u64 {
+        match self {
+            Self::Sixteen(o2)    => u64::from(o2),
+            Self::ThirtyTwo(o4)  => u64::from(o4),
+            Self::SixtyFour(o8)  => o8,
+        }
+    }
+
+    /// Converts this value to its Gray code, keeping its width.
+    fn gray_code(self) -> Self {
+        match self {
+            Self::Sixteen(o2)    => Self::Sixteen(o2 ^ (o2 >> 1)),
+            Self::ThirtyTwo(o4)  => Self::ThirtyTwo(o4 ^ (o4 >> 1)),
+            Self::SixtyFour(o8)  => Self::SixtyFour(o8 ^ (o8 >> 1)),
+        }
+    }
+}
+
 
 impl<'consts> Evaluator<'consts> {
 
+    /// Creates a new evaluator that looks up constants in the given table.
+    /// The output can be limited overall, and for each top-level expression.
+    pub fn new(constants: &'consts Table, limit: Option<usize>, line_limit: Option<usize>) -> Self {
+        Self { constants, limit, line_limit }
+    }
+
+    /// Runs the given program, returning the vector of bytes that it has
+    /// produced, or an evaluation error along with the position of the
+    /// expression that caused it.
+    pub fn evaluate<'src>(&self, program: &Program<'src>) -> Result<Vec<u8>, LocatedError<'src>> {
+        let (bytes, _) = self.evaluate_with_spans(program)?;
+        Ok(bytes)
+    }
+
+    /// Runs the given program in the same way as `evaluate`, also returning
+    /// the range of the output that each top-level expression produced,
+    /// along with the position in the source that the expression came from.
+    pub fn evaluate_with_spans<'src>(&self, program: &Program<'src>) -> Result<(Vec<u8>, Spans<'src>), LocatedError<'src>> {
+        self.run_program(program, &mut None)
+    }
+
+    /// Runs the given program in the same way as `evaluate`, calling the
+    /// given function for each expression that gets evaluated, each function
+    /// that gets run, and each range of the output that a top-level
+    /// expression produces, so that tools can trace what the program does.
+    /// The expressions always get evaluated in order, one at a time.
+    pub fn evaluate_with_hook<'src>(&self, program: &Program<'src>, hook: &mut dyn FnMut(EvalEvent<'_, 'src>)) -> Result<Vec<u8>, LocatedError<'src>> {
+        let (bytes, _) = self.run_program(program, &mut Some(hook))?;
+        Ok(bytes)
+    }
+
+    /// Runs the given program, returning its bytes along with the range of
+    /// the output that each top-level expression produced, and telling the
+    /// hook about each step, if there is one.
+    fn run_program<'src>(&self, program: &Program<'src>, hook: &mut Hook<'_, 'src>) -> Result<(Vec<u8>, Spans<'src>), LocatedError<'src>> {
+        debug!("Running expressions → {:#?}", program.exps);
+
+        let placed_exps = program.exps.iter().zip(program.positions.iter().copied()).collect::<Vec<_>>();
+        let mut spans = Vec::with_capacity(placed_exps.len());
+
+        // Reject any expression that is sure to hit a limit before evaluating
+        // anything, so nested repeats don’t get to allocate their inner bytes,
+        // and add up the estimates to know how much space to reserve
+        let mut estimate = 0_usize;
+        for &(exp, position) in &placed_exps {
+            let length = self.estimate_top_level(exp).map_err(|error| LocatedError::new(error, position))?;
+            estimate = estimate.saturating_add(length);
+        }
+
+        let capacity = estimate.min(self.limit.unwrap_or(MAX_RESERVED_CAPACITY)).min(MAX_RESERVED_CAPACITY);
+        let mut bytes = Vec::with_capacity(capacity);
+
+        // a hook has to see the expressions in order, so it stops them from
+        // being evaluated in parallel
+        #[cfg(feature = "parallel")]
+        let chunks = if hook.is_some() { self.evaluate_in_order(&placed_exps, hook)? } else { self.evaluate_in_parallel(&placed_exps)? };
+
+        #[cfg(not(feature = "parallel"))]
+        let chunks = self.evaluate_in_order(&placed_exps, hook)?;
+
+        for (exp_bytes, position) in chunks.into_iter().zip(program.positions.iter().copied()) {
+            let start = bytes.len();
+            bytes.extend(exp_bytes);
+            spans.push((position, start .. bytes.len()));
+        }
+
+        Ok((bytes, spans))
+    }
+
+    /// Runs the given program, returning the value that each top-level
+    /// expression evaluated to, without turning any of them into bytes. The
+    /// limits get checked against the widths of the values that have one.
+    pub fn evaluate_values<'src>(&self, program: &Program<'src>) -> Result<Vec<Value<'src>>, LocatedError<'src>> {
+        let mut values = Vec::with_capacity(program.exps.len());
+
+        for (exp, &position) in program.exps.iter().zip(program.positions.iter()) {
+            let value = self.estimate_top_level(exp)
+                            .and_then(|_| self.evaluate_exp(exp, &mut None))
+                            .and_then(|value| match value.byte_width() {
+                                Some(width)  => self.check_length(width).map(|()| value),
+                                None         => Ok(value),
+                            })
+                            .map_err(|error| LocatedError::new(error, position))?;
+
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    /// Checks the given program for anything that looks like a mistake but
+    /// that can only be found with the constants and limits to hand, without
+    /// running it. At the moment, this is any repeat that would produce
+    /// more than a mebibyte of output while still being under the limits.
+    pub fn lint<'src>(&self, program: &Program<'src>) -> Vec<read::Warning<'src>> {
+        program.exps.iter().filter_map(|exp| {
+            self.find_large_repeat(exp).map(|(position, length)| read::Warning::LargeRepeat { position, length })
+        }).collect()
+    }
+
+    /// Searches the given expression for the outermost repeat that is
+    /// estimated to produce more output than the lint threshold, returning
+    /// its position and estimated length. Repeats that would hit a limit are
+    /// left alone, as running the program reports those anyway.
+    fn find_large_repeat<'src>(&self, exp: &Exp<'src>) -> Option<(Placed<&'src str>, usize)> {
+        match exp {
+            Exp::Function { name: FunctionName::Repeat(_) | FunctionName::RepeatDynamic { .. } | FunctionName::Joined { .. }, position, args } => {
+                match self.estimate_length(exp) {
+                    Ok(length) if length > LARGE_REPEAT_THRESHOLD  => Some((*position, length)),
+                    Ok(_)                                          => args.iter().find_map(|arg| self.find_large_repeat(arg)),
+                    Err(_)                                         => None,
+                }
+            }
+            Exp::Function { args, .. } | Exp::Group(args) => {
+                args.iter().find_map(|arg| self.find_large_repeat(arg))
+            }
+            _ => None,
+        }
+    }
+
+    /// Turns this evaluator into an iterator over the bytes that the given
+    /// program produces, which only evaluates each top-level expression once
+    /// the bytes from the one before it have been used up.
+    pub fn into_bytes_iter<'prog, 'src>(self, program: &'prog Program<'src>) -> BytesIter<'prog, 'consts, 'src> {
+        let placed_exps = program.exps.iter().zip(program.positions.iter());
+        let current = Vec::new().into_iter();
+        BytesIter { evaluator: self, placed_exps, current, failed: false }
+    }
+
+    /// Evaluates each top-level expression into its own chunk of bytes, one
+    /// after the other, stopping at the first error. The hook, if there is
+    /// one, gets told about the range of the output each chunk will take up.
+    fn evaluate_in_order<'src>(&self, placed_exps: &[(&Exp<'src>, Placed<&'src str>)], hook: &mut Hook<'_, 'src>) -> Result<Vec<Vec<u8>>, LocatedError<'src>> {
+        let mut chunks = Vec::with_capacity(placed_exps.len());
+        let mut offset = 0;
+
+        for &(exp, position) in placed_exps {
+            let exp_bytes = self.evaluate_top_level(exp, hook).map_err(|error| LocatedError::new(error, position))?;
+            let range = offset .. offset + exp_bytes.len();
+            offset = range.end;
+
+            notify(hook, EvalEvent::Output { position, range });
+            chunks.push(exp_bytes);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Evaluates each top-level expression into its own chunk of bytes, in
+    /// parallel, as none of them depend on each other. Every expression gets
+    /// evaluated before any errors are looked at, so that the error returned
+    /// is always the earliest one, just as if they were evaluated in order.
+    /// The limits only apply to single expressions, so they need no sharing
+    /// between threads.
+    #[cfg(feature = "parallel")]
+    fn evaluate_in_parallel<'src>(&self, placed_exps: &[(&Exp<'src>, Placed<&'src str>)]) -> Result<Vec<Vec<u8>>, LocatedError<'src>> {
+        use rayon::prelude::*;
+
+        let results = placed_exps.par_iter()
+            .map(|&(exp, position)| self.evaluate_top_level(exp, &mut None).map_err(|error| LocatedError::new(error, position)))
+            .collect::<Vec<_>>();
+
+        results.into_iter().collect()
+    }
+
+    /// Evaluates a top-level expression all the way into bytes, checking
+    /// that there aren’t too many of them.
+    fn evaluate_top_level<'src>(&self, exp: &Exp<'src>, hook: &mut Hook<'_, 'src>) -> Result<Vec<u8>, Error<'src>> {
+        let exp_bytes = self.evaluate_to_bytes(exp, hook)?;
+        self.check_length(exp_bytes.len())?;
+        Ok(exp_bytes)
+    }
+
+    /// Evaluates this expression and converts the result into bytes. A
+    /// 16-bit constant cannot be converted, as it has no endianness, so the
+    /// error names the constant, as its value alone would be confusing.
+    fn evaluate_to_bytes<'src>(&self, exp: &Exp<'src>, hook: &mut Hook<'_, 'src>) -> Result<Vec<u8>, Error<'src>> {
+        let value = self.evaluate_exp(exp, hook)?;
+        value_to_bytes(exp, value)
+    }
+
     /// Evaluates this expression by converting it into a “value in flight”,
     /// which possibly involves evaluating the expression’s sub-expressions.
-    fn evaluate_exp<'src>(&self, exp: Exp<'src>) -> Result<Value<'src>, Error<'src>> {
+    fn evaluate_exp<'src>(&self, exp: &Exp<'src>, hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
         trace!("Evaluating expression → {:#?}", exp);
+        notify(hook, EvalEvent::Expression(exp));
 
         match exp {
             Exp::Char(byte) => {
-                Ok(Value::Byte(byte))
+                Ok(Value::Byte(*byte))
             }
 
             Exp::Dec(number) => {
@@ -102,26 +541,28 @@ impl<'consts> Evaluator<'consts> {
             }
 
             Exp::Constant { name } => {
-                match self.constants.lookup(name) {
+                match self.constants.lookup(name.contents) {
                     Some(Constant::Eight(num)) => {
                         Ok(Value::Byte(num))
                     }
                     Some(Constant::Sixteen(num)) => {
                         Ok(Value::MultiByte(MultiByteValue::Sixteen(num)))
                     }
+                    Some(Constant::ThirtyTwo(num)) => {
+                        Ok(Value::MultiByte(MultiByteValue::ThirtyTwo(num)))
+                    }
                     None => {
-                        return Err(Error::UnknownConstant(name));
+                        return Err(Error::UnknownConstant(*name));
                     }
                 }
             }
 
-            Exp::Function { name, args } => {
-                self.run_function(name, args)
+            Exp::Function { name, args, .. } => {
+                self.run_function(*name, args, hook)
             }
 
-            Exp::StringLiteral { chars } => {
-                let bytes = chars.bytes().collect::<Vec<_>>();
-                Ok(Value::VariableBytes(bytes))
+            Exp::StringLiteral { bytes } => {
+                Ok(Value::VariableBytes(bytes.to_vec()))
             }
 
             Exp::IPv4 { bytes } => {
@@ -132,8 +573,40 @@ impl<'consts> Evaluator<'consts> {
                 Ok(Value::VariableBytes(bytes.to_vec()))
             }
 
-            Exp::Timestamp(unix_time) => {
-                Ok(Value::MultiByte(MultiByteValue::ThirtyTwo(unix_time)))
+            Exp::Typed { bytes } => {
+                Ok(Value::VariableBytes(bytes.clone()))
+            }
+
+            Exp::Group(exps) => {
+                // a group of only a ‘be’ or ‘le’ number keeps its width and
+                // endianness, as its bytes would be the same either way
+                if let [ exp ] = &exps[..] {
+                    return match self.evaluate_exp(exp, hook)? {
+                        ordered @ Value::Ordered(..)  => Ok(ordered),
+                        value                         => value_to_bytes(exp, value).map(Value::VariableBytes),
+                    };
+                }
+
+                let mut bytes = Vec::<u8>::new();
+
+                for exp in exps {
+                    let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
+                    bytes.extend(&sub_bytes);
+                }
+
+                Ok(Value::VariableBytes(bytes))
+            }
+
+            Exp::Timestamp { time, unit: TimeUnit::Seconds } => {
+                // timestamps in seconds only need 64 bits after 2106
+                match u32::try_from(*time) {
+                    Ok(time)  => Ok(Value::MultiByte(MultiByteValue::ThirtyTwo(time))),
+                    Err(_)    => Ok(Value::MultiByte(MultiByteValue::SixtyFour(*time))),
+                }
+            }
+
+            Exp::Timestamp { time, .. } => {
+                Ok(Value::MultiByte(MultiByteValue::SixtyFour(*time)))
             }
 
             Exp::Float(number) => {
@@ -144,8 +617,8 @@ impl<'consts> Evaluator<'consts> {
                 if bit_vec.len() <= 8 {
                     let mut num = 0_u8;
 
-                    for (index, bit) in bit_vec.into_iter().rev().enumerate() {
-                        if bit {
+                    for (index, bit) in bit_vec.iter().rev().enumerate() {
+                        if *bit {
                             num += 2_u8.pow(index as u32);
                         }
                     }
@@ -155,8 +628,8 @@ impl<'consts> Evaluator<'consts> {
                 else if bit_vec.len() <= 16 {
                     let mut num = 0_u16;
 
-                    for (index, bit) in bit_vec.into_iter().rev().enumerate() {
-                        if bit {
+                    for (index, bit) in bit_vec.iter().rev().enumerate() {
+                        if *bit {
                             num += 2_u16.pow(index as u32);
                         }
                     }
@@ -166,8 +639,8 @@ impl<'consts> Evaluator<'consts> {
                 else if bit_vec.len() <= 32 {
                     let mut num = 0_u32;
 
-                    for (index, bit) in bit_vec.into_iter().rev().enumerate() {
-                        if bit {
+                    for (index, bit) in bit_vec.iter().rev().enumerate() {
+                        if *bit {
                             num += 2_u32.pow(index as u32);
                         }
                     }
@@ -177,8 +650,8 @@ impl<'consts> Evaluator<'consts> {
                 else if bit_vec.len() <= 64 {
                     let mut num = 0_u64;
 
-                    for (index, bit) in bit_vec.into_iter().rev().enumerate() {
-                        if bit {
+                    for (index, bit) in bit_vec.iter().rev().enumerate() {
+                        if *bit {
                             num += 2_u64.pow(index as u32);
                         }
                     }
@@ -195,77 +668,111 @@ impl<'consts> Evaluator<'consts> {
     /// Runs the function with the given name, using the list of expressions
     /// as its arguments. The arguments have not yet been evaluated
     /// themselves, so that the number of arguments can first be checked.
-    fn run_function<'src>(&self, name: FunctionName, args: Vec<Exp<'src>>) -> Result<Value<'src>, Error<'src>> {
+    fn run_function<'src>(&self, name: FunctionName, args: &[Exp<'src>], hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
         trace!("Running function → {:?}", name);
         trace!("Function arguments → {:#?}", args);
+        notify(hook, EvalEvent::Function { name, args });
 
         match name {
-            FunctionName::MultiByte(MultiByteType::Be16) => {
+            FunctionName::MultiByte(mbt) => {
+                let (width, endianness) = width_and_endianness(mbt);
                 let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_two_variable_bytes(u16::to_be_bytes)
+                let val = self.evaluate_exp(arg, hook)?;
+                val.to_fixed_width(width, endianness)
             }
 
-            FunctionName::MultiByte(MultiByteType::Le16) => {
+            FunctionName::FixedPoint(fpt) => {
+                let (width, endianness, fraction_bits) = fixed_point_format(fpt);
                 let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_two_variable_bytes(u16::to_le_bytes)
+                let val = self.evaluate_exp(arg, hook)?;
+                let num = val.to_fixed_point(width, fraction_bits)?;
+                Ok(Value::VariableBytes(width.bytes_of(num, endianness)))
             }
 
-            FunctionName::MultiByte(MultiByteType::Be32) => {
-                let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_four_variable_bytes(u32::to_be_bytes, f32::to_be_bytes)
+            FunctionName::Repeat(amount) => {
+                self.run_repeat(amount, args, hook)
             }
 
-            FunctionName::MultiByte(MultiByteType::Le32) => {
-                let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_four_variable_bytes(u32::to_le_bytes, f32::to_le_bytes)
+            FunctionName::RepeatDynamic { allow_zero } => {
+                let mut args = args.iter();
+                let amount = match args.next() {
+                    Some(exp)  => self.evaluate_exp(exp, hook)?.eval_to_count()?,
+                    None       => return Err(Error::InvalidArgs(InvalidArgsError::NoArgumentsForRepeat)),
+                };
+
+                if amount == 0 && ! allow_zero {
+                    return Err(Error::InvalidArgs(InvalidArgsError::ZeroRepeatAmount));
+                }
+
+                let amount = RepeatAmount::try_from(amount)
+                    .map_err(|_| Error::InvalidArgs(InvalidArgsError::RepeatAmountTooLarge(amount)))?;
+
+                self.run_repeat(amount, args, hook)
             }
 
-            FunctionName::MultiByte(MultiByteType::Be64) => {
-                let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_eight_variable_bytes(u64::to_be_bytes, f64::to_be_bytes)
+            FunctionName::Joined { allow_zero } => {
+                self.run_joined(allow_zero, args, hook)
+            }
+
+            FunctionName::Fill => {
+                self.run_fill(args, hook)
+            }
+
+            FunctionName::Bits => {
+                self.run_bits(args, hook)
+            }
+
+            FunctionName::Zero => {
+                self.run_same_byte(0x00, args, hook)
+            }
+
+            FunctionName::Ones => {
+                self.run_same_byte(0xFF, args, hook)
             }
 
-            FunctionName::MultiByte(MultiByteType::Le64) => {
+            FunctionName::Gray => {
                 let arg = only_arg(args)?;
-                let val = self.evaluate_exp(arg)?;
-                val.to_eight_variable_bytes(u64::to_le_bytes, f64::to_le_bytes)
+                let val = self.evaluate_exp(arg, hook)?;
+                val.gray_code()
             }
 
-            FunctionName::Repeat(amount) => {
-                let mut bytes = Vec::new();
+            FunctionName::Parity => {
+                let mut bytes = Vec::<u8>::new();
 
                 for exp in args {
-                    let sub_bytes = self.evaluate_exp(exp)?.eval_to_bytes()?;
+                    let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
                     bytes.extend(&sub_bytes);
                 }
 
-                // Check whether this would hit the limit, because it’s
-                // possible for repeat functions to generate lots of
-                // output very quickly
-                if let Some(limit) = self.limit {
-                    if limit <= bytes.len() * usize::from(amount) {
-                        return Err(Error::TooMuchOutput);
-                    }
-                }
+                let set_bits = bytes.iter().map(|b| b.count_ones()).sum::<u32>();
+                bytes.push(u8::from(set_bits % 2 == 1));
 
-                let mut result_bytes = Vec::new();
-                for _ in 0 .. amount {
-                    result_bytes.extend(&bytes);
-                }
+                Ok(Value::VariableBytes(bytes))
+            }
+
+            FunctionName::Varint => {
+                let arg = only_arg(args)?;
+                let num = self.evaluate_exp(arg, hook)?.to_varint_number()?;
+                Ok(Value::VariableBytes(leb128(num)))
+            }
+
+            FunctionName::SignedVarint => {
+                let arg = only_arg(args)?;
+                let num = self.evaluate_exp(arg, hook)?.to_signed_varint_number()?;
+                Ok(Value::VariableBytes(leb128(zigzag(num))))
+            }
 
-                Ok(Value::VariableBytes(result_bytes))
+            FunctionName::Bcd => {
+                let arg = only_arg(args)?;
+                let val = self.evaluate_exp(arg, hook)?;
+                Ok(Value::VariableBytes(val.to_bcd()?))
             }
 
             FunctionName::Bitwise(bitwise_operator) => {
-                let mut iter = args.into_iter().map(|exp| self.evaluate_exp(exp));
+                let mut iter = args.iter().map(|exp| self.evaluate_exp(exp, hook));
                 let mut result = match iter.next() {
                     Some(val)  => val?,
-                    None       => return Err(Error::InvalidArgs(InvalidArgsError::NoArgumentsForBitwise)),
+                    None       => return bitwise_operator.empty_result(),
                 };
 
                 for next_val in iter {
@@ -280,7 +787,7 @@ impl<'consts> Evaluator<'consts> {
                 let mut bytes = Vec::<u8>::new();
 
                 for exp in args {
-                    let sub_bytes = self.evaluate_exp(exp)?.eval_to_bytes()?;
+                    let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
                     bytes.extend(&sub_bytes);
                 }
 
@@ -290,171 +797,473 @@ impl<'consts> Evaluator<'consts> {
 
                 Ok(Value::VariableBytes(bytes))
             }
-        }
-    }
-}
 
+            FunctionName::NibbleSwap => {
+                let mut bytes = Vec::<u8>::new();
 
-impl<'src> Value<'src> {
+                for exp in args {
+                    let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
+                    bytes.extend(&sub_bytes);
+                }
 
-    /// Converts this “value in flight” into a series of bytes, or return an
-    /// error if the conversion is not possible. This is used when printing
-    /// bytes at the top level, or converting values to sequences for a
-    /// repetition function.
-    fn eval_to_bytes(self) -> Result<Vec<u8>, Error<'src>> {
-        match self {
-            Self::Byte(byte) => {
-                Ok(vec![ byte ])
-            }
-            Self::VariableBytes(bytes) => {
-                Ok(bytes)
+                for b in &mut bytes {
+                    *b = b.rotate_left(4);
+                }
+
+                Ok(Value::VariableBytes(bytes))
             }
-            Self::MultiByte(v) => {
-                Err(Error::TopLevelBigDecimal(LargeNumber::Known(v)))
+
+            FunctionName::Neg => {
+                let arg = only_arg(args)?;
+                let val = self.evaluate_exp(arg, hook)?;
+                val.negate()
             }
-            Self::RawNumber(s) => {
-                match s.parse() {
-                    Ok(v) => {
-                        Ok(vec![ v ])
-                    }
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                        Err(Error::TopLevelBigDecimal(LargeNumber::FoundRawNumber(s)))
-                    }
+
+            FunctionName::PopCount => {
+                // Counted in a u64 so that no amount of input can overflow
+                // the accumulator before it gets checked against a byte.
+                let mut count = 0_u64;
+
+                for exp in args {
+                    let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
+                    count += sub_bytes.iter().map(|b| u64::from(b.count_ones())).sum::<u64>();
+                }
+
+                // It takes more than 31 bytes with every bit set to produce
+                // a count that does not fit in a byte.
+                match u8::try_from(count) {
+                    Ok(byte) => Ok(Value::Byte(byte)),
+                    Err(_)   => Err(Error::InvalidArgs(InvalidArgsError::PopCountTooLarge(count))),
                 }
             }
-            Self::RawFloat(s) => {
-                Err(Error::TopLevelBigDecimal(LargeNumber::FoundRawFloat(s)))
+
+            FunctionName::LeadingZeros => {
+                let arg = only_arg(args)?;
+                let val = self.evaluate_exp(arg, hook)?;
+                val.count_zeros("clz", u8::leading_zeros, u16::leading_zeros, u32::leading_zeros, u64::leading_zeros)
+            }
+
+            FunctionName::TrailingZeros => {
+                let arg = only_arg(args)?;
+                let val = self.evaluate_exp(arg, hook)?;
+                val.count_zeros("ctz", u8::trailing_zeros, u16::trailing_zeros, u32::trailing_zeros, u64::trailing_zeros)
             }
         }
     }
 
-    /// Converts this “value in flight” into a 2-byte value, using the given
-    /// function to perform the conversion with a certain endianness, or
-    /// return an error if the conversion is not possible. This is used when
-    /// passing a value to the `be16` or `le16` functions. Values cannot be
-    /// made more narrow.
-    fn to_two_variable_bytes(self, endianify: impl Fn(u16) -> [u8; 2]) -> Result<Self, Error<'src>> {
-        let bytes = match self {
-            Self::Byte(b) => {
-                endianify(u16::from(b))
+    /// Checks whether producing the given number of bytes would hit either
+    /// the overall limit or the per-expression limit, returning an error if
+    /// so. The overall limit is checked first, as it’s the one that would
+    /// be hit even without the per-expression limit.
+    fn check_length<'src>(&self, length: usize) -> Result<(), Error<'src>> {
+        if let Some(limit) = self.limit {
+            if limit <= length {
+                return Err(Error::TooMuchOutput);
             }
-            Self::VariableBytes(bytes) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::VariableToFixed(bytes, 2)));
+        }
+
+        if let Some(line_limit) = self.line_limit {
+            if line_limit <= length {
+                return Err(Error::LineTooLong(line_limit));
             }
-            Self::MultiByte(MultiByteValue::Sixteen(o2)) => {
-                endianify(o2)
+        }
+
+        Ok(())
+    }
+
+    /// Estimates the length of the given top-level expression, returning an
+    /// error if it’s sure to hit either limit, without evaluating it.
+    fn estimate_top_level<'src>(&self, exp: &Exp<'src>) -> Result<usize, Error<'src>> {
+        let length = self.estimate_length(exp)?;
+        self.check_length(length)?;
+        Ok(length)
+    }
+
+    /// Works out the fewest bytes that the given expression can produce,
+    /// checking the result of each repetition against the limits just as
+    /// evaluating it would. Amounts that are only known after evaluation
+    /// count as zero, so the estimate never goes over the real length.
+    fn estimate_length<'src>(&self, exp: &Exp<'src>) -> Result<usize, Error<'src>> {
+        match exp {
+            Exp::Char(_)                  => Ok(1),
+            Exp::StringLiteral { bytes }  => Ok(bytes.len()),
+            Exp::IPv4 { bytes }           => Ok(bytes.len()),
+            Exp::IPv6 { bytes }           => Ok(bytes.len()),
+            Exp::Typed { bytes }          => Ok(bytes.len()),
+            Exp::Group(exps)              => self.estimate_sum(exps),
+            Exp::Function { name, args, .. }  => self.estimate_function(*name, args),
+
+            // the width of these depends on where they get used
+            Exp::Dec(_) | Exp::Float(_) | Exp::Constant { .. } | Exp::Timestamp { .. } | Exp::Bits(_) => Ok(0),
+        }
+    }
+
+    /// Estimates the length of each expression, adding them all up.
+    fn estimate_sum<'src>(&self, exps: &[Exp<'src>]) -> Result<usize, Error<'src>> {
+        let mut total = 0_usize;
+
+        for exp in exps {
+            total = total.saturating_add(self.estimate_length(exp)?);
+        }
+
+        Ok(total)
+    }
+
+    /// Estimates the length of the output of a function, using its
+    /// arguments’ estimates.
+    fn estimate_function<'src>(&self, name: FunctionName, args: &[Exp<'src>]) -> Result<usize, Error<'src>> {
+        let (first, rest) = match args.split_first() {
+            Some((first, rest))  => (Some(first), rest),
+            None                 => (None, args),
+        };
+
+        let length = match name {
+            FunctionName::MultiByte(mbt) => {
+                self.estimate_sum(args)?;
+                usize::from(width_and_endianness(mbt).0.byte_count())
             }
-            Self::MultiByte(MultiByteValue::ThirtyTwo(o4)) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::CannotNarrow4to2(o4)));
+            FunctionName::FixedPoint(fpt) => {
+                self.estimate_sum(args)?;
+                usize::from(fixed_point_format(fpt).0.byte_count())
             }
-            Self::MultiByte(MultiByteValue::SixtyFour(o8)) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::CannotNarrow8to2(o8)));
+            FunctionName::Repeat(amount) => {
+                self.estimate_sum(args)?.saturating_mul(usize::from(amount))
             }
-            Self::RawNumber(s) => {
-                match s.parse() {
-                    Ok(num) => endianify(num),
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                        return Err(Error::TooBigDecimal(LargeNumber::FoundRawNumber(s)));
-                    }
-                }
+            FunctionName::RepeatDynamic { .. } => {
+                let amount = first.and_then(|exp| self.static_count(exp)).unwrap_or(0);
+                self.estimate_sum(rest)?.saturating_mul(amount)
             }
-            Self::RawFloat(s) => {
-                return Err(Error::TooBigDecimal(LargeNumber::FoundRawFloat(s)));
+            FunctionName::Joined { .. } => {
+                let amount = first.and_then(|exp| self.static_count(exp)).unwrap_or(0);
+                let (separator, pattern) = match rest.split_first() {
+                    Some((separator, pattern))  => (self.estimate_length(separator)?, self.estimate_sum(pattern)?),
+                    None                        => (0, 0),
+                };
+                joined_length(pattern, separator, amount)
             }
-        };
-
-        Ok(Value::VariableBytes(bytes.to_vec()))
-    }
-
-    /// Converts this “value in flight” into a 4-byte value, using the given
-    /// function to perform the conversion with a certain endianness, or
-    /// return an error if the conversion is not possible. This is used when
-    /// passing a value to the `be32` or `le32` functions. Values cannot be
-    /// made more narrow.
-    fn to_four_variable_bytes(self, endianify: impl Fn(u32) -> [u8; 4], flendianify: impl Fn(f32) -> [u8; 4]) -> Result<Self, Error<'src>> {
-        let bytes = match self {
-            Self::Byte(b) => {
-                endianify(u32::from(b))
+            FunctionName::Fill | FunctionName::Zero | FunctionName::Ones => {
+                self.estimate_sum(rest)?;
+                first.and_then(|exp| self.static_count(exp)).unwrap_or(0)
             }
-            Self::VariableBytes(bytes) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::VariableToFixed(bytes, 4)));
+            FunctionName::Parity => {
+                self.estimate_sum(args)?.saturating_add(1)
             }
-            Self::MultiByte(MultiByteValue::Sixteen(o2)) => {
-                endianify(u32::from(o2))
+            FunctionName::BitwiseNot | FunctionName::NibbleSwap => {
+                self.estimate_sum(args)?
             }
-            Self::MultiByte(MultiByteValue::ThirtyTwo(o4)) => {
-                endianify(o4)
+            FunctionName::PopCount | FunctionName::LeadingZeros | FunctionName::TrailingZeros => {
+                self.estimate_sum(args)?;
+                1
             }
-            Self::MultiByte(MultiByteValue::SixtyFour(o8)) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::CannotNarrow8to4(o8)));
+            FunctionName::Varint | FunctionName::SignedVarint | FunctionName::Bcd => {
+                // at least one byte, and the rest is only known later
+                self.estimate_sum(args)?;
+                1
             }
-            Self::RawNumber(s) => {
-                match s.parse() {
-                    Ok(num) => endianify(num),
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                        return Err(Error::TooBigDecimal(LargeNumber::FoundRawNumber(s)));
+            FunctionName::Bits | FunctionName::Bitwise(_) | FunctionName::Gray | FunctionName::Neg => {
+                self.estimate_sum(args)?;
+                0
+            }
+        };
+
+        self.check_length(length)?;
+        Ok(length)
+    }
+
+    /// Returns the amount that the given expression would evaluate to, if
+    /// it can be known without evaluating anything.
+    fn static_count(&self, exp: &Exp<'_>) -> Option<usize> {
+        match exp {
+            Exp::Char(byte)   => Some(usize::from(*byte)),
+            Exp::Dec(number)  => number.parse().ok(),
+            Exp::Constant { name } => {
+                match self.constants.lookup(name.contents)? {
+                    Constant::Eight(num)      => Some(usize::from(num)),
+                    Constant::Sixteen(num)    => Some(usize::from(num)),
+                    Constant::ThirtyTwo(num)  => usize::try_from(num).ok(),
+                }
+            }
+            _                 => None,
+        }
+    }
+
+    /// Runs a repeat function, evaluating each of the expressions into bytes
+    /// and repeating the lot the given number of times.
+    fn run_repeat<'a, 'src: 'a>(&self, amount: RepeatAmount, args: impl IntoIterator<Item=&'a Exp<'src>>, hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
+        let mut bytes = Vec::new();
+
+        for exp in args {
+            bytes.extend(self.evaluate_to_bytes(exp, hook)?);
+        }
+
+        // Check whether this would hit the limit, because it’s
+        // possible for repeat functions to generate lots of
+        // output very quickly
+        let length = bytes.len().saturating_mul(usize::from(amount));
+        self.check_length(length)?;
+
+        // The repetitions get copied from the ones already made, in the
+        // same buffer, doubling them each time, so the bytes are never
+        // held twice
+        if amount == 0 {
+            bytes.clear();
+        }
+        else {
+            bytes.reserve_exact(length - bytes.len());
+            while bytes.len() < length {
+                let copy_length = bytes.len().min(length - bytes.len());
+                bytes.extend_from_within(.. copy_length);
+            }
+        }
+
+        Ok(Value::VariableBytes(bytes))
+    }
+
+    /// Runs the `joined` function, which repeats the bytes of every argument
+    /// after the second the number of times given by the first argument,
+    /// putting the bytes of the second argument in between each repetition
+    /// (but not before the first or after the last).
+    fn run_joined<'src>(&self, allow_zero: bool, args: &[Exp<'src>], hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
+        if args.len() < 2 {
+            return Err(Error::InvalidArgs(InvalidArgsError::NotEnoughArgumentsForJoined));
+        }
+
+        let (amount, separator, pattern_exps) = (&args[0], &args[1], &args[2 ..]);
+
+        let amount = self.evaluate_exp(amount, hook)?.eval_to_count()?;
+        if amount == 0 && ! allow_zero {
+            return Err(Error::InvalidArgs(InvalidArgsError::ZeroRepeatAmount));
+        }
+
+        if RepeatAmount::try_from(amount).is_err() {
+            return Err(Error::InvalidArgs(InvalidArgsError::RepeatAmountTooLarge(amount)));
+        }
+
+        let separator = self.evaluate_to_bytes(separator, hook)?;
+        let mut pattern = Vec::new();
+        for exp in pattern_exps {
+            pattern.extend(self.evaluate_to_bytes(exp, hook)?);
+        }
+
+        // The separators count towards the limit as well, as there can be
+        // far more of them than there are bytes in the pattern
+        let length = joined_length(pattern.len(), separator.len(), amount);
+        self.check_length(length)?;
+
+        let mut bytes = Vec::with_capacity(length);
+        for index in 0 .. amount {
+            if index > 0 {
+                bytes.extend_from_slice(&separator);
+            }
+
+            bytes.extend_from_slice(&pattern);
+        }
+
+        Ok(Value::VariableBytes(bytes))
+    }
+
+    /// Runs the `fill` function, which repeats the bytes of every argument
+    /// after the first until the number of bytes given by the first argument
+    /// have been produced, cutting the last repetition short if necessary.
+    fn run_fill<'src>(&self, args: &[Exp<'src>], hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
+        let mut args = args.iter();
+        let length = match args.next() {
+            Some(exp)  => self.evaluate_exp(exp, hook)?.eval_to_count()?,
+            None       => return Err(Error::InvalidArgs(InvalidArgsError::NoArgumentsForFill)),
+        };
+
+        if length == 0 {
+            return Err(Error::InvalidArgs(InvalidArgsError::ZeroFillLength));
+        }
+
+        self.check_length(length)?;
+
+        let mut pattern = Vec::new();
+        for exp in args {
+            let sub_bytes = self.evaluate_to_bytes(exp, hook)?;
+            pattern.extend(&sub_bytes);
+        }
+
+        if pattern.is_empty() {
+            return Err(Error::InvalidArgs(InvalidArgsError::EmptyFillPattern));
+        }
+
+        let bytes = pattern.into_iter().cycle().take(length).collect();
+        Ok(Value::VariableBytes(bytes))
+    }
+
+    /// Adds the bits of each of the given arguments to the `bits` function
+    /// to the vector, including the bits inside any groups.
+    fn collect_bits<'src>(&self, args: &[Exp<'src>], bit_vec: &mut Vec<bool>, hook: &mut Hook<'_, 'src>) -> Result<(), Error<'src>> {
+        for exp in args {
+            match exp {
+                Exp::Bits(bits) => {
+                    bit_vec.extend(bits.iter().copied());
+                }
+                Exp::Group(exps) => {
+                    notify(hook, EvalEvent::Expression(exp));
+                    self.collect_bits(exps, bit_vec, hook)?;
+                }
+                _ => {
+                    for byte in self.evaluate_to_bytes(exp, hook)? {
+                        bit_vec.extend((0 .. 8).rev().map(|i| byte >> i & 1 == 1));
                     }
                 }
             }
-            Self::RawFloat(s) => {
+        }
+
+        Ok(())
+    }
+
+    /// Runs the `bits` function, which packs its arguments together into
+    /// bytes, most significant bit first. Bit fields and bit forms add their
+    /// bits, and anything else adds all the bits of its bytes. The total
+    /// number of bits has to be a multiple of eight.
+    fn run_bits<'src>(&self, args: &[Exp<'src>], hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
+        let mut bit_vec = Vec::new();
+        self.collect_bits(args, &mut bit_vec, hook)?;
+
+        if bit_vec.len() % 8 != 0 {
+            return Err(Error::InvalidArgs(InvalidArgsError::BitsNotWholeBytes(bit_vec.len())));
+        }
+
+        let bytes = bit_vec.chunks(8)
+                           .map(|chunk| chunk.iter().fold(0, |byte, bit| byte << 1 | u8::from(*bit)))
+                           .collect();
+
+        Ok(Value::VariableBytes(bytes))
+    }
+
+    /// Runs the `zero` or `ones` function, which produces the given byte
+    /// the number of times given by its only argument. Unlike a repeat
+    /// function, a count of zero is fine, and produces nothing.
+    fn run_same_byte<'src>(&self, byte: u8, args: &[Exp<'src>], hook: &mut Hook<'_, 'src>) -> Result<Value<'src>, Error<'src>> {
+        let arg = only_arg(args)?;
+        let count = self.evaluate_exp(arg, hook)?.eval_to_count()?;
+        self.check_length(count)?;
+        Ok(Value::VariableBytes(vec![ byte; count ]))
+    }
+}
+
+
+impl<'src> Value<'src> {
+
+    /// Converts this “value in flight” into a series of bytes, or return an
+    /// error if the conversion is not possible. This is used when printing
+    /// bytes at the top level, or converting values to sequences for a
+    /// repetition function.
+    pub fn eval_to_bytes(self) -> Result<Vec<u8>, Error<'src>> {
+        match self {
+            Self::Byte(byte) => {
+                Ok(vec![ byte ])
+            }
+            Self::VariableBytes(bytes) => {
+                Ok(bytes)
+            }
+            Self::Ordered(..) => {
+                self.laid_out().eval_to_bytes()
+            }
+            Self::MultiByte(v) => {
+                Err(Error::TopLevelBigDecimal(LargeNumber::Known(v)))
+            }
+            Self::RawNumber(s) => {
                 match s.parse() {
-                    Ok(num) => flendianify(num),
+                    Ok(v) => {
+                        Ok(vec![ v ])
+                    }
                     Err(e) => {
                         warn!("Parse error: {}", e);
-                        return Err(Error::TooBigDecimal(LargeNumber::FoundRawFloat(s)));
+                        Err(Error::TopLevelBigDecimal(LargeNumber::FoundRawNumber(s)))
                     }
                 }
             }
-        };
+            Self::RawFloat(s) => {
+                Err(Error::TopLevelBigDecimal(LargeNumber::FoundRawFloat(s)))
+            }
+        }
+    }
 
-        Ok(Value::VariableBytes(bytes.to_vec()))
+    /// Lays out a number that has been given an endianness as the series of
+    /// bytes it stands for, leaving any other value as it is. This is used by
+    /// everything that has no need for the number itself.
+    fn laid_out(self) -> Self {
+        match self {
+            Self::Ordered(mbv, endianness)  => Self::VariableBytes(mbv.width().bytes_of(mbv.widened(), endianness)),
+            other                           => other,
+        }
     }
 
-    /// Converts this “value in flight” into an 8-byte value, using the given
-    /// function to perform the conversion with a certain endianness, or
-    /// return an error if the conversion is not possible. This is used when
-    /// passing a value to the `be64` or `le64` functions.
-    fn to_eight_variable_bytes(self, endianify: impl Fn(u64) -> [u8; 8], flendianify: impl Fn(f64) -> [u8; 8]) -> Result<Self, Error<'src>> {
-        let bytes = match self {
+    /// Converts this “value in flight” into a number of bytes, or returns an
+    /// error if the conversion is not possible. This is used when a function
+    /// takes an amount as one of its arguments, such as the `fill` function.
+    fn eval_to_count(self) -> Result<usize, Error<'src>> {
+        match self {
             Self::Byte(b) => {
-                endianify(u64::from(b))
-            }
-            Self::VariableBytes(bytes) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::VariableToFixed(bytes, 8)));
+                Ok(usize::from(b))
             }
             Self::MultiByte(MultiByteValue::Sixteen(o2)) => {
-                endianify(u64::from(o2))
+                Ok(usize::from(o2))
             }
             Self::MultiByte(MultiByteValue::ThirtyTwo(o4)) => {
-                endianify(u64::from(o4))
+                usize::try_from(o4).map_err(|_| Error::TooBigDecimal(LargeNumber::Known(MultiByteValue::ThirtyTwo(o4))))
             }
             Self::MultiByte(MultiByteValue::SixtyFour(o8)) => {
-                endianify(o8)
+                usize::try_from(o8).map_err(|_| Error::TooBigDecimal(LargeNumber::Known(MultiByteValue::SixtyFour(o8))))
             }
             Self::RawNumber(s) => {
-                match s.parse() {
-                    Ok(num) => endianify(num),
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                        return Err(Error::TooBigDecimal(LargeNumber::FoundRawNumber(s)));
-                    }
-                }
+                parse_raw_number(s)
             }
-            Self::RawFloat(s) => {
-                match s.parse() {
-                    Ok(num) => flendianify(num),
-                    Err(e) => {
-                        warn!("Parse error: {}", e);
-                        return Err(Error::TooBigDecimal(LargeNumber::FoundRawFloat(s)));
-                    }
-                }
+            other => {
+                Err(Error::InvalidArgs(InvalidArgsError::CountWrongType(other.type_name())))
+            }
+        }
+    }
+
+    /// Converts this “value in flight” into a value of the given width and
+    /// endianness, or returns an error if the conversion is not possible.
+    /// This is used when passing a value to the `be` and `le` functions.
+    /// Values cannot be made more narrow, and floating-point numbers can
+    /// only be given a width of 32 or 64 bits.
+    fn to_fixed_width(self, width: Width, endianness: Endianness) -> Result<Self, Error<'src>> {
+        let num = self.to_fixed_width_number(width)?;
+        Ok(Value::Ordered(width.value_of(num), endianness))
+    }
+
+    /// Converts this “value in flight” into a number that fits in the given
+    /// width, without laying it out as bytes yet, or returns an error if the
+    /// conversion is not possible.
+    fn to_fixed_width_number(self, width: Width) -> Result<u64, Error<'src>> {
+        let num = match (self, width) {
+            (Self::Byte(b), _) => {
+                u64::from(b)
+            }
+            (Self::VariableBytes(bytes), _) => {
+                return Err(Error::InvalidArgs(InvalidArgsError::VariableToFixed(bytes, width.byte_count())));
+            }
+            (ordered @ Self::Ordered(..), _) => {
+                return ordered.laid_out().to_fixed_width_number(width);
+            }
+            (Self::MultiByte(mbv), _) if mbv.width() > width => {
+                return Err(Error::CannotNarrow(mbv, width));
+            }
+            (Self::MultiByte(mbv), _) => {
+                mbv.widened()
+            }
+            (Self::RawNumber(s), _) => {
+                parse_raw_number_at(s, width)?
+            }
+            (Self::RawFloat(s), Width::Sixteen) => {
+                return Err(Error::FloatNeedsWiderTarget(s));
+            }
+            (Self::RawFloat(s), Width::ThirtyTwo) => {
+                u64::from(parse_raw_float::<f32>(s)?.to_bits())
+            }
+            (Self::RawFloat(s), Width::SixtyFour) => {
+                parse_raw_float::<f64>(s)?.to_bits()
             }
         };
 
-        Ok(Value::VariableBytes(bytes.to_vec()))
+        Ok(num)
     }
 
     /// Applies the given bitwise function to this “value in flight”, with the
@@ -463,7 +1272,7 @@ impl<'src> Value<'src> {
     /// and 16-bit number, or byte sequences of different lengths) or are raw
     /// (a number or float where the size is not yet known).
     fn apply_bitwise(self, next_val: Self, bitwise_op: BitwiseFold) -> Result<Self, Error<'src>> {
-        match (self, next_val) {
+        match (self.laid_out(), next_val.laid_out()) {
             (Self::Byte(left),
              Self::Byte(right)) => {
                 let result = bitwise_op.apply_u8(left, right);
@@ -491,7 +1300,7 @@ impl<'src> Value<'src> {
             (Self::VariableBytes(lefts),
              Self::VariableBytes(rights)) => {
                 if lefts.len() != rights.len() {
-                    return Err(Error::InvalidArgs(InvalidArgsError::BitwiseDifferentLengths(lefts.len(), rights.len())));
+                    return Err(Error::InvalidArgs(InvalidArgsError::BitwiseMismatchedWidths(lefts.len(), rights.len())));
                 }
 
                 let bytes = lefts.into_iter()
@@ -503,21 +1312,220 @@ impl<'src> Value<'src> {
             }
 
             (a, b) => {
-                return Err(Error::InvalidArgs(InvalidArgsError::BitwiseWrongTypes(a.type_name(), b.type_name())));
+                if let (Some(left_width), Some(right_width)) = (a.byte_width(), b.byte_width()) {
+                    if left_width != right_width {
+                        return Err(Error::InvalidArgs(InvalidArgsError::BitwiseMismatchedWidths(left_width, right_width)));
+                    }
+                }
+
+                Err(Error::InvalidArgs(InvalidArgsError::BitwiseWrongTypes(a.type_name(), b.type_name())))
+            }
+        }
+    }
+
+    /// Negates this “value in flight” using two’s complement, wrapping
+    /// within the value’s width, or returns an error if the value has no
+    /// single width (variable bytes, or a raw number or float).
+    fn negate(self) -> Result<Self, Error<'src>> {
+        match self {
+            Self::Byte(b) => {
+                Ok(Self::Byte(b.wrapping_neg()))
+            }
+            Self::MultiByte(MultiByteValue::Sixteen(o2)) => {
+                Ok(Self::MultiByte(MultiByteValue::Sixteen(o2.wrapping_neg())))
+            }
+            Self::MultiByte(MultiByteValue::ThirtyTwo(o4)) => {
+                Ok(Self::MultiByte(MultiByteValue::ThirtyTwo(o4.wrapping_neg())))
+            }
+            Self::MultiByte(MultiByteValue::SixtyFour(o8)) => {
+                Ok(Self::MultiByte(MultiByteValue::SixtyFour(o8.wrapping_neg())))
+            }
+            other => {
+                Err(Error::InvalidArgs(InvalidArgsError::FixedWidthRequired("neg", other.type_name())))
+            }
+        }
+    }
+
+    /// Converts this “value in flight” to a signed fixed-point number of the
+    /// given width, with the given number of its bits being fractional,
+    /// returning the two’s complement bits. The decimal gets worked out
+    /// exactly, then rounded to the nearest fraction that can be represented,
+    /// with halves rounded away from zero. Returns an error if the value is
+    /// not a plain decimal number, or if it does not fit.
+    fn to_fixed_point(self, width: Width, fraction_bits: u32) -> Result<u64, Error<'src>> {
+        let number = match self {
+            Self::RawNumber(s) | Self::RawFloat(s)  => s,
+            other  => return Err(Error::InvalidArgs(InvalidArgsError::FixedPointWrongType(other.type_name()))),
+        };
+
+        let total_bits = u32::from(width.byte_count()) * 8;
+        let out_of_range = || Error::FixedPointOutOfRange(number, total_bits - fraction_bits, fraction_bits);
+
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(digits)  => (true, digits),
+            None          => (false, number),
+        };
+
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() || ! whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidArgs(InvalidArgsError::FixedPointWrongType(self.type_name())));
+        }
+
+        // a whole part too big for this is too big for any format anyway
+        let whole = whole.bytes()
+                         .try_fold(0_u64, |acc, b| acc.checked_mul(10)?.checked_add(u64::from(b - b'0')))
+                         .ok_or_else(out_of_range)?;
+
+        // the fraction gets doubled once for each fractional bit, with the
+        // digit carried out of the top becoming the next bit, so it stays
+        // exact however many digits it has
+        let mut fraction_digits = fraction.trim_end_matches('0').bytes().map(|b| b - b'0').collect::<Vec<_>>();
+        let mut fraction_value = 0_u128;
+        for _ in 0 .. fraction_bits {
+            let mut carry = 0;
+            for digit in fraction_digits.iter_mut().rev() {
+                let doubled = *digit * 2 + carry;
+                *digit = doubled % 10;
+                carry = doubled / 10;
+            }
+
+            fraction_value = fraction_value << 1 | u128::from(carry);
+        }
+
+        // whatever is left over decides the rounding, with halves rounded
+        // away from zero
+        let round_up = matches!(fraction_digits.first(), Some(digit) if *digit >= 5);
+        let magnitude = (u128::from(whole) << fraction_bits) + fraction_value + u128::from(round_up);
+
+        // the smallest number that does not fit, which is one more for
+        // negative numbers than positive ones
+        let limit = 1_u128 << (total_bits - 1);
+        if (negative && magnitude > limit) || (! negative && magnitude >= limit) {
+            return Err(out_of_range());
+        }
+
+        let mask = (1_u128 << total_bits) - 1;
+        let bits = if negative { magnitude.wrapping_neg() & mask } else { magnitude };
+        Ok(u64::try_from(bits).unwrap())
+    }
+
+    /// Converts this “value in flight” to its Gray code within the value’s
+    /// width. Returns an error if the value is raw or a variable-length byte
+    /// string, as neither has a width (or, for bytes, an endianness) to work
+    /// within. The output of `be` and `le` keeps its endianness.
+    fn gray_code(self) -> Result<Self, Error<'src>> {
+        match self {
+            Self::Byte(b) => {
+                Ok(Self::Byte(b ^ (b >> 1)))
+            }
+            Self::MultiByte(mbv) => {
+                Ok(Self::MultiByte(mbv.gray_code()))
+            }
+            Self::Ordered(mbv, endianness) => {
+                Ok(Self::Ordered(mbv.gray_code(), endianness))
+            }
+            other => {
+                Err(Error::InvalidArgs(InvalidArgsError::FixedWidthRequired("gray", other.type_name())))
+            }
+        }
+    }
+
+    /// Counts the zero bits at one end of this “value in flight” within the
+    /// value’s width, using whichever of the given functions matches that
+    /// width, and returns the count as a byte. Returns an error if the value
+    /// has no single width, as the count would depend on it.
+    fn count_zeros(self, function_name: &'static str, count8: impl Fn(u8) -> u32, count16: impl Fn(u16) -> u32, count32: impl Fn(u32) -> u32, count64: impl Fn(u64) -> u32) -> Result<Self, Error<'src>> {
+        let count = match self {
+            Self::Byte(b)                                   => count8(b),
+            Self::MultiByte(MultiByteValue::Sixteen(o2))    => count16(o2),
+            Self::MultiByte(MultiByteValue::ThirtyTwo(o4))  => count32(o4),
+            Self::MultiByte(MultiByteValue::SixtyFour(o8))  => count64(o8),
+            other => {
+                return Err(Error::InvalidArgs(InvalidArgsError::FixedWidthRequired(function_name, other.type_name())));
+            }
+        };
+
+        // the count is at most 64, so it always fits in a byte
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(Self::Byte(count as u8))
+    }
+
+    /// Converts this “value in flight” to the number that the `varint`
+    /// function encodes. A variable-length byte string of up to eight bytes
+    /// is treated as one big-endian number, so that the output of `be16` and
+    /// friends can be used. Returns an error for floats and longer strings.
+    fn to_varint_number(self) -> Result<u64, Error<'src>> {
+        match self.laid_out() {
+            Self::Byte(b)         => Ok(u64::from(b)),
+            Self::MultiByte(mbv)  => Ok(mbv.widened()),
+            Self::RawNumber(s)    => parse_raw_number(s),
+            Self::VariableBytes(bytes) if (1 ..= 8).contains(&bytes.len()) => {
+                Ok(bytes.iter().fold(0, |acc, b| (acc << 8) | u64::from(*b)))
+            }
+            other => {
+                Err(Error::InvalidArgs(InvalidArgsError::VarintWrongType(other.type_name())))
             }
         }
     }
 
+    /// Converts this “value in flight” to packed binary-coded decimal, with
+    /// two digits to a byte, and a leading zero to make up an odd number of
+    /// digits. Only raw decimal numbers have digits to pack, so any other
+    /// value is an error; leading zeros are kept, so `[0012]` is `00 12`.
+    fn to_bcd(self) -> Result<Vec<u8>, Error<'src>> {
+        let digits = match self {
+            Self::RawNumber(s)  => s.as_bytes(),
+            other => {
+                return Err(Error::InvalidArgs(InvalidArgsError::BcdWrongType(other.type_name())));
+            }
+        };
+
+        let padding = if digits.len() % 2 == 1 { &b"0"[..] } else { &[] };
+        let nibbles = padding.iter().chain(digits).map(|d| d - b'0').collect::<Vec<_>>();
+        Ok(nibbles.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect())
+    }
+
+    /// Converts this “value in flight” to the number that the `svarint`
+    /// function encodes. Values with a known width are read as two’s
+    /// complement numbers of that width, so `[i32:-1]` counts as −1, while
+    /// raw decimal numbers are always positive.
+    fn to_signed_varint_number(self) -> Result<i64, Error<'src>> {
+        if let Self::RawNumber(s) = self {
+            return parse_raw_number(s);
+        }
+
+        let width = self.byte_width();
+        let num = self.to_varint_number()?;
+
+        // move the sign bit to the top, then shift back down to extend it
+        let shift = 64 - 8 * u32::try_from(width.unwrap()).unwrap();
+        Ok(i64::from_ne_bytes((num << shift).to_ne_bytes()) >> shift)
+    }
+
+    /// Returns the number of bytes this “value in flight” takes up, if it
+    /// is known, which is used when complaining about bitwise functions
+    /// being given values of different widths.
+    pub fn byte_width(&self) -> Option<usize> {
+        match self {
+            Self::Byte(_)           => Some(1),
+            Self::MultiByte(mbv)    => Some(usize::from(mbv.width().byte_count())),
+            Self::VariableBytes(v)  => Some(v.len()),
+            Self::Ordered(mbv, _)   => Some(usize::from(mbv.width().byte_count())),
+            Self::RawNumber(_)      => None,
+            Self::RawFloat(_)       => None,
+        }
+    }
+
     /// Returns a string describing the type of value this is. The result gets
     /// shown to the user as part of error handling, when a function cannot
     /// handle a value of a certain type.
-    fn type_name(&self) -> &'static str {
+    pub fn type_name(&self) -> &'static str {
         match self {
             Self::Byte(_)                                  => "byte",
             Self::MultiByte(MultiByteValue::Sixteen(_))    => "sixteen-bit number",
             Self::MultiByte(MultiByteValue::ThirtyTwo(_))  => "thirty-two-bit number",
             Self::MultiByte(MultiByteValue::SixtyFour(_))  => "sixty-four-bit number",
-            Self::VariableBytes(_)                         => "variable-length byte string",
+            Self::VariableBytes(_) | Self::Ordered(..)     => "variable-length byte string",
             Self::RawNumber(_)                             => "unsized number",
             Self::RawFloat(_)                              => "unsized float",
         }
@@ -526,6 +1534,26 @@ impl<'src> Value<'src> {
 
 
 impl BitwiseFold {
+
+    /// Returns the result of folding over no arguments at all, which is
+    /// whatever leaves any other value unchanged when combined with it.
+    fn empty_result<'src>(self) -> Result<Value<'src>, Error<'src>> {
+        match self {
+
+            // The identity for ‘and’ is a value with every bit set, but
+            // without a width there is no telling how many bytes of FF that
+            // would be, so this is an error.
+            Self::And  => Err(Error::InvalidArgs(InvalidArgsError::NoArgumentsForBitwise)),
+
+            // Combining nothing with ‘or’ sets no bits, so the result is
+            // nothing, not even a zero byte.
+            Self::Or   => Ok(Value::VariableBytes(Vec::new())),
+
+            // Likewise, combining nothing with ‘xor’ flips no bits.
+            Self::Xor  => Ok(Value::VariableBytes(Vec::new())),
+        }
+    }
+
     fn apply_u8(self, left: u8, right: u8) -> u8 {
         match self {
             Self::And => left & right,
@@ -560,31 +1588,61 @@ impl BitwiseFold {
 }
 
 
-/// Returns the only argument in the vector if just one is present, or returns
+/// Converts the value that the given expression has evaluated to into
+/// bytes, naming the constant in the error if the expression was one.
+fn value_to_bytes<'src>(exp: &Exp<'src>, value: Value<'src>) -> Result<Vec<u8>, Error<'src>> {
+    match (exp, value.eval_to_bytes()) {
+        (Exp::Constant { name }, Err(Error::TopLevelBigDecimal(LargeNumber::Known(mbv)))) => {
+            Err(Error::TopLevelBigDecimal(LargeNumber::FoundConstant(name.contents, mbv)))
+        }
+        (_, result) => {
+            result
+        }
+    }
+}
+
+/// Returns the only argument in the slice if just one is present, or returns
 /// an “invalid arguments” error.
-fn only_arg<'src>(mut args: Vec<Exp<'src>>) -> Result<Exp<'src>, Error<'src>> {
+fn only_arg<'a, 'src>(args: &'a [Exp<'src>]) -> Result<&'a Exp<'src>, Error<'src>> {
     if args.len() == 1 {
-        Ok(args.remove(0))
+        Ok(&args[0])
     }
     else {
         Err(Error::InvalidArgs(InvalidArgsError::TooManyArguments(args.len())))
     }
 }
 
+/// Returns the number of bytes that the `joined` function produces when it
+/// repeats a pattern of the given length by the given amount, with a
+/// separator of the given length between each repetition.
+fn joined_length(pattern_length: usize, separator_length: usize, amount: usize) -> usize {
+    let separators = separator_length.saturating_mul(amount.saturating_sub(1));
+    pattern_length.saturating_mul(amount).saturating_add(separators)
+}
+
 
 /// An error that can occur while evaluating a tree of expressions.
 #[derive(PartialEq, Debug)]
 pub enum Error<'src> {
 
-    /// A decimal number was too big for a byte at the top level, such as
-    /// `[9999999]`.
+    /// A decimal number was too big for a byte, and was output without an
+    /// endianness to lay it out with, such as `[9999999]` on its own or in a
+    /// group.
     TopLevelBigDecimal(LargeNumber<'src>),
 
     /// A decimal number was too big for its target.
     TooBigDecimal(LargeNumber<'src>),
 
+    /// A floating-point number was given a width of 16 bits, such as
+    /// `be16[f1.2]`. Floats can only be 32 or 64 bits wide.
+    FloatNeedsWiderTarget(&'src str),
+
+    /// A value with a known width was given a narrower one, such as
+    /// `be16(be32[1234])`. Hexit never narrows values, so this is an error.
+    CannotNarrow(MultiByteValue, Width),
+
     /// A constant value was referenced that does not exist.
-    UnknownConstant(&'src str),
+    UnknownConstant(Placed<&'src str>),
 
     /// A function was called with the wrong type or number of arguments.
     InvalidArgs(InvalidArgsError),
@@ -592,8 +1650,122 @@ pub enum Error<'src> {
     /// The amount of output hit the limit.
     TooMuchOutput,
 
+    /// The amount of output from one top-level expression hit the
+    /// per-expression limit.
+    LineTooLong(usize),
+
     /// The recursion depth hit the limit.
     TooMuchRecursion,
+
+    /// A decimal number did not fit in a fixed-point function’s Q format,
+    /// which has the given numbers of integer and fractional bits.
+    FixedPointOutOfRange(&'src str, u32, u32),
+}
+
+/// An iterator over the bytes that a program produces, evaluating one
+/// top-level expression at a time. The limits get checked for each
+/// expression just before it is evaluated, rather than for the whole program
+/// up front, so the bytes before an error all get yielded first. Once an
+/// error has been yielded, there are no more items.
+pub struct BytesIter<'prog, 'consts, 'src> {
+
+    /// The evaluator that runs each expression.
+    evaluator: Evaluator<'consts>,
+
+    /// The top-level expressions still to be evaluated, along with their
+    /// positions in the source.
+    placed_exps: Zip<slice::Iter<'prog, Exp<'src>>, slice::Iter<'prog, Placed<&'src str>>>,
+
+    /// The bytes produced by the last expression that have not yet been
+    /// yielded.
+    current: vec::IntoIter<u8>,
+
+    /// Whether an expression has failed to evaluate, in which case no more
+    /// of them get evaluated.
+    failed: bool,
+}
+
+impl<'prog, 'consts, 'src> Iterator for BytesIter<'prog, 'consts, 'src> {
+    type Item = Result<u8, LocatedError<'src>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(byte) = self.current.next() {
+                return Some(Ok(byte));
+            }
+            else if self.failed {
+                return None;
+            }
+
+            let (exp, position) = self.placed_exps.next()?;
+            let result = self.evaluator.estimate_top_level(exp)
+                             .and_then(|_| self.evaluator.evaluate_top_level(exp, &mut None));
+
+            match result {
+                Ok(bytes) => {
+                    self.current = bytes.into_iter();
+                }
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(LocatedError::new(error, *position)));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.current.len();
+
+        if self.failed || self.placed_exps.len() == 0 {
+            (buffered, Some(buffered))
+        }
+        else {
+            (buffered, None)
+        }
+    }
+}
+
+impl<'prog, 'consts, 'src> FusedIterator for BytesIter<'prog, 'consts, 'src> {}
+
+
+/// An error that occurred while evaluating a program, along with the
+/// position of the expression that caused it.
+#[derive(PartialEq, Debug)]
+pub struct LocatedError<'src> {
+
+    /// The error itself.
+    pub error: Error<'src>,
+
+    /// The position of the expression in the source. This is the most
+    /// specific position known: a constant that does not exist is pointed at
+    /// directly, and other errors point at the top-level expression that
+    /// they occurred within.
+    pub position: Placed<&'src str>,
+}
+
+impl<'src> LocatedError<'src> {
+
+    /// Locates the given error, using the position of the top-level
+    /// expression unless the error has a more specific one of its own.
+    fn new(error: Error<'src>, top_level_position: Placed<&'src str>) -> Self {
+        let position = match &error {
+            Error::UnknownConstant(name)  => *name,
+            _                             => top_level_position,
+        };
+
+        Self { error, position }
+    }
+
+    /// Returns the position of the expression that caused the error.
+    pub fn source_pos(&self) -> &Placed<&'src str> {
+        &self.position
+    }
+}
+
+impl<'src> fmt::Display for LocatedError<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
 }
 
 /// A number that was too big for its target. This is used in error handling.
@@ -607,12 +1779,16 @@ pub enum LargeNumber<'src> {
     /// A raw decimal number was too big, such as `be16[99999999]`.
     FoundRawNumber(&'src str),
 
-    /// A raw floating-point number was put in a `be16` or `le16`.
-    /// Floating-point numbers can only be put in 32-bit or 64-bit widths.
+    /// A raw floating-point number was used without a width, such as at the
+    /// top level, or could not be parsed at the width it was given.
     FoundRawFloat(&'src str),
 
     /// A number of bits were too many, such as `be16[b0101_0101_0101_0101_1].
     FoundBits(usize),
+
+    /// A multi-byte constant was used without an endianness, such as
+    /// `DNS_IN` on its own, even though its value may fit in a byte.
+    FoundConstant(&'src str, MultiByteValue),
 }
 
 /// An error caused by the wrong number or types of arguments being passed to
@@ -624,21 +1800,13 @@ pub enum InvalidArgsError {
     /// number by a multi-byte function.
     VariableToFixed(Vec<u8>, u8),
 
-    /// A 4-byte value was passed to a ‘le16’ or ‘be16’ function.
-    CannotNarrow4to2(u32),
-
-    /// An 8-byte value was passed to a ‘le16’ or ‘be16’ function.
-    CannotNarrow8to2(u64),
-
-    /// An 8-byte value was passed to a ‘le32’ or ‘be32’ function.
-    CannotNarrow8to4(u64),
-
-    /// A function was called with no arguments.
+    /// The ‘and’ function was called with no arguments. (The ‘or’ and ‘xor’
+    /// functions produce no bytes instead.)
     NoArgumentsForBitwise,
 
-    /// A bitwise function was called with two arguments of variable-width
-    /// byte strings that were of different lengths.
-    BitwiseDifferentLengths(usize, usize),
+    /// A bitwise function was called with two arguments that each have a
+    /// known number of bytes, but different numbers of them.
+    BitwiseMismatchedWidths(usize, usize),
 
     /// A bitwise function was called with arguments of differing types, or
     /// one or more arguments was a raw number or float.
@@ -646,29 +1814,119 @@ pub enum InvalidArgsError {
 
     /// A function was called with too many arguments.
     TooManyArguments(usize),
+
+    /// A function that works within a value’s width, such as ‘neg’ or
+    /// ‘clz’, was called with an argument without a single known width,
+    /// such as a variable-length byte string or raw number.
+    FixedWidthRequired(&'static str, &'static str),
+
+    /// The ‘popcount’ function counted more set bits than fit in a byte.
+    PopCountTooLarge(u64),
+
+    /// The ‘repeat’ function was called with no arguments.
+    NoArgumentsForRepeat,
+
+    /// The ‘repeat’ function was given an amount of zero, without zero
+    /// repeats being allowed.
+    ZeroRepeatAmount,
+
+    /// The ‘repeat’ function was given an amount too big to repeat by.
+    RepeatAmountTooLarge(usize),
+
+    /// The ‘joined’ function was called without both an amount and a
+    /// separator.
+    NotEnoughArgumentsForJoined,
+
+    /// The ‘fill’ function was called with no arguments.
+    NoArgumentsForFill,
+
+    /// The ‘fill’ function was asked to produce zero bytes.
+    ZeroFillLength,
+
+    /// The ‘fill’ function was given a length but no bytes to fill it with.
+    EmptyFillPattern,
+
+    /// A function that takes an amount was given a value that cannot be used
+    /// as one, such as a string or a float.
+    CountWrongType(&'static str),
+
+    /// The ‘bits’ function was given a number of bits that does not make up
+    /// a whole number of bytes.
+    BitsNotWholeBytes(usize),
+
+    /// A fixed-point function was given a value that is not a plain decimal
+    /// number, such as a byte or a float with an exponent.
+    FixedPointWrongType(&'static str),
+
+    /// A varint function was given a value that is not a number of up to
+    /// eight bytes, such as a float or a long string.
+    VarintWrongType(&'static str),
+
+    /// The ‘bcd’ function was given a value that is not a raw decimal
+    /// number, so there are no digits to pack.
+    BcdWrongType(&'static str),
 }
 
 impl<'src> fmt::Display for Error<'src> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::TopLevelBigDecimal(dec)  => write!(f, "{} at top level", dec),
-            Self::TooBigDecimal(dec)       => write!(f, "{} is too big for target", dec),
-            Self::UnknownConstant(uc)      => write!(f, "Unknown constant ‘{}’", uc),
-            Self::InvalidArgs(oh)          => write!(f, "Invalid arguments: {}", oh),
-            Self::TooMuchOutput            => write!(f, "Too much output!"),
-            Self::TooMuchRecursion         => write!(f, "Nested too deeply!"),
+            Self::TopLevelBigDecimal(dec)   => write!(f, "{} without an endianness", dec),
+            Self::TooBigDecimal(dec)        => write!(f, "{} is too big for target", dec),
+            Self::FloatNeedsWiderTarget(_)  => write!(f, "Floating-point values require a 32-bit or 64-bit width"),
+            Self::CannotNarrow(mbv, width)  => write!(f, "{} cannot be narrowed to {} bytes (Hexit never narrows values)", mbv, width.byte_count()),
+            Self::UnknownConstant(uc)       => write!(f, "Unknown constant ‘{}’", uc.contents),
+            Self::InvalidArgs(oh)           => write!(f, "Invalid arguments: {}", oh),
+            Self::TooMuchOutput             => write!(f, "Too much output!"),
+            Self::LineTooLong(limit)        => write!(f, "Too much output from one expression (limit is {} bytes)!", limit),
+            Self::TooMuchRecursion          => write!(f, "Nested too deeply!"),
+            Self::FixedPointOutOfRange(num, integer_bits, fraction_bits) => {
+                write!(f, "Number ‘{}’ does not fit in Q{}.{} fixed point", num, integer_bits, fraction_bits)
+            }
         }
     }
 }
 
 impl<'src> Error<'src> {
+
+    /// Returns the name of this kind of runtime error. Invalid arguments to
+    /// any function all share the one name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::TopLevelBigDecimal(_)     => "top_level_big_decimal",
+            Self::TooBigDecimal(_)          => "too_big_decimal",
+            Self::FloatNeedsWiderTarget(_)  => "float_needs_wider_target",
+            Self::CannotNarrow(..)          => "cannot_narrow",
+            Self::UnknownConstant(_)        => "unknown_constant",
+            Self::InvalidArgs(_)            => "invalid_args",
+            Self::TooMuchOutput             => "too_much_output",
+            Self::LineTooLong(_)            => "line_too_long",
+            Self::TooMuchRecursion          => "too_much_recursion",
+            Self::FixedPointOutOfRange(..)  => "fixed_point_out_of_range",
+        }
+    }
+
     pub fn note(&self) -> Option<&'static str> {
         match self {
             Self::TopLevelBigDecimal(LargeNumber::FoundRawNumber(_)) => {
-                Some("Top-level multi-byte values must be given an endianness using a function such as ‘be16’ or ‘le32’")
+                Some("Multi-byte values must be given an endianness using a function such as ‘be16’ or ‘le32’")
             }
             Self::TopLevelBigDecimal(LargeNumber::FoundRawFloat(_)) => {
-                Some("Top-level floating point values must be given an endianness and width using a function such as ‘be32’ or ‘le64’")
+                Some("Floating point values must be given an endianness and width using a function such as ‘be32’ or ‘le64’")
+            }
+            Self::TopLevelBigDecimal(LargeNumber::FoundConstant(_, MultiByteValue::Sixteen(_))) => {
+                Some("Constants are as wide as the field they are for, even if their value fits in a byte, so 16-bit constants must be given an endianness by wrapping them in ‘be16’ or ‘le16’")
+            }
+            Self::TopLevelBigDecimal(LargeNumber::FoundConstant(..)) => {
+                Some("Constants are as wide as the field they are for, even if their value fits in a byte, so 32-bit constants must be given an endianness by wrapping them in ‘be32’ or ‘le32’")
+            }
+            Self::TopLevelBigDecimal(LargeNumber::Known(MultiByteValue::Sixteen(_))) => {
+                Some("Multi-byte values must be given an endianness by wrapping them in ‘be16’ or ‘le16’")
+            }
+            Self::TopLevelBigDecimal(LargeNumber::Known(MultiByteValue::ThirtyTwo(_))) => {
+                Some("Multi-byte values must be given an endianness by wrapping them in ‘be32’ or ‘le32’")
+            }
+            Self::TopLevelBigDecimal(LargeNumber::Known(MultiByteValue::SixtyFour(_))) => {
+                Some("Multi-byte values must be given an endianness by wrapping them in ‘be64’ or ‘le64’")
             }
             _ => {
                 None
@@ -680,10 +1938,11 @@ impl<'src> Error<'src> {
 impl<'src> fmt::Display for LargeNumber<'src> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Known(mbv)           => mbv.fmt(f),
-            Self::FoundRawNumber(num)  => write!(f, "Decimal number ‘{}’", num),
-            Self::FoundRawFloat(num)   => write!(f, "Floating-point number ‘{}’", num),
-            Self::FoundBits(length)    => write!(f, "Bit set of length {}", length),
+            Self::Known(mbv)                => mbv.fmt(f),
+            Self::FoundRawNumber(num)       => write!(f, "Decimal number ‘{}’", num),
+            Self::FoundRawFloat(num)        => write!(f, "Floating-point number ‘{}’", num),
+            Self::FoundBits(length)         => write!(f, "Bit set of length {}", length),
+            Self::FoundConstant(name, mbv)  => write!(f, "{}-bit constant ‘{}’", mbv.width().byte_count() * 8, name),
         }
     }
 }
@@ -702,13 +1961,24 @@ impl fmt::Display for InvalidArgsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::VariableToFixed(_, size)       => write!(f, "Cannot convert variable bytes into {} bytes", size),
-            Self::CannotNarrow4to2(o4)           => write!(f, "Cannot narrow a 4-byte number ({}) into 2 bytes", o4),
-            Self::CannotNarrow8to2(o8)           => write!(f, "Cannot narrow an 8-byte number ({}) into 2 bytes", o8),
-            Self::CannotNarrow8to4(o8)           => write!(f, "Cannot narrow an 8-byte number ({}) into 4 bytes", o8),
-            Self::NoArgumentsForBitwise          => write!(f, "No arguments passed to bitwise function"),
-            Self::BitwiseDifferentLengths(l, r)  => write!(f, "Variable byte strings of different lengths passed to bitwise function ({} and {})", l, r),
+            Self::NoArgumentsForBitwise          => write!(f, "No arguments passed to ‘and’ function"),
+            Self::BitwiseMismatchedWidths(l, r)  => write!(f, "Values of mismatched widths passed to bitwise function ({} and {} bytes)", l, r),
             Self::BitwiseWrongTypes(l, r)        => write!(f, "Arguments of different types passed to bitwise function ({} and {})", l, r),
             Self::TooManyArguments(count)        => write!(f, "Too many arguments ({}) passed to function", count),
+            Self::FixedWidthRequired(func, t)    => write!(f, "Function ‘{}’ needs a value with a fixed width, not a value of type {}", func, t),
+            Self::PopCountTooLarge(count)        => write!(f, "Population count ({}) does not fit in a byte", count),
+            Self::NoArgumentsForRepeat           => write!(f, "No arguments passed to repeat function"),
+            Self::ZeroRepeatAmount               => write!(f, "Repeat function given an amount of zero"),
+            Self::RepeatAmountTooLarge(amount)   => write!(f, "Repeat amount {} is too large", amount),
+            Self::NotEnoughArgumentsForJoined    => write!(f, "Function ‘joined’ needs an amount and a separator"),
+            Self::NoArgumentsForFill             => write!(f, "No arguments passed to fill function"),
+            Self::ZeroFillLength                 => write!(f, "Fill function given a length of zero"),
+            Self::EmptyFillPattern               => write!(f, "Fill function given no bytes to fill with"),
+            Self::CountWrongType(t)              => write!(f, "Cannot use a value of type {} as an amount", t),
+            Self::BitsNotWholeBytes(count)       => write!(f, "Bits function given {} bits, which is not a whole number of bytes", count),
+            Self::FixedPointWrongType(t)         => write!(f, "Fixed-point functions need a decimal number, not a value of type {}", t),
+            Self::VarintWrongType(t)             => write!(f, "Varint functions need a number of up to eight bytes, not a value of type {}", t),
+            Self::BcdWrongType(t)                => write!(f, "Function ‘bcd’ needs a decimal number, not a value of type {}", t),
         }
     }
 }
@@ -718,50 +1988,352 @@ impl fmt::Display for InvalidArgsError {
 mod test {
     use super::*;
     use pretty_assertions::assert_eq;
+    use crate::pos::At;
 
     #[test]
     fn nothing() {
         let exps = vec![];
-        assert_eq!(evaluate_exps(exps, &Table::empty(), None),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
                    Ok(vec![]));
     }
 
     #[test]
     fn one_top_level_byte() {
         let exps = vec![ Exp::Char(0x73) ];
-        assert_eq!(evaluate_exps(exps, &Table::empty(), None),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
                    Ok(vec![ 0x73 ]));
     }
 
     #[test]
     fn top_level_decimal_73() {
         let exps = vec![ Exp::Dec("73") ];
-        assert_eq!(evaluate_exps(exps, &Table::empty(), None),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
                    Ok(vec![ 73 ]));
     }
 
     #[test]
     fn top_level_decimal_255() {
         let exps = vec![ Exp::Dec("255") ];
-        assert_eq!(evaluate_exps(exps, &Table::empty(), None),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
                    Ok(vec![ 255 ]));
     }
 
     #[test]
     fn top_level_decimal_256() {
         let exps = vec![ Exp::Dec("256") ];
-        assert_eq!(evaluate_exps(exps, &Table::empty(), None),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
                    Err(Error::TopLevelBigDecimal(LargeNumber::FoundRawNumber("256"))));
     }
 
+    #[test]
+    fn group_of_bytes() {
+        let exps = vec![ Exp::Group(vec![ Exp::Char(0xAB), Exp::Char(0xCD) ]) ];
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0xAB, 0xCD ]));
+    }
+
+    #[test]
+    fn empty_group() {
+        let exps = vec![ Exp::Char(0x12), Exp::Group(vec![]) ];
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x12 ]));
+    }
+
+    #[test]
+    fn bits_ipv4_first_byte() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Bits,
+            position: "bits".at(1, 0),
+            args: vec![ Exp::Bits(vec![ false, true, false, false ]),
+                        Exp::Bits(vec![ false, true, false, true ]) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x45 ]));
+    }
+
+    #[test]
+    fn bits_with_bytes() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Bits,
+            position: "bits".at(1, 0),
+            args: vec![ Exp::Bits(vec![ true; 4 ]),
+                        Exp::Char(0x12),
+                        Exp::Bits(vec![ false; 4 ]) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0xF1, 0x20 ]));
+    }
+
+    #[test]
+    fn bits_not_whole_bytes() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Bits,
+            position: "bits".at(1, 0),
+            args: vec![ Exp::Bits(vec![ true; 12 ]) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Err(Error::InvalidArgs(InvalidArgsError::BitsNotWholeBytes(12))));
+    }
+
+    #[test]
+    fn zero_bytes() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Zero,
+            position: "zero".at(1, 0),
+            args: vec![ Exp::Dec("3") ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x00; 3 ]));
+    }
+
+    #[test]
+    fn ones_bytes() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Ones,
+            position: "ones".at(1, 0),
+            args: vec![ Exp::Char(0x04) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0xFF; 4 ]));
+    }
+
+    #[test]
+    fn zero_limit() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Zero,
+            position: "zero".at(1, 0),
+            args: vec![ Exp::Dec("5000") ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), None),
+                   Err(Error::TooMuchOutput));
+    }
+
+    #[test]
+    fn repeat_nothing() {
+        let exps = vec![ Exp::Char(0x12), Exp::Function {
+            name: FunctionName::Repeat(0),
+            position: "x0".at(1, 0),
+            args: vec![ Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x12 ]));
+    }
+
+    #[test]
+    fn fill_limit() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Fill,
+            position: "fill".at(1, 0),
+            args: vec![ Exp::Dec("5000"), Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), None),
+                   Err(Error::TooMuchOutput));
+    }
+
     #[test]
     fn test_limit() {
         let exps = vec![ Exp::Function {
             name: FunctionName::Repeat(30000),
+            position: "x30000".at(1, 0),
             args: vec![ Exp::Char(0x73), Exp::Char(0x73), Exp::Char(0x73) ]
         } ];
 
-        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000)),
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), None),
+                   Err(Error::TooMuchOutput));
+    }
+
+    #[test]
+    fn nested_repeat_limit() {
+        let inner = Exp::Function {
+            name: FunctionName::Repeat(60000),
+            position: "x60000".at(1, 0),
+            args: vec![ Exp::Char(0xFF) ]
+        };
+
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Repeat(60000),
+            position: "x60000".at(1, 0),
+            args: vec![ inner ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(100_000_000), None),
                    Err(Error::TooMuchOutput));
     }
+
+    #[test]
+    fn estimate_with_dynamic_amount() {
+        let evaluator = Evaluator { constants: &Table::builtin_set(), limit: None, line_limit: None };
+        let exp = Exp::Function {
+            name: FunctionName::RepeatDynamic { allow_zero: false },
+            position: "repeat".at(1, 0),
+            args: vec![ Exp::Dec("3"), Exp::Char(0xAB), Exp::StringLiteral { bytes: b"cd".as_ref().into() } ]
+        };
+
+        assert_eq!(evaluator.estimate_length(&exp),
+                   Ok(9));
+    }
+
+    #[cfg(feature = "parallel")]
+    fn many_placed_exps(count: usize) -> Vec<(Exp<'static>, Placed<&'static str>)> {
+        use crate::pos::At;
+
+        (0 .. count).map(|index| {
+            let exp = match index % 3 {
+                0 => Exp::Char(u8::try_from(index % 256).unwrap()),
+                1 => Exp::Function { name: FunctionName::Repeat(40), position: "x40".at(index + 1, 0), args: vec![ Exp::Char(0xAB) ] },
+                _ => Exp::StringLiteral { bytes: b"hexit".as_ref().into() },
+            };
+
+            (exp, "exp".at(index + 1, 0))
+        }).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_matches_sequential() {
+        let evaluator = Evaluator { constants: &Table::empty(), limit: Some(1000), line_limit: None };
+
+        let exps = many_placed_exps(30000);
+        let placed_exps = exps.iter().map(|(exp, position)| (exp, *position)).collect::<Vec<_>>();
+
+        assert_eq!(evaluator.evaluate_in_parallel(&placed_exps),
+                   evaluator.evaluate_in_order(&placed_exps, &mut None));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_earliest_error() {
+        use crate::pos::At;
+
+        let evaluator = Evaluator { constants: &Table::empty(), limit: None, line_limit: Some(30) };
+        let exps = many_placed_exps(30000);
+        let placed_exps = exps.iter().map(|(exp, position)| (exp, *position)).collect::<Vec<_>>();
+
+        let error = evaluator.evaluate_in_parallel(&placed_exps).unwrap_err();
+        assert_eq!(error, LocatedError::new(Error::LineTooLong(30), "exp".at(2, 0)));
+    }
+
+    fn run_width(mbt: MultiByteType, number: &str) -> Vec<u8> {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::MultiByte(mbt),
+            position: "be16".at(1, 0),
+            args: vec![ Exp::Dec(number) ]
+        } ];
+
+        evaluate_exps(exps, &Table::empty(), None, None).unwrap()
+    }
+
+    #[test]
+    fn both_sixteen() {
+        assert_eq!(number_to_both("4660", Width::Sixteen),
+                   Ok((vec![ 0x12, 0x34 ], vec![ 0x34, 0x12 ])));
+    }
+
+    #[test]
+    fn both_matches_functions() {
+        for &(width, be, le) in &[ (Width::Sixteen,   MultiByteType::Be16, MultiByteType::Le16),
+                                     (Width::ThirtyTwo, MultiByteType::Be32, MultiByteType::Le32),
+                                     (Width::SixtyFour, MultiByteType::Be64, MultiByteType::Le64) ] {
+            assert_eq!(number_to_both("258", width),
+                       Ok((run_width(be, "258"), run_width(le, "258"))));
+            assert_eq!(number_to_both("0", width),
+                       Ok((run_width(be, "0"), run_width(le, "0"))));
+        }
+    }
+
+    #[test]
+    fn both_too_big() {
+        assert_eq!(number_to_both("65536", Width::Sixteen),
+                   Err(Error::TooBigDecimal(LargeNumber::FoundRawNumber("65536"))));
+    }
+
+    #[test]
+    fn top_level_constant_note() {
+        let exps = vec![ Exp::Constant { name: "DNS_A".at(1, 0) } ];
+        let error = evaluate_exps(exps, &Table::builtin_set(), None, None).unwrap_err();
+
+        assert_eq!(error, Error::TopLevelBigDecimal(LargeNumber::FoundConstant("DNS_A", MultiByteValue::Sixteen(1))));
+        assert_eq!(error.note(),
+                   Some("Constants are as wide as the field they are for, even if their value fits in a byte, so 16-bit constants must be given an endianness by wrapping them in ‘be16’ or ‘le16’"));
+    }
+
+    #[test]
+    fn repeat_dynamic() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::RepeatDynamic { allow_zero: false },
+            position: "repeat".at(1, 0),
+            args: vec![ Exp::Dec("3"), Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x73, 0x73, 0x73 ]));
+    }
+
+    #[test]
+    fn repeat_dynamic_zero() {
+        let exps = vec![ Exp::Char(0x12), Exp::Function {
+            name: FunctionName::RepeatDynamic { allow_zero: true },
+            position: "repeat".at(1, 0),
+            args: vec![ Exp::Dec("0"), Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, None),
+                   Ok(vec![ 0x12 ]));
+    }
+
+    #[test]
+    fn repeat_dynamic_limit() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::RepeatDynamic { allow_zero: false },
+            position: "repeat".at(1, 0),
+            args: vec![ Exp::Dec("30000"), Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), None),
+                   Err(Error::TooMuchOutput));
+    }
+
+    #[test]
+    fn line_limit() {
+        let exps = vec![ Exp::Char(0x12), Exp::Function {
+            name: FunctionName::Repeat(300),
+            position: "x300".at(1, 0),
+            args: vec![ Exp::Char(0x73) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), Some(200)),
+                   Err(Error::LineTooLong(200)));
+    }
+
+    #[test]
+    fn line_limit_each_line() {
+        let exps = vec![ Exp::Function {
+            name: FunctionName::Repeat(150),
+            position: "x150".at(1, 0),
+            args: vec![ Exp::Char(0x73) ]
+        }, Exp::Function {
+            name: FunctionName::Repeat(150),
+            position: "x150".at(1, 0),
+            args: vec![ Exp::Char(0x74) ]
+        } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), Some(1000), Some(200)).map(|bs| bs.len()),
+                   Ok(300));
+    }
+
+    #[test]
+    fn line_limit_string() {
+        let exps = vec![ Exp::StringLiteral { bytes: b"abcdef"[..].into() } ];
+
+        assert_eq!(evaluate_exps(exps, &Table::empty(), None, Some(4)),
+                   Err(Error::LineTooLong(4)));
+    }
 }
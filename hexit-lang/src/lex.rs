@@ -1,22 +1,41 @@
 //! The lexical analysis step, or tokenising. This involves
 
-use std::fmt;
-use std::str::CharIndices;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::CharIndices;
 
 use crate::pos::Placed;
-use crate::tokens::Token;
+use crate::tokens::{Token, StringEncoding};
+use crate::{FrontComments, ParseOptions};
 
 
 /// Tokenises a line of Hexit into a vector of tokens that contain references
 /// to the original source string. Returns an error if there is a problem with
 /// the input, using the line number to indicate which line had the problem.
-pub fn lex_source<'src>(line_number: usize, input_source: &'src str) -> Result<Vec<Token<'src>>, Error<'src>> {
-    let mut lexer = Lexer::new(line_number, input_source);
+pub fn lex_source<'src>(line_number: usize, input_source: &'src str, options: ParseOptions) -> Result<Vec<Token<'src>>, Error<'src>> {
+    let mut lexer = Lexer::new(line_number, input_source, options, false);
     while lexer.next_token() {}
     lexer.last_token()?;
     Ok(lexer.tokens)
 }
 
+/// Tokenises a line of Hexit in the same way as `lex_source`, but keeps the
+/// comments in the line as `Comment` tokens, rather than dropping them. This
+/// includes the front comment, if there is one and front comments have not
+/// been turned off, which becomes the first token. Tools that need to
+/// re-write a program without losing its comments use this.
+pub fn lex_source_with_comments<'src>(line_number: usize, input_source: &'src str, options: ParseOptions) -> Result<Vec<Token<'src>>, Error<'src>> {
+    let mut lexer = Lexer::new(line_number, input_source, options, true);
+    while lexer.next_token() {}
+    lexer.last_token()?;
+
+    if options.front_comments != FrontComments::Off {
+        lexer.front_comment_token();
+    }
+
+    Ok(lexer.tokens)
+}
+
 
 /// The lexing processor. A lexer analyses the input string, one character at
 /// a time, mutating an internal state depending on what the character was.
@@ -37,6 +56,22 @@ struct Lexer<'src> {
     /// get read from the iterator.
     column_number: usize,
 
+    /// The options that change what the lexer accepts, such as the width of
+    /// a tab stop, or which characters start a comment. This does not change.
+    options: ParseOptions,
+
+    /// Whether comments should be emitted as tokens, rather than dropped.
+    /// This does not change.
+    keep_comments: bool,
+
+    /// The index just past the last colon read outside of a string or form,
+    /// which is where the front comment ends, if there is one.
+    front_comment_end: Option<usize>,
+
+    /// How many `(` parentheses are currently open. Inside them, a colon
+    /// can join two alphanums into a bit field, such as `4:5`.
+    paren_depth: usize,
+
     /// The lexer’s current state, which changes as characters are read.
     state: State,
 
@@ -61,13 +96,17 @@ enum State {
     ReadAlphanum { anchor: Anchor },
 
     /// We have just read the opening `[` bracket of a form, or one of the
-    /// characters within the form.
-    ReadForm { anchor: Anchor },
+    /// characters within the form. We also need to store how many `[`
+    /// brackets inside the form are still open, so that forms can be nested,
+    /// and only the `]` matching the first bracket ends the form.
+    ReadForm { anchor: Anchor, depth: usize },
 
     /// We have just read the opening `"` quote of a quoted string, or one of
     /// the characters within the string. We also need to store whether we
-    /// have just read a backslash, in order to handle nested quotes.
-    ReadQuote { anchor: Anchor, backslash: bool },
+    /// have just read a backslash, in order to handle nested quotes, and the
+    /// encoding given by the string’s prefix, if it had one, in which case
+    /// the anchor is at the start of the prefix.
+    ReadQuote { anchor: Anchor, backslash: bool, encoding: Option<StringEncoding> },
 
     /// We are done parsing this line, so no further token should be produced.
     Done,
@@ -92,13 +131,15 @@ struct Anchor {
 impl<'src> Lexer<'src> {
 
     /// Creates a new lexer with the given parameters.
-    fn new(line_number: usize, input_source: &'src str) -> Self {
+    fn new(line_number: usize, input_source: &'src str, options: ParseOptions, keep_comments: bool) -> Self {
         let iter = input_source.char_indices();
         let column_number = 0;
         let state = State::Ready;
         let tokens = Vec::new();
+        let front_comment_end = None;
+        let paren_depth = 0;
 
-        Self { line_number, input_source, iter, column_number, state, tokens }
+        Self { line_number, input_source, iter, column_number, options, keep_comments, front_comment_end, paren_depth, state, tokens }
     }
 
     /// Analyses the next character from the iterator, possibly changing the
@@ -124,6 +165,15 @@ impl<'src> Lexer<'src> {
             (c, State::ReadAlphanum { .. }) if c.is_ascii_alphanumeric() || c == '_' => {
                 // continue reading alphanums
             }
+            ('.', State::ReadAlphanum { .. }) => {
+                // a namespaced constant, such as `dns.A`, which the parser
+                // checks the rest of
+            }
+            (':', State::ReadAlphanum { .. }) if self.paren_depth > 0 => {
+                // a bit field, such as `4:5`, which is only allowed inside
+                // parentheses so it cannot be mistaken for a front comment,
+                // and which the parser checks is an argument to `bits`
+            }
             (c, State::ReadWhitespace) if c.is_ascii_alphanumeric() || c == '_' => {
                 self.tokens.push(Token::Whitespace);
 
@@ -149,64 +199,100 @@ impl<'src> Lexer<'src> {
                 self.tokens.push(Token::Whitespace);
 
                 let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadForm { anchor: new_anchor };
+                self.state = State::ReadForm { anchor: new_anchor, depth: 0 };
             }
             ('[', State::Ready) => {
                 let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadForm { anchor: new_anchor };
+                self.state = State::ReadForm { anchor: new_anchor, depth: 0 };
             }
             ('[', State::ReadAlphanum { anchor }) => {
                 let alphanum_string = self.span(anchor, index);
                 self.tokens.push(Token::Alphanum(alphanum_string));
 
                 let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadForm { anchor: new_anchor };
+                self.state = State::ReadForm { anchor: new_anchor, depth: 0 };
+            }
+            ('[', State::ReadForm { anchor, depth }) => {
+                self.state = State::ReadForm { anchor, depth: depth + 1 };
             }
-            (']', State::ReadForm { mut anchor }) => {
+            (']', State::ReadForm { anchor, depth }) if depth > 0 => {
+                self.state = State::ReadForm { anchor, depth: depth - 1 };
+            }
+            (']', State::ReadForm { mut anchor, .. }) => {
                 anchor.index += 1;
                 let form_string = self.span(anchor, index);
                 self.tokens.push(Token::Form(form_string));
 
                 self.state = State::Ready;
             }
-            (_, State::ReadForm { anchor: _ }) => {
+            (_, State::ReadForm { .. }) => {
                 // continue reading the form
             }
 
             ('"', State::ReadAlphanum { anchor }) => {
                 let alphanum_string = self.span(anchor, index);
-                self.tokens.push(Token::Alphanum(alphanum_string));
 
-                let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadQuote { anchor: new_anchor, backslash: false };
+                if let Some(encoding) = StringEncoding::from_prefix(alphanum_string.contents) {
+                    self.state = State::ReadQuote { anchor, backslash: false, encoding: Some(encoding) };
+                }
+                else {
+                    self.tokens.push(Token::Alphanum(alphanum_string));
+
+                    let new_anchor = Anchor { index, column_number };
+                    self.state = State::ReadQuote { anchor: new_anchor, backslash: false, encoding: None };
+                }
             }
             ('"', State::ReadWhitespace) => {
                 self.tokens.push(Token::Whitespace);
 
                 let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadQuote { anchor: new_anchor, backslash: false };
+                self.state = State::ReadQuote { anchor: new_anchor, backslash: false, encoding: None };
             }
             ('"', State::Ready) => {
                 let new_anchor = Anchor { index, column_number };
-                self.state = State::ReadQuote { anchor: new_anchor, backslash: false };
+                self.state = State::ReadQuote { anchor: new_anchor, backslash: false, encoding: None };
             }
-            ('\\', State::ReadQuote { anchor, backslash: false }) => {
-                self.state = State::ReadQuote { anchor, backslash: true };
+            ('\\', State::ReadQuote { anchor, backslash: false, encoding }) => {
+                self.state = State::ReadQuote { anchor, backslash: true, encoding };
             }
-            ('"', State::ReadQuote { mut anchor, backslash: false }) => {
+            ('"', State::ReadQuote { mut anchor, backslash: false, encoding: None }) => {
                 anchor.index += 1;
                 let quoted = self.span(anchor, index);
                 self.tokens.push(Token::Quoted(quoted));
 
                 self.state = State::Ready;
             }
-            (_, State::ReadQuote { anchor, .. }) => {
+            ('"', State::ReadQuote { mut anchor, backslash: false, encoding: Some(encoding) }) => {
+                // the token stays placed at the prefix, but only holds what
+                // is inside the quotes
+                anchor.index += encoding.prefix().len() + 1;
+                let quoted = self.span(anchor, index);
+                self.tokens.push(Token::Encoded(encoding, quoted));
+
+                self.state = State::Ready;
+            }
+            (_, State::ReadQuote { anchor, encoding, .. }) => {
                 // continue reading the string, but switch off backslash flag
-                self.state = State::ReadQuote { anchor, backslash: false };
+                self.state = State::ReadQuote { anchor, backslash: false, encoding };
+            }
+
+            (c, State::Done) if c == '#' || c == ';' => {
+                // we are already done
             }
+            (c, state) if c == '#' || (c == ';' && self.options.semicolon_comments) => {
+                // we are done parsing this line, but alphanums that run right
+                // up to the comment still count
+                if let State::ReadAlphanum { anchor } = state {
+                    let alphanum_string = self.span(anchor, index);
+                    self.tokens.push(Token::Alphanum(alphanum_string));
+                }
+
+                if self.keep_comments {
+                    let comment_anchor = Anchor { index, column_number };
+                    let comment_string = self.span_rest(comment_anchor);
+                    self.tokens.push(Token::Comment(comment_string));
+                }
 
-            ('#', _) => {
-                // we are done parsing this line
                 self.state = State::Done;
             }
             (_, State::Done) => {
@@ -217,11 +303,31 @@ impl<'src> Lexer<'src> {
                 let char_position = Anchor { index, column_number };
                 let open_string = self.span(char_position, index + 1);
                 self.tokens.push(Token::Open(open_string));
+                self.paren_depth += 1;
             }
             (')', State::Ready) => {
                 let char_position = Anchor { index, column_number };
                 let close_string = self.span(char_position, index + 1);
                 self.tokens.push(Token::Close(close_string));
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+            }
+            ('(', State::ReadWhitespace) => {
+                self.tokens.push(Token::Whitespace);
+
+                let char_position = Anchor { index, column_number };
+                let open_string = self.span(char_position, index + 1);
+                self.tokens.push(Token::Open(open_string));
+                self.paren_depth += 1;
+                self.state = State::Ready;
+            }
+            (')', State::ReadWhitespace) => {
+                self.tokens.push(Token::Whitespace);
+
+                let char_position = Anchor { index, column_number };
+                let close_string = self.span(char_position, index + 1);
+                self.tokens.push(Token::Close(close_string));
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+                self.state = State::Ready;
             }
             ('(', State::ReadAlphanum { anchor }) => {
                 let alphanum_string = self.span(anchor, index);
@@ -230,6 +336,7 @@ impl<'src> Lexer<'src> {
                 let char_position = Anchor { index, column_number };
                 let open_string = self.span(char_position, index + 1);
                 self.tokens.push(Token::Open(open_string));
+                self.paren_depth += 1;
                 self.state = State::Ready;
             }
             (')', State::ReadAlphanum { anchor }) => {
@@ -239,18 +346,38 @@ impl<'src> Lexer<'src> {
                 let char_position = Anchor { index, column_number };
                 let close_string = self.span(char_position, index + 1);
                 self.tokens.push(Token::Close(close_string));
+                self.paren_depth = self.paren_depth.saturating_sub(1);
                 self.state = State::Ready;
             }
 
-            (c, _) => {
+            (c, state) => {
+                // alphanums that run right up to the stray character still
+                // count, in case the character turns out to be a colon
+                if let State::ReadAlphanum { anchor } = state {
+                    let alphanum_string = self.span(anchor, index);
+                    self.tokens.push(Token::Alphanum(alphanum_string));
+                }
+
                 let char_position = Anchor { index, column_number };
                 let char_string = self.span(char_position, index + c.len_utf8());
                 self.tokens.push(Token::Stray(char_string));
                 self.state = State::Ready;
+
+                if c == ':' {
+                    self.front_comment_end = Some(index + 1);
+                }
+            }
+        }
+
+        match (c, self.options.tab_width) {
+            ('\t', Some(width)) if width > 1 => {
+                self.column_number = (self.column_number / width + 1) * width;
+            }
+            _ => {
+                self.column_number += 1;
             }
         }
 
-        self.column_number += 1;
         true
     }
 
@@ -278,6 +405,18 @@ impl<'src> Lexer<'src> {
         }
     }
 
+    /// Adds the front comment, which runs from the start of the line up to
+    /// and including the last colon, as a token at the very start of the
+    /// vector, if there is one. The tokens within it are left alone, as the
+    /// front comment gets stripped out when reading.
+    fn front_comment_token(&mut self) {
+        if let Some(end) = self.front_comment_end {
+            let start = Anchor { index: 0, column_number: 0 };
+            let comment_string = self.span(start, end);
+            self.tokens.insert(0, Token::Comment(comment_string));
+        }
+    }
+
     /// Returns a `Placed` string slice of the original input string between
     /// the given anchor and end position.
     fn span(&self, anchor: Anchor, to: usize) -> Placed<&'src str> {
@@ -330,94 +469,293 @@ impl<'src> Error<'src> {
             Self::UnclosedForm(form)  => form,
         }
     }
+
+    /// Returns the name of this kind of error, for strings or forms that
+    /// never get closed.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnclosedString(_)  => "unclosed_string",
+            Self::UnclosedForm(_)    => "unclosed_form",
+        }
+    }
+
+    /// Returns the number of characters in the source line that the error
+    /// covers, starting from its position.
+    pub fn span_length(&self) -> usize {
+        self.source_pos().contents.chars().count()
+    }
 }
 
 
 #[cfg(test)]
 mod test {
+    use alloc::vec;
     use pretty_assertions::assert_eq;
     use crate::pos::At;
     use super::*;
 
     #[test]
     fn empty() {
-        assert_eq!(lex_source(0, ""),
+        assert_eq!(lex_source(0, "", ParseOptions::default()),
                    Ok(vec![ ]));
     }
 
     #[test]
     fn stray() {
-        assert_eq!(lex_source(0, "&"),
+        assert_eq!(lex_source(0, "&", ParseOptions::default()),
                    Ok(vec![ Token::Stray("&".at(0, 0)) ]));
     }
 
+    #[test]
+    fn namespaced_alphanum() {
+        assert_eq!(lex_source(0, "dns.A", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("dns.A".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn leading_dot() {
+        assert_eq!(lex_source(0, ".A", ParseOptions::default()),
+                   Ok(vec![ Token::Stray(".".at(0, 0)),
+                            Token::Alphanum("A".at(0, 1)) ]));
+    }
+
     #[test]
     fn utf8() {
-        assert_eq!(lex_source(0, "é"),
+        assert_eq!(lex_source(0, "é", ParseOptions::default()),
                    Ok(vec![ Token::Stray("é".at(0, 0)) ]));
     }
 
     #[test]
     fn utf8_column() {
-        assert_eq!(lex_source(0, "Aé"),
-                   Ok(vec![ Token::Stray("é".at(0, 1)) ]));
+        assert_eq!(lex_source(0, "Aé", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("A".at(0, 0)), Token::Stray("é".at(0, 1)) ]));
+    }
+
+    #[test]
+    fn tab_column() {
+        assert_eq!(lex_source(0, "\t&", ParseOptions::default()),
+                   Ok(vec![ Token::Stray("&".at(0, 1)) ]));
+    }
+
+    #[test]
+    fn tab_column_expanded() {
+        let options = ParseOptions { tab_width: Some(4), ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "\t&", options),
+                   Ok(vec![ Token::Stray("&".at(0, 4)) ]));
+    }
+
+    #[test]
+    fn tab_column_to_next_stop() {
+        let options = ParseOptions { tab_width: Some(8), ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "AB\t\t&", options),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)), Token::Stray("&".at(0, 16)) ]));
+    }
+
+    #[test]
+    fn hash_comment() {
+        assert_eq!(lex_source(0, "AB # comment", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn hash_comment_directly_after() {
+        assert_eq!(lex_source(0, "AB#CD", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn semicolon_by_default() {
+        assert_eq!(lex_source(0, "AB ;", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)), Token::Stray(";".at(0, 3)) ]));
+    }
+
+    #[test]
+    fn semicolon_comment() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "AB ; comment (with parens", options),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn semicolon_comment_directly_after() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "AB;CD", options),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn semicolon_in_string() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "\"a;b\" CD", options),
+                   Ok(vec![ Token::Quoted("a;b".at(0, 0)), Token::Whitespace, Token::Alphanum("CD".at(0, 6)) ]));
+    }
+
+    #[test]
+    fn semicolon_in_form() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(lex_source(0, "[a;b]", options),
+                   Ok(vec![ Token::Form("a;b".at(0, 0)) ]));
+    }
+
+    #[test]
+    fn comments_kept() {
+        assert_eq!(lex_source_with_comments(0, "AB # comment", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(0, 0)), Token::Comment("# comment".at(0, 3)) ]));
+    }
+
+    #[test]
+    fn comment_directly_after_kept() {
+        assert_eq!(lex_source_with_comments(1, "AB#CD", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(1, 0)), Token::Comment("#CD".at(1, 2)) ]));
+    }
+
+    #[test]
+    fn semicolon_comment_kept() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(lex_source_with_comments(2, "\"#\" ; hash", options),
+                   Ok(vec![ Token::Quoted("#".at(2, 0)), Token::Comment("; hash".at(2, 4)) ]));
+    }
+
+    #[test]
+    fn front_comment_kept() {
+        assert_eq!(lex_source_with_comments(3, "Size: 01 # one", ParseOptions::default()),
+                   Ok(vec![ Token::Comment("Size:".at(3, 0)),
+                            Token::Alphanum("Size".at(3, 0)),
+                            Token::Stray(":".at(3, 4)),
+                            Token::Whitespace,
+                            Token::Alphanum("01".at(3, 6)),
+                            Token::Comment("# one".at(3, 9)) ]));
     }
 
     #[test]
     fn some_bytes() {
-        assert_eq!(lex_source(1, "1A2B"),
+        assert_eq!(lex_source(1, "1A2B", ParseOptions::default()),
                    Ok(vec![ Token::Alphanum("1A2B".at(1, 0)) ]));
     }
 
     #[test]
     fn a_quoted() {
-        assert_eq!(lex_source(2, "\"PANL\""),
+        assert_eq!(lex_source(2, "\"PANL\"", ParseOptions::default()),
                    Ok(vec![ Token::Quoted("PANL".at(2, 0)) ]));
     }
 
     #[test]
     fn a_form() {
-        assert_eq!(lex_source(3, "[FORM]"),
+        assert_eq!(lex_source(3, "[FORM]", ParseOptions::default()),
                    Ok(vec![ Token::Form("FORM".at(3, 0)) ]));
     }
 
     #[test]
     fn eventually_a_quoted() {
-        assert_eq!(lex_source(4, "    \"PANL\""),
+        assert_eq!(lex_source(4, "    \"PANL\"", ParseOptions::default()),
                    Ok(vec![ Token::Whitespace,
                             Token::Quoted("PANL".at(4, 4)) ]));
     }
 
     #[test]
     fn eventually_a_form() {
-        assert_eq!(lex_source(5, "    [FORM]"),
+        assert_eq!(lex_source(5, "    [FORM]", ParseOptions::default()),
                    Ok(vec![ Token::Whitespace,
                             Token::Form("FORM".at(5, 4)) ]));
     }
 
     #[test]
     fn unclosed_quote() {
-        assert_eq!(lex_source(6, "\"FORM"),
+        assert_eq!(lex_source(6, "\"FORM", ParseOptions::default()),
                    Err(Error::UnclosedString("\"FORM".at(6, 0))));
     }
 
     #[test]
     fn unclosed_form() {
-        assert_eq!(lex_source(7, "[FORM"),
+        assert_eq!(lex_source(7, "[FORM", ParseOptions::default()),
                    Err(Error::UnclosedForm("[FORM".at(7, 0))));
     }
 
+    #[test]
+    fn nested_form() {
+        assert_eq!(lex_source(7, "[ipv4:[127.0.0.1]]", ParseOptions::default()),
+                   Ok(vec![ Token::Form("ipv4:[127.0.0.1]".at(7, 0)) ]));
+    }
+
+    #[test]
+    fn doubly_nested_form() {
+        assert_eq!(lex_source(7, "[a[b[c]]d] AB", ParseOptions::default()),
+                   Ok(vec![ Token::Form("a[b[c]]d".at(7, 0)),
+                            Token::Whitespace,
+                            Token::Alphanum("AB".at(7, 11)) ]));
+    }
+
+    #[test]
+    fn nested_forms_in_a_row() {
+        assert_eq!(lex_source(7, "[[:alpha:]][x]", ParseOptions::default()),
+                   Ok(vec![ Token::Form("[:alpha:]".at(7, 0)),
+                            Token::Form("x".at(7, 11)) ]));
+    }
+
+    #[test]
+    fn unclosed_nested_form() {
+        assert_eq!(lex_source(7, "[u16:[1]", ParseOptions::default()),
+                   Err(Error::UnclosedForm("[u16:[1]".at(7, 0))));
+    }
+
     #[test]
     fn in_parentheses() {
-        assert_eq!(lex_source(8, "(AB34)"),
+        assert_eq!(lex_source(8, "(AB34)", ParseOptions::default()),
                    Ok(vec![ Token::Open("(".at(8, 0)),
                             Token::Alphanum("AB34".at(8, 1)),
                             Token::Close(")".at(8, 5)) ]));
     }
 
+    #[test]
+    fn parentheses_after_whitespace() {
+        assert_eq!(lex_source(8, "AB ( CD )", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(8, 0)),
+                            Token::Whitespace,
+                            Token::Open("(".at(8, 3)),
+                            Token::Whitespace,
+                            Token::Alphanum("CD".at(8, 5)),
+                            Token::Whitespace,
+                            Token::Close(")".at(8, 8)) ]));
+    }
+
+    #[test]
+    fn bit_fields() {
+        assert_eq!(lex_source(9, "bits(4:4 4:5)", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("bits".at(9, 0)),
+                            Token::Open("(".at(9, 4)),
+                            Token::Alphanum("4:4".at(9, 5)),
+                            Token::Whitespace,
+                            Token::Alphanum("4:5".at(9, 9)),
+                            Token::Close(")".at(9, 12)) ]));
+    }
+
+    #[test]
+    fn colon_in_other_parentheses() {
+        // the lexer does not know which function the parentheses belong to,
+        // so this is left for the parser to reject
+        assert_eq!(lex_source(9, "x2(4:5)", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("x2".at(9, 0)),
+                            Token::Open("(".at(9, 2)),
+                            Token::Alphanum("4:5".at(9, 3)),
+                            Token::Close(")".at(9, 6)) ]));
+    }
+
+    #[test]
+    fn colon_after_parentheses() {
+        assert_eq!(lex_source(9, "x2(AB) 4:5", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("x2".at(9, 0)),
+                            Token::Open("(".at(9, 2)),
+                            Token::Alphanum("AB".at(9, 3)),
+                            Token::Close(")".at(9, 5)),
+                            Token::Whitespace,
+                            Token::Alphanum("4".at(9, 7)),
+                            Token::Stray(":".at(9, 8)),
+                            Token::Alphanum("5".at(9, 9)) ]));
+    }
+
     #[test]
     fn function_call() {
-        assert_eq!(lex_source(9, "x86(AB34)"),
+        assert_eq!(lex_source(9, "x86(AB34)", ParseOptions::default()),
                    Ok(vec![ Token::Alphanum("x86".at(9, 0)),
                             Token::Open("(".at(9, 3)),
                             Token::Alphanum("AB34".at(9, 4)),
@@ -426,7 +764,7 @@ mod test {
 
     #[test]
     fn surrounded_by_quotes() {
-        assert_eq!(lex_source(10, "\"\"\"\"A\"\"\"\""),
+        assert_eq!(lex_source(10, "\"\"\"\"A\"\"\"\"", ParseOptions::default()),
                    Ok(vec![ Token::Quoted("".at(10, 0)),
                             Token::Quoted("".at(10, 2)),
                             Token::Alphanum("A".at(10, 4)),
@@ -436,13 +774,13 @@ mod test {
 
     #[test]
     fn quotes_backslashes() {
-        assert_eq!(lex_source(11, "\"\\\"\""),
+        assert_eq!(lex_source(11, "\"\\\"\"", ParseOptions::default()),
                    Ok(vec![ Token::Quoted("\\\"".at(11, 0)) ]));
     }
 
     #[test]
     fn mixture() {
-        assert_eq!(lex_source(12, "1A2B[FORM]\"PANL\"[FORM]1A2B\"PANL\"1A2B"),
+        assert_eq!(lex_source(12, "1A2B[FORM]\"PANL\"[FORM]1A2B\"PANL\"1A2B", ParseOptions::default()),
                    Ok(vec![ Token::Alphanum("1A2B".at(12, 0)),
                             Token::Form("FORM".at(12, 4)),
                             Token::Quoted("PANL".at(12, 10)),
@@ -454,7 +792,7 @@ mod test {
 
     #[test]
     fn a_lowly_underscore() {
-        assert_eq!(lex_source(13, "___ _"),
+        assert_eq!(lex_source(13, "___ _", ParseOptions::default()),
                    Ok(vec![ Token::Alphanum("___".at(13, 0)),
                             Token::Whitespace,
                             Token::Alphanum("_".at(13, 4)), ]));
@@ -462,8 +800,29 @@ mod test {
 
     #[test]
     fn whitespace_then_quoted_nothing() {
-        assert_eq!(lex_source(14, "    \"\""),
+        assert_eq!(lex_source(14, "    \"\"", ParseOptions::default()),
                    Ok(vec![ Token::Whitespace,
                             Token::Quoted("".at(14, 4)) ]));
     }
+
+    #[test]
+    fn base32_string() {
+        assert_eq!(lex_source(15, "AB b32\"MFRGG===\"", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("AB".at(15, 0)),
+                            Token::Whitespace,
+                            Token::Encoded(StringEncoding::Base32, "MFRGG===".at(15, 3)) ]));
+    }
+
+    #[test]
+    fn unknown_prefix_is_alphanums() {
+        assert_eq!(lex_source(16, "b64\"YWJj\"", ParseOptions::default()),
+                   Ok(vec![ Token::Alphanum("b64".at(16, 0)),
+                            Token::Quoted("YWJj".at(16, 3)) ]));
+    }
+
+    #[test]
+    fn unclosed_base32_string() {
+        assert_eq!(lex_source(17, "b32\"MFRG", ParseOptions::default()),
+                   Err(Error::UnclosedString("b32\"MFRG".at(17, 0))));
+    }
 }
@@ -10,6 +10,21 @@
 //!    series of `Token` values, which get parsed into a series of `Exp` values.
 //! 2. Next, once all of the input program has been read, it gets “run” — the
 //!    expressions are evaluated, resulting in a series of bytes.
+//!
+//! Programs too large to read all at once can instead be read and run one
+//! line at a time using a `LineRunner`.
+//!
+//! Every error and warning has a `kind` method that returns a short name for
+//! it, such as `unknown_constant`, for tools that want to tell them apart
+//! without parsing the message. Messages may get reworded, but these names
+//! stay the same once they have been released, so they are safe to match on.
+//!
+//! The standard library is only needed to read timestamp forms, such as
+//! `[2017-12-31T21:36:45]`, so it can be turned off by disabling the ‘std’
+//! feature, leaving a crate that only needs `alloc`. Timestamps are not
+//! recognised without it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
 #![warn(future_incompatible)]
 #![warn(missing_copy_implementations)]
@@ -33,21 +48,100 @@
 #![deny(clippy::cast_sign_loss)]
 #![deny(unsafe_code)]
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+
 use log::*;
 
-mod ast;
+pub mod ast;
 pub mod constants;
 mod eval;
 mod lex;
 mod parse;
 mod pos;
 mod read;
+mod stream;
 mod tokens;
+pub mod verify;
+
+pub use eval::{number_to_both, Width, Evaluator, EvalEvent, LocatedError, BytesIter, Value, MultiByteValue, Endianness};
+pub use pos::Placed;
+pub use stream::{LineRunner, LineError};
+
+
+/// Options that change how a Hexit program gets read.
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+pub struct ParseOptions {
+
+    /// Whether a repeat function with an amount of zero, such as `x0(AB)`,
+    /// should be allowed, producing no bytes. This is usually a mistake, so
+    /// it’s rejected by default, but programs that have been generated by
+    /// another program may legitimately compute a repeat count of zero.
+    pub allow_zero_repeats: bool,
+
+    /// The width of a tab stop, used when working out the column numbers
+    /// shown in errors, so they line up with how an editor displays the
+    /// line. By default, a tab counts as one column, like any character.
+    pub tab_width: Option<usize>,
+
+    /// Whether a `;` should start a comment that lasts until the end of the
+    /// line, just like a `#`, for those used to writing assembly. This is
+    /// off by default, leaving `;` as a stray symbol.
+    pub semicolon_comments: bool,
+
+    /// How everything before the last colon on a line gets treated. By
+    /// default, it’s a front comment, and gets ignored.
+    pub front_comments: FrontComments,
+
+    /// Whether to check for things that are valid, but that look like they
+    /// could be mistakes, such as a run of hex letters like `DEAD` that reads
+    /// like a word. These are returned as warnings. Linting is off by
+    /// default.
+    pub lint: bool,
+}
+
+
+/// How the text before the last colon on a line, known as a front comment,
+/// gets treated when reading a program.
+#[derive(PartialEq, Debug, Default, Copy, Clone)]
+pub enum FrontComments {
+
+    /// Everything before the last colon is a comment, and gets ignored.
+    #[default]
+    Ignored,
+
+    /// Everything before the last colon is a comment, but it gets checked
+    /// for values that it hides, such as the `03` in `03: 04`, which was
+    /// probably not meant to be a comment. These are returned as warnings.
+    /// This means parsing each part of every front comment.
+    Checked,
+
+    /// There are no front comments, for input that needs colons in the
+    /// middle of a line. Colons are stray symbols, unless they are part of
+    /// a form.
+    Off,
+}
+
+
+/// The position in the source of each top-level expression in a program,
+/// along with the range of the output that it produced.
+pub type Spans<'src> = Vec<(Placed<&'src str>, Range<usize>)>;
 
 
 /// A Hexit program.
 pub struct Program<'src> {
     exps: Vec<ast::Exp<'src>>,
+
+    /// The position in the source that each expression originated from, in
+    /// the same order as the expressions.
+    positions: Vec<Placed<&'src str>>,
+
+    /// Anything suspicious found while reading the program.
+    warnings: Vec<read::Warning<'src>>,
 }
 
 impl<'src> Program<'src> {
@@ -55,7 +149,16 @@ impl<'src> Program<'src> {
     /// Reads a Hexit program from a series of strings of Hexit source,
     /// returning a valid program or at least one read error.
     pub fn read(input_source_lines: &'src [impl AsRef<str>]) -> Result<Self, Vec<read::Error<'src>>> {
+        Self::read_with_options(input_source_lines, ParseOptions::default())
+    }
+
+    /// Reads a Hexit program from a series of strings of Hexit source using
+    /// the given parse options, returning a valid program or at least one
+    /// read error.
+    pub fn read_with_options(input_source_lines: &'src [impl AsRef<str>], options: ParseOptions) -> Result<Self, Vec<read::Error<'src>>> {
         let mut all_exps = Vec::new();
+        let mut all_positions = Vec::new();
+        let mut all_warnings = Vec::new();
         let mut all_errors = Vec::new();
 
         for (line_index, input_line) in input_source_lines.iter().enumerate() {
@@ -63,26 +166,177 @@ impl<'src> Program<'src> {
             debug!("Reading line → {:?}", input_line);
 
             let line_number = line_index + 1;
-            match read::tokenise_and_parse(input_line, line_number) {
-                Ok(exps)  => all_exps.extend(exps),
+            let input_line = read::skip_byte_order_mark(input_line, line_number);
+            match read::read_line(input_line, line_number, options) {
+                Ok((exps, warnings))  => {
+                    for (exp, position) in exps {
+                        all_exps.push(exp);
+                        all_positions.push(position);
+                    }
+
+                    all_warnings.extend(warnings);
+                }
                 Err(e)    => all_errors.push(e),
             };
         }
 
         if all_errors.is_empty() {
-            Ok(Self { exps: all_exps })
+            Ok(Self { exps: all_exps, positions: all_positions, warnings: all_warnings })
+        }
+        else {
+            Err(all_errors)
+        }
+    }
+
+    /// Reads the comments from a series of strings of Hexit source, along
+    /// with their positions, including any front comments. Tools that need
+    /// to re-write a program without losing its comments use this, as
+    /// comments are not part of a `Program`.
+    pub fn read_comments(input_source_lines: &'src [impl AsRef<str>], options: ParseOptions) -> Result<Vec<Placed<&'src str>>, Vec<read::Error<'src>>> {
+        let mut all_comments = Vec::new();
+        let mut all_errors = Vec::new();
+
+        for (line_index, input_line) in input_source_lines.iter().enumerate() {
+            let line_number = line_index + 1;
+            let input_line = read::skip_byte_order_mark(input_line.as_ref(), line_number);
+            match read::comments(input_line, line_number, options) {
+                Ok(comments)  => all_comments.extend(comments),
+                Err(e)        => all_errors.push(e),
+            }
+        }
+
+        if all_errors.is_empty() {
+            Ok(all_comments)
         }
         else {
             Err(all_errors)
         }
     }
 
+    /// Returns the expressions that make up this program, in order.
+    pub fn exps(&self) -> &[ast::Exp<'src>] {
+        &self.exps
+    }
+
+    /// Returns the warnings found while reading this program, such as values
+    /// hidden by a front comment, and any lints, if they were asked for in
+    /// the parse options. These do not stop the program from running.
+    pub fn warnings(&self) -> &[read::Warning<'src>] {
+        &self.warnings
+    }
+
     /// Runs this Hexit program, returning the vector of bytes that it has
-    /// produced, or an evaluation error.
+    /// produced, or an evaluation error. The output can be limited overall.
+    /// To run a program more than once, use an `Evaluator` instead.
     pub fn run(self, constants: &constants::Table, limit: Option<usize>) -> Result<Vec<u8>, eval::Error<'src>> {
-        debug!("Running expressions → {:#?}", self.exps);
+        self.run_located(constants, limit).map_err(|e| e.error)
+    }
+
+    /// Runs this Hexit program in the same way as `run`, but returns an error
+    /// along with the position of the expression that caused it, so that it
+    /// can be pointed at in the source.
+    pub fn run_located(self, constants: &constants::Table, limit: Option<usize>) -> Result<Vec<u8>, LocatedError<'src>> {
+        self.run_with_line_limit(constants, limit, None)
+    }
+
+    /// Runs this Hexit program in the same way as `run_located`, also
+    /// limiting the output of each top-level expression, so that one runaway
+    /// repeat gets reported on its own rather than as the whole output being
+    /// too long.
+    pub fn run_with_line_limit(self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> Result<Vec<u8>, LocatedError<'src>> {
+        self.run_ref(constants, limit, line_limit)
+    }
+
+    /// Runs this Hexit program in the same way as `run_with_line_limit`, but
+    /// without consuming it, so a program can be read once and then run repeatedly,
+    /// with different limits or constants tables each time.
+    pub fn run_ref(&self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> Result<Vec<u8>, LocatedError<'src>> {
+        Evaluator::new(constants, limit, line_limit).evaluate(self)
+    }
+
+    /// Runs this Hexit program in the same way as `run_ref`, calling the given
+    /// function for each expression evaluated, each function run, and each
+    /// range of the output produced, so that tools can trace the program or
+    /// measure its coverage.
+    pub fn run_with_hook(&self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>, hook: &mut dyn FnMut(EvalEvent<'_, 'src>)) -> Result<Vec<u8>, LocatedError<'src>> {
+        Evaluator::new(constants, limit, line_limit).evaluate_with_hook(self, hook)
+    }
+
+    /// Runs this Hexit program in the same way as `run_ref`, but returns the
+    /// value that each top-level expression evaluated to, rather than the
+    /// bytes they produce, so that an output stage can see their types.
+    pub fn run_values(&self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> Result<Vec<Value<'src>>, LocatedError<'src>> {
+        Evaluator::new(constants, limit, line_limit).evaluate_values(self)
+    }
+
+    /// Checks this Hexit program for anything that looks like a mistake but
+    /// that needs the constants and limits to find, such as a suspiciously
+    /// large repeat, without running it. These are separate from the
+    /// `warnings` found while reading the program.
+    pub fn lint(&self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> Vec<read::Warning<'src>> {
+        Evaluator::new(constants, limit, line_limit).lint(self)
+    }
+
+    /// Returns an iterator over the bytes this Hexit program produces, for
+    /// code that wants to consume them one at a time rather than as a vector.
+    /// Each top-level expression only gets evaluated once the bytes from the
+    /// one before it have been used up. An error takes the place of the
+    /// bytes from the expression that caused it, and ends the iteration.
+    pub fn bytes_iter<'prog, 'consts>(&'prog self, constants: &'consts constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> BytesIter<'prog, 'consts, 'src> {
+        Evaluator::new(constants, limit, line_limit).into_bytes_iter(self)
+    }
+
+    /// Runs this Hexit program in the same way as `run`, also returning the
+    /// range of the output that each top-level expression produced, along
+    /// with the position in the source that the expression came from.
+    pub fn run_with_spans(self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>) -> Result<(Vec<u8>, Spans<'src>), LocatedError<'src>> {
+        Evaluator::new(constants, limit, line_limit).evaluate_with_spans(&self)
+    }
+
+    /// Runs this Hexit program in the same way as `run`, without any limits,
+    /// returning its bytes as an array of exactly the given length. This is
+    /// for embedders building structures that have a fixed size, such as
+    /// headers; an error is returned if the program produces more or fewer
+    /// bytes than that.
+    pub fn run_exact<const N: usize>(self, constants: &constants::Table) -> Result<[u8; N], verify::ExactRunError<'src>> {
+        let bytes = self.run_located(constants, None).map_err(verify::ExactRunError::Run)?;
+
+        <[u8; N]>::try_from(bytes).map_err(|bytes| {
+            let kind = verify::VerificationKind::ExactLength;
+            verify::ExactRunError::Length(verify::VerificationError { expected: N, actual: bytes.len(), kind })
+        })
+    }
+
+    /// Runs this Hexit program in the same way as `run`, returning the bytes
+    /// split into groups of the given size. The last group is shorter if the
+    /// bytes do not divide evenly, and there are no groups at all if the
+    /// program produces no bytes.
+    pub fn to_bytes_grouped(self, constants: &constants::Table, limit: Option<usize>, line_limit: Option<usize>, group_size: NonZeroUsize) -> Result<Vec<Vec<u8>>, LocatedError<'src>> {
+        let bytes = self.run_with_line_limit(constants, limit, line_limit)?;
+        Ok(bytes.chunks(group_size.get()).map(<[u8]>::to_vec).collect())
+    }
+}
+
+
+/// These only get compiled without the ‘std’ feature, to check that a
+/// program can still be read and run with nothing but `alloc`.
+#[cfg(all(test, not(feature = "std")))]
+mod alloc_only_test {
+    use super::*;
+    use alloc::vec;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn read_and_run() {
+        let lines = [ "AB x2(CD) be16[256] [127.0.0.1] \"e\"" ];
+        let program = Program::read(&lines).expect("Parsing failed");
+        assert_eq!(program.run(&constants::Table::builtin_set(), Some(1000)),
+                   Ok(vec![ 0xAB, 0xCD, 0xCD, 0x01, 0x00, 127, 0, 0, 1, b'e' ]));
+    }
 
-        let bytes = eval::evaluate_exps(self.exps, constants, limit)?;
-        Ok(bytes)
+    #[test]
+    fn timestamps_unrecognised() {
+        let lines = [ "be32[2017-12-31T21:36:45]" ];
+        assert!(Program::read(&lines).is_err());
     }
 }
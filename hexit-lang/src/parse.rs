@@ -1,23 +1,28 @@
 //! The parsing stage, which involves taking a series of `Token` values and
 //! building a series of `Exp` values.
 
-use std::borrow::Cow;
-use std::fmt;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::convert::TryFrom;
+use core::fmt;
 
 use log::*;
 
 use crate::ast::*;
 use crate::pos::Placed;
-use crate::tokens::Token;
+use crate::tokens::{Token, StringEncoding};
+use crate::ParseOptions;
 
 
-/// Parses the given iterator of tokens into a vector of expressions, failing
-/// at the first parse error.
-pub fn parse_tokens<'src>(iter: impl IntoIterator<Item=Token<'src>>) -> Result<Vec<Exp<'src>>, Error<'src>> {
+/// Parses the given iterator of tokens into a vector of expressions using
+/// the given options, failing at the first parse error. Each expression is
+/// paired with the position of the token it originated from.
+pub fn parse_tokens<'src>(iter: impl IntoIterator<Item=Token<'src>>, options: ParseOptions) -> Result<Vec<(Exp<'src>, Placed<&'src str>)>, Error<'src>> {
     let mut iter = iter.into_iter();
-    let mut parser = Parser::new(&mut iter);
+    let mut parser = Parser::new(&mut iter, options);
     parser.parse()?;
-    Ok(parser.exps)
+    Ok(parser.exps.into_iter().zip(parser.positions).collect())
 }
 
 /// The internal parser.
@@ -29,13 +34,25 @@ struct Parser<'iter, 'src, I> {
     /// The list of expressions that gets built up over time.
     exps: Vec<Exp<'src>>,
 
+    /// The position of the token that each expression originated from, such
+    /// as the name of a function, in the same order as the expressions.
+    positions: Vec<Placed<&'src str>>,
+
     /// The parser’s current state.
     state: State<'src>,
 
-    /// If this parser is parsing tokens that occur after the open parenthesis
-    /// of a function, rather than at the top-level, this holds the name and
-    /// position of the function name.
-    enclosing_function_name: Option<Placed<&'src str>>,
+    /// If this parser is parsing tokens that occur after an open parenthesis,
+    /// rather than at the top-level, this holds what the parenthesis opened.
+    enclosing: Option<Enclosing<'src>>,
+
+    /// The function whose arguments this parser is reading, if any, which
+    /// groups inside them carry on from. Some functions accept things in
+    /// their arguments that are not allowed anywhere else, such as the bit
+    /// fields in `bits(4:4 4:5)` or the plain decimal in `q8_8([1.5])`.
+    arguments_of: Option<FunctionName>,
+
+    /// The options that change what the parser accepts. This does not change.
+    options: ParseOptions,
 }
 
 /// The state of a parser.
@@ -52,15 +69,31 @@ enum State<'src> {
     ReadAlphanum(Placed<&'src str>),
 }
 
+/// What an open parenthesis that a sub-parser is reading the contents of
+/// belongs to.
+#[derive(Debug, Copy, Clone)]
+enum Enclosing<'src> {
+
+    /// The parenthesis holds the arguments to a function, with the given
+    /// name and position.
+    Function(Placed<&'src str>),
+
+    /// The parenthesis has no function name before it, and just groups its
+    /// contents together. This holds the position of the parenthesis.
+    Group(Placed<&'src str>),
+}
+
 
 impl<'iter, 'src, I> Parser<'iter, 'src, I> {
 
     /// Creates a new parser that reads from the given iterator.
-    fn new(iter: &'iter mut I) -> Self {
+    fn new(iter: &'iter mut I, options: ParseOptions) -> Self {
         let state = State::Ready;
         let exps = Vec::new();
-        let enclosing_function_name = None;
-        Self { iter, exps, state, enclosing_function_name }
+        let positions = Vec::new();
+        let enclosing = None;
+        let arguments_of = None;
+        Self { iter, exps, positions, state, enclosing, arguments_of, options }
     }
 }
 
@@ -83,72 +116,100 @@ impl<'iter, 'src, I: 'iter + Iterator<Item=Token<'src>>> Parser<'iter, 'src, I>
                 }
 
                 (Token::Open(_), State::ReadAlphanum(slice)) => {
-                    let mut sub_parser = Parser::new(self.iter);
-                    sub_parser.enclosing_function_name = Some(slice);
+                    let mut sub_parser = Parser::new(self.iter, self.options);
+                    sub_parser.enclosing = Some(Enclosing::Function(slice));
+                    sub_parser.arguments_of = parse_function_name(slice, self.options).ok().flatten();
                     sub_parser.parse()?;
                     let args = sub_parser.exps;
 
-                    let name = match parse_function_name(slice)? {
+                    let name = match parse_function_name(slice, self.options)? {
                         Some(n) => n,
                         None    => return Err(Error::InvalidFunctionName(slice)),
                     };
 
-                    self.exps.push(Exp::Function { name, args });
+                    self.push(Exp::Function { name, position: slice, args }, slice);
                     self.state = State::Ready;
                 }
 
                 (Token::Open(span), State::Ready) => {
-                    return Err(Error::StrayCharacter(span));
+                    let mut sub_parser = Parser::new(self.iter, self.options);
+                    sub_parser.enclosing = Some(Enclosing::Group(span));
+                    sub_parser.arguments_of = self.arguments_of;
+                    sub_parser.parse()?;
+                    let exps = sub_parser.exps;
+
+                    self.push(Exp::Group(exps), span);
                 }
 
                 (Token::Close(span), State::Ready) => {
-                    if self.enclosing_function_name.is_none() {
+                    if self.enclosing.is_none() {
                         return Err(Error::StrayCharacter(span));
                     }
 
                     self.state = State::Ready;
-                    self.enclosing_function_name = None;  // skip check below
+                    self.enclosing = None;  // skip check below
                     break;
                 }
                 (Token::Close(span), State::ReadAlphanum(slice)) => {
-                    if self.enclosing_function_name.is_none() {
+                    if self.enclosing.is_none() {
                         return Err(Error::StrayCharacter(span));
                     }
 
-                    let alphanums = parse_alphanums(slice)?;
+                    let alphanums = self.read_alphanums(slice)?;
                     self.add(alphanums, slice)?;
                     self.state = State::Ready;
-                    self.enclosing_function_name = None;  // skip check below
+                    self.enclosing = None;  // skip check below
                     break;
                 }
 
                 (Token::Form(form_slice), State::ReadAlphanum(alpha_slice)) => {
-                    let form = parse_form(form_slice)?;
+                    let name = parse_function_name(alpha_slice, self.options);
+
+                    // ‘be16[PORT_HTTPS]’ is short for ‘be16(PORT_HTTPS)’, in
+                    // the same way that ‘be16[443]’ is short for ‘be16([443])’,
+                    // as long as the name is not a form of its own
+                    let form = match parse_argument_form(form_slice, *name.as_ref().unwrap_or(&None)) {
+                        Err(Error::InvalidForm(_)) if is_constant_name(form_slice.contents) => Exp::Constant { name: form_slice },
+                        form => form?,
+                    };
 
-                    let name = match parse_function_name(alpha_slice)? {
+                    let name = match name? {
                         Some(n) => n,
                         None    => return Err(Error::InvalidFunctionName(alpha_slice)),
                     };
 
-                    self.exps.push(Exp::Function { name, args: vec![ form ] });
+                    self.push(Exp::Function { name, position: alpha_slice, args: vec![ form ] }, alpha_slice);
                     self.state = State::Ready;
                 }
 
                 (Token::Form(slice), State::Ready) => {
-                    let form = parse_form(slice)?;
-                    self.exps.push(form);
+                    let form = parse_argument_form(slice, self.arguments_of)?;
+                    self.push(form, slice);
                 }
 
                 (Token::Quoted(slice), State::Ready) => {
-                    let chars = parse_backslashes(slice)?;
-                    self.exps.push(Exp::StringLiteral { chars });
+                    let bytes = parse_backslashes(quoted_contents(slice))?;
+                    self.push(Exp::StringLiteral { bytes }, slice);
                 }
 
                 (Token::Quoted(quote_slice), State::ReadAlphanum(alpha_slice)) => {
-                    let alphanums = parse_alphanums(alpha_slice)?;
+                    let alphanums = self.read_alphanums(alpha_slice)?;
+                    self.add(alphanums, alpha_slice)?;
+                    let bytes = parse_backslashes(quoted_contents(quote_slice))?;
+                    self.push(Exp::StringLiteral { bytes }, quote_slice);
+                    self.state = State::Ready;
+                }
+
+                (Token::Encoded(encoding, slice), State::Ready) => {
+                    let bytes = decode_string(encoding, encoded_contents(encoding, slice))?;
+                    self.push(Exp::StringLiteral { bytes: bytes.into() }, slice);
+                }
+
+                (Token::Encoded(encoding, slice), State::ReadAlphanum(alpha_slice)) => {
+                    let alphanums = self.read_alphanums(alpha_slice)?;
                     self.add(alphanums, alpha_slice)?;
-                    let chars = parse_backslashes(quote_slice)?;
-                    self.exps.push(Exp::StringLiteral { chars });
+                    let bytes = decode_string(encoding, encoded_contents(encoding, slice))?;
+                    self.push(Exp::StringLiteral { bytes: bytes.into() }, slice);
                     self.state = State::Ready;
                 }
 
@@ -157,11 +218,15 @@ impl<'iter, 'src, I: 'iter + Iterator<Item=Token<'src>>> Parser<'iter, 'src, I>
                 }
 
                 (Token::Whitespace, State::ReadAlphanum(slice)) => {
-                    let alphanums = parse_alphanums(slice)?;
+                    let alphanums = self.read_alphanums(slice)?;
                     self.add(alphanums, slice)?;
                     self.state = State::Ready;
                 }
 
+                (Token::Comment(_), _) => {
+                    // comments produce nothing
+                }
+
                 (Token::Stray(_), _) => {
                     unreachable!("Stray token not filtered out by ‘read’");
                 }
@@ -170,26 +235,63 @@ impl<'iter, 'src, I: 'iter + Iterator<Item=Token<'src>>> Parser<'iter, 'src, I>
             trace!("Parse state → {:?}", self.state);
         }
 
-        if let Some(function_name) = self.enclosing_function_name {
-            return Err(Error::UnclosedFunction(function_name));
+        match self.enclosing {
+            Some(Enclosing::Function(function_name)) => {
+                return Err(Error::UnclosedFunction(function_name));
+            }
+            Some(Enclosing::Group(open)) => {
+                return Err(Error::UnclosedGroup(open));
+            }
+            None => {}
         }
 
         if let State::ReadAlphanum(slice) = self.state {
-            let alphanums = parse_alphanums(slice)?;
+            let alphanums = self.read_alphanums(slice)?;
             self.add(alphanums, slice)?;
         }
 
         Ok(())
     }
 
+    /// Parses a string of alphanumeric characters, first checking that it is
+    /// not a bit field in a place where bit fields are not allowed.
+    fn read_alphanums(&self, slice: Placed<&'src str>) -> Result<Alphanums<'src>, Error<'src>> {
+        if slice.contents.contains(':') && self.arguments_of != Some(FunctionName::Bits) {
+            return Err(Error::MisplacedBitField(slice));
+        }
+
+        parse_alphanums(slice, self.options)
+    }
+
+    /// Adds an expression, along with the position it originated from.
+    fn push(&mut self, exp: Exp<'src>, position: Placed<&'src str>) {
+        self.exps.push(exp);
+        self.positions.push(position);
+    }
+
     fn add(&mut self, alphanums: Alphanums<'src>, original_slice: Placed<&'src str>) -> Result<(), Error<'src>> {
         match alphanums {
             Alphanums::Bytes(bytes) => {
-                self.exps.extend(bytes.iter().copied().map(Exp::Char));
+                // each byte came from two hex characters, skipping over any
+                // underscores that separate them
+                let digit_indices = original_slice.contents.char_indices()
+                                                  .filter(|(_, c)| *c != '_')
+                                                  .map(|(index, _)| index)
+                                                  .collect::<Vec<_>>();
+
+                for (index, byte) in bytes.into_iter().enumerate() {
+                    let from = digit_indices[index * 2];
+                    let to = digit_indices[index * 2 + 1] + 1;
+                    self.push(Exp::Char(byte), original_slice.substring_ascii(from, to));
+                }
+                Ok(())
+            }
+            Alphanums::ConstantName(_) => {
+                self.push(Exp::Constant { name: original_slice }, original_slice);
                 Ok(())
             }
-            Alphanums::ConstantName(name) => {
-                self.exps.push(Exp::Constant { name });
+            Alphanums::BitField(bits) => {
+                self.push(Exp::Bits(bits), original_slice);
                 Ok(())
             }
             Alphanums::FunctionName(_) => {
@@ -213,23 +315,39 @@ enum Alphanums<'src> {
 
     /// The characters form some hex bytes, such as `09F7`.
     Bytes(Vec<u8>),
+
+    /// The characters form a bit field with a width and a value, such as
+    /// `4:5`, which has already been turned into bits.
+    BitField(Vec<bool>),
 }
 
 /// Parses a string of alphanumeric characters into some `Alphanums`,
 /// returning an error if the string does not match any of the known patterns.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate)]
-fn parse_alphanums(span: Placed<&'_ str>) -> Result<Alphanums<'_>, Error<'_>> {
+fn parse_alphanums(span: Placed<&'_ str>, options: ParseOptions) -> Result<Alphanums<'_>, Error<'_>> {
     let input = span.contents;
 
-    if is_constant_name(input) {
+    if input.contains(':') {
+        parse_bit_field(span).map(Alphanums::BitField)
+    }
+    else if is_constant_name(input) {
         Ok(Alphanums::ConstantName(input))
     }
-    else if let Some(name) = parse_function_name(span)? {
+    else if matches!(input.split_once('.'), Some((namespace, _)) if is_namespace(namespace)) {
+        Err(Error::InvalidNamespacedConstant(span))
+    }
+    else if let Some(name) = parse_function_name(span, options)? {
         Ok(Alphanums::FunctionName(name))
     }
+    else if input.bytes().all(|b| b == b'_') {
+        let placed = span.substring_ascii(0, 1);
+        Err(Error::StrayCharacter(placed))
+    }
     else {
+        // underscores can be used to separate the hex characters, such as
+        // in `DE_AD_BE_EF`, so they get skipped over
         let mut bytes = Vec::new();
-        let mut chars = input.char_indices();
+        let mut chars = input.char_indices().filter(|(_, c)| *c != '_');
 
         #[allow(clippy::cast_possible_truncation)]
         while let Some((index, first_char)) = chars.next() {
@@ -264,28 +382,77 @@ fn parse_alphanums(span: Placed<&'_ str>) -> Result<Alphanums<'_>, Error<'_>> {
     }
 }
 
+/// Parses a bit field, such as `4:5`, into a vector of bits. The width comes
+/// before the colon, and the value after it, which can be in decimal or in
+/// hex with a `0x` prefix. The value has to fit in the width.
+fn parse_bit_field(span: Placed<&'_ str>) -> Result<Vec<bool>, Error<'_>> {
+    let invalid = || Error::InvalidBitField(span);
+
+    let (width, value) = span.contents.split_once(':').ok_or_else(invalid)?;
+
+    let width: u32 = match width.parse() {
+        Ok(w) if (1 ..= 64).contains(&w)  => w,
+        _                                 => return Err(invalid()),
+    };
+
+    let value = match value.strip_prefix("0x") {
+        Some(hex)  => u64::from_str_radix(hex, 16),
+        None       => value.parse(),
+    };
+
+    let value = value.map_err(|_| invalid())?;
+    if width < 64 && value >> width != 0 {
+        return Err(invalid());
+    }
+
+    Ok((0 .. width).rev().map(|i| value >> i & 1 == 1).collect())
+}
+
 /// Determines whether this set of alphanums is a valid constant name: it must
 /// start with at least one uppercase letter, and then contain uppercase
-/// letters or digits or underscores.
+/// letters or digits or underscores. Names made up of only hex characters and
+/// underscores, such as `DE_AD`, are read as hex bytes instead.
+///
+/// A name can also be written with a lowercase namespace in front of it and
+/// a dot in between, such as `dns.A`, which gets looked up as `DNS_A`.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate(mutators = not(lit_int, binop_num)))]
 fn is_constant_name(input: &str) -> bool {
     // by this point, non-ASCII characters should already be handled
+    if let Some((namespace, name)) = input.split_once('.') {
+        return is_namespace(namespace) &&
+            ! name.is_empty() &&
+            name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    }
+
     input.len() >= 3 &&
         input.contains('_') &&
         input[0..1].chars().all(|c| c.is_ascii_uppercase()) &&
-        input[1..].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        input[1..].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') &&
+        ! input.chars().all(|c| c.is_ascii_hexdigit() || c == '_')
+}
+
+/// Whether the given string could be the namespace part of a namespaced
+/// constant, such as the `dns` in `dns.A`.
+fn is_namespace(input: &str) -> bool {
+    input.starts_with(|c: char| c.is_ascii_lowercase()) &&
+        input.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
 }
 
 /// Parses a string of characters into a function name, returning an error if
-/// the string does not match any of the known function names.
+/// the string does not match any of the known function names. Repeat
+/// functions can start with either `x` or `X`, and a repeat amount of zero
+/// is only allowed if the options say so.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate)]
-fn parse_function_name(span: Placed<&'_ str>) -> Result<Option<FunctionName>, Error<'_>> {
+fn parse_function_name(span: Placed<&'_ str>, options: ParseOptions) -> Result<Option<FunctionName>, Error<'_>> {
     let input = span.contents;
     if input.is_empty() {
         unreachable!("Empty function name")
     }
-    else if input.len() >= 2 && input.as_bytes()[0] == b'x' && input[1..].bytes().all(|b| b.is_ascii_digit()) {
+    else if input.len() >= 2 && matches!(input.as_bytes()[0], b'x' | b'X') && input[1..].bytes().all(|b| b.is_ascii_digit()) {
         match input[1..].parse() {
+            Ok(0) if options.allow_zero_repeats => {
+                Ok(Some(FunctionName::Repeat(0)))
+            }
             Ok(0) => {
                 warn!("Repeat amount of zero");
                 Err(Error::InvalidRepeatAmount(span))
@@ -300,29 +467,73 @@ fn parse_function_name(span: Placed<&'_ str>) -> Result<Option<FunctionName>, Er
         }
     }
     else {
+        let input = FUNCTION_NAME_ALIASES.iter()
+                        .find(|(alias, _)| *alias == input)
+                        .map_or(input, |(_, canonical)| canonical);
+
         match input {
-            "be16" => Ok(Some(FunctionName::MultiByte(MultiByteType::Be16))),
-            "be32" => Ok(Some(FunctionName::MultiByte(MultiByteType::Be32))),
-            "be64" => Ok(Some(FunctionName::MultiByte(MultiByteType::Be64))),
-            "le16" => Ok(Some(FunctionName::MultiByte(MultiByteType::Le16))),
-            "le32" => Ok(Some(FunctionName::MultiByte(MultiByteType::Le32))),
-            "le64" => Ok(Some(FunctionName::MultiByte(MultiByteType::Le64))),
-            "and"  => Ok(Some(FunctionName::Bitwise(BitwiseFold::And))),
-            "or"   => Ok(Some(FunctionName::Bitwise(BitwiseFold::Or))),
-            "xor"  => Ok(Some(FunctionName::Bitwise(BitwiseFold::Xor))),
-            "not"  => Ok(Some(FunctionName::BitwiseNot)),
-            _      => Ok(None),
+            "be16"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Be16))),
+            "be32"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Be32))),
+            "be64"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Be64))),
+            "le16"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Le16))),
+            "le32"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Le32))),
+            "le64"     => Ok(Some(FunctionName::MultiByte(MultiByteType::Le64))),
+            "q8_8be"   => Ok(Some(FunctionName::FixedPoint(FixedPointType::Q8_8Be))),
+            "q8_8le"   => Ok(Some(FunctionName::FixedPoint(FixedPointType::Q8_8Le))),
+            "q16_16be" => Ok(Some(FunctionName::FixedPoint(FixedPointType::Q16_16Be))),
+            "q16_16le" => Ok(Some(FunctionName::FixedPoint(FixedPointType::Q16_16Le))),
+            "and"      => Ok(Some(FunctionName::Bitwise(BitwiseFold::And))),
+            "or"       => Ok(Some(FunctionName::Bitwise(BitwiseFold::Or))),
+            "xor"      => Ok(Some(FunctionName::Bitwise(BitwiseFold::Xor))),
+            "not"      => Ok(Some(FunctionName::BitwiseNot)),
+            "nibbleswap" => Ok(Some(FunctionName::NibbleSwap)),
+            "neg"      => Ok(Some(FunctionName::Neg)),
+            "popcount" => Ok(Some(FunctionName::PopCount)),
+            "clz"      => Ok(Some(FunctionName::LeadingZeros)),
+            "ctz"      => Ok(Some(FunctionName::TrailingZeros)),
+            "fill"     => Ok(Some(FunctionName::Fill)),
+            "bits"     => Ok(Some(FunctionName::Bits)),
+            "zero"     => Ok(Some(FunctionName::Zero)),
+            "ones"     => Ok(Some(FunctionName::Ones)),
+            "gray"     => Ok(Some(FunctionName::Gray)),
+            "parity"   => Ok(Some(FunctionName::Parity)),
+            "varint"   => Ok(Some(FunctionName::Varint)),
+            "svarint"  => Ok(Some(FunctionName::SignedVarint)),
+            "bcd"      => Ok(Some(FunctionName::Bcd)),
+            "repeat"   => Ok(Some(FunctionName::RepeatDynamic { allow_zero: options.allow_zero_repeats })),
+            "joined"   => Ok(Some(FunctionName::Joined { allow_zero: options.allow_zero_repeats })),
+            _          => Ok(None),
         }
     }
 }
 
+/// Alternative spellings of function names that users coming from other
+/// tools might expect, paired with the canonical names they resolve to.
+const FUNCTION_NAME_ALIASES: &[(&str, &str)] = &[
+    ("u16be", "be16"),
+    ("u32be", "be32"),
+    ("u64be", "be64"),
+    ("u16le", "le16"),
+    ("u32le", "le32"),
+    ("u64le", "le64"),
+    ("wordbe", "be16"),
+    ("wordle", "le16"),
+    ("dwordbe", "be32"),
+    ("dwordle", "le32"),
+    ("qwordbe", "be64"),
+    ("qwordle", "le64"),
+    ("q8_8", "q8_8be"),
+    ("q16_16", "q16_16be"),
+    ("uvarint", "varint"),
+];
+
 /// Parses the contents of a form into an expression, returning an error if
 /// the string does not match any of the known form patterns. The string
 /// should not include the surrounding `[` and `]` characters.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate)]
 fn parse_form(span: Placed<&'_ str>) -> Result<Exp<'_>, Error<'_>> {
-    use std::net::{Ipv4Addr, Ipv6Addr};
-    use std::str::FromStr;
+    use core::net::{Ipv4Addr, Ipv6Addr};
+    use core::str::FromStr;
 
     let input = span.contents;
 
@@ -344,27 +555,124 @@ fn parse_form(span: Placed<&'_ str>) -> Result<Exp<'_>, Error<'_>> {
     else if let Some(float) = parse_float_form(input) {
         Ok(Exp::Float(float))
     }
-    else if let Ok(time) = humantime::parse_rfc3339_weak(input) {
-        let unix_time = time.duration_since(std::time::SystemTime::UNIX_EPOCH).expect("epoch fail");
-        Ok(Exp::Timestamp(unix_time.as_secs() as u32))  // TODO: 64-bit timestamps
+    else if let Some(result) = parse_typed_form(span) {
+        result
+    }
+    else if let Some(result) = parse_percent(span) {
+        result
+    }
+    else if let Some(timestamp) = parse_timestamp_form(input) {
+        Ok(timestamp)
     }
     else {
         Err(Error::InvalidForm(span))
     }
 }
 
+/// Examines the contents of a form to see if it looks like an ISO 8601
+/// timestamp; if it does, parses it into an expression, and if not, returns
+/// `None`. Timestamps are in UTC, unless they end with a numeric offset such
+/// as `+02:00`, which gets taken into account. They count seconds since the
+/// Unix epoch, unless they start with a `ms:` or `ns:` prefix. A fraction of
+/// a second, such as `.250`, is only accepted if the unit can represent it.
+#[cfg(feature = "std")]
+fn parse_timestamp_form(input: &str) -> Option<Exp<'static>> {
+    use std::time::{Duration, SystemTime};
+
+    let (input, unit) = if let Some(rest) = input.strip_prefix("ms:") {
+        (rest, TimeUnit::Millis)
+    }
+    else if let Some(rest) = input.strip_prefix("ns:") {
+        (rest, TimeUnit::Nanos)
+    }
+    else {
+        (input, TimeUnit::Seconds)
+    };
+
+    let (input, offset_seconds) = match split_timezone_offset(input) {
+        Some((time, offset))  => (time, offset),
+        None                  => (input, 0),
+    };
+
+    let time = humantime::parse_rfc3339_weak(input).ok()?;
+    let local_time = time.duration_since(SystemTime::UNIX_EPOCH).expect("epoch fail");
+
+    let offset = Duration::from_secs(offset_seconds.unsigned_abs());
+    let unix_time = if offset_seconds < 0 { local_time.checked_add(offset)? }
+                                     else { local_time.checked_sub(offset)? };
+
+    // fractions of a second are kept, unless the unit is too coarse to
+    // hold them, in which case the form is rejected rather than rounded
+    let nanos_per_unit = match unit {
+        TimeUnit::Seconds  => 1_000_000_000,
+        TimeUnit::Millis   => 1_000_000,
+        TimeUnit::Nanos    => 1,
+    };
+
+    if unix_time.subsec_nanos() % nanos_per_unit != 0 {
+        return None;
+    }
+
+    let time = match unit {
+        TimeUnit::Seconds  => unix_time.as_secs(),
+        TimeUnit::Millis   => u64::try_from(unix_time.as_millis()).ok()?,
+        TimeUnit::Nanos    => u64::try_from(unix_time.as_nanos()).ok()?,
+    };
+
+    Some(Exp::Timestamp { time, unit })
+}
+
+/// Splits a numeric timezone offset, such as `+02:00` or `-05:30`, off the
+/// end of a timestamp, returning the rest of the timestamp and the offset in
+/// seconds, or `None` if there’s no offset.
+#[cfg(feature = "std")]
+fn split_timezone_offset(input: &str) -> Option<(&str, i64)> {
+    let split_index = input.len().checked_sub(6)?;
+    let offset = input.get(split_index ..)?.as_bytes();
+
+    let sign = match offset[0] {
+        b'+'  => 1,
+        b'-'  => -1,
+        _     => return None,
+    };
+
+    if offset[3] != b':' || ! [ 1, 2, 4, 5 ].iter().all(|&i| offset[i].is_ascii_digit()) {
+        return None;
+    }
+
+    let hours = i64::from(offset[1] - b'0') * 10 + i64::from(offset[2] - b'0');
+    let minutes = i64::from(offset[4] - b'0') * 10 + i64::from(offset[5] - b'0');
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    Some((&input[.. split_index], sign * (hours * 60 + minutes) * 60))
+}
+
+/// Without the standard library, there’s no clock to measure timestamps
+/// against, so they are never recognised.
+#[cfg(not(feature = "std"))]
+fn parse_timestamp_form(_input: &str) -> Option<Exp<'static>> {
+    None
+}
+
 /// Examines the contents of a form to see if it looks like a series of bits;
 /// if it does, parses it into a vector of bits, and if not, returns `None`.
+/// The bits can be split up with underscores or spaces, such as
+/// `b1100_0011` or `b 1100 0011`, which get skipped over.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate(mutators = not(lit_int, binop_num)))]
 fn parse_bit_form(input: &str) -> Option<Vec<bool>> {
-    if input.starts_with('b') && input[1..].bytes().all(|c| c == b'1' || c == b'0' || c == b'_') {
-        let mut bit_vec = Vec::with_capacity(input.len() - 1);  // skip mutation testing again
+    let bits = input.strip_prefix('b')?;
 
-        for byte in input[1..].bytes() {
+    if bits.bytes().all(|c| c == b'1' || c == b'0' || c == b'_' || c.is_ascii_whitespace()) {
+        let mut bit_vec = Vec::with_capacity(bits.len());
+
+        for byte in bits.bytes() {
             match byte {
                 b'0'  => bit_vec.push(false),
                 b'1'  => bit_vec.push(true),
                 b'_'  => {/* skip */},
+                c if c.is_ascii_whitespace() => {/* skip */},
                 _     => unreachable!(),
             }
         }
@@ -397,35 +705,294 @@ fn parse_float_form(input: &str) -> Option<&str> {
     }
 }
 
+/// Parses the contents of a form in the arguments to the given function, if
+/// there is one, as some functions accept forms that nothing else does.
+fn parse_argument_form(span: Placed<&'_ str>, function: Option<FunctionName>) -> Result<Exp<'_>, Error<'_>> {
+    match function {
+        Some(FunctionName::FixedPoint(_))  => parse_fixed_point_form(span),
+        _                                  => parse_form(span),
+    }
+}
+
+/// Parses the contents of a form given to a fixed-point function. As well as
+/// the usual forms, these can be plain decimals with a point or a minus sign
+/// in them, such as `[1.5]` or `[-2]`, which get read in the same way as
+/// floats.
+fn parse_fixed_point_form(span: Placed<&'_ str>) -> Result<Exp<'_>, Error<'_>> {
+    let all_digits = |s: &str| ! s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    let (negative, number) = match span.contents.strip_prefix('-') {
+        Some(number)  => (true, number),
+        None          => (false, span.contents),
+    };
+
+    let is_decimal = match number.split_once('.') {
+        Some((whole, fraction))  => all_digits(whole) && all_digits(fraction),
+        None                     => negative && all_digits(number),
+    };
+
+    if is_decimal {
+        Ok(Exp::Float(span.contents))
+    }
+    else {
+        parse_form(span)
+    }
+}
+
+/// Examines the contents of a form to see if it looks like a number with an
+/// explicit type, such as `[u16:256]` or `[i32le:-1]`, returning `None` if it
+/// does not. The type is `u` or `i` for unsigned or signed, then a width of
+/// 8, 16, 32, or 64 bits, then optionally `be` or `le` (defaulting to
+/// big-endian). The number is decimal, or hex if it starts with `0x`, and
+/// may start with `-` if the type is signed. As the type is fully known,
+/// the number gets turned into bytes straight away.
+#[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate(mutators = not(lit_int, binop_num)))]
+fn parse_typed_form(span: Placed<&'_ str>) -> Option<Result<Exp<'_>, Error<'_>>> {
+    let input = span.contents;
+    let colon_index = input.find(':')?;
+    let (type_name, number) = (&input[.. colon_index], &input[colon_index + 1 ..]);
+
+    let (signed, rest) = if let Some(rest) = type_name.strip_prefix('u') {
+        (false, rest)
+    }
+    else if let Some(rest) = type_name.strip_prefix('i') {
+        (true, rest)
+    }
+    else {
+        return None;
+    };
+
+    let (bits, big_endian) = if let Some(bits) = rest.strip_suffix("be") {
+        (bits, true)
+    }
+    else if let Some(bits) = rest.strip_suffix("le") {
+        (bits, false)
+    }
+    else {
+        (rest, true)
+    };
+
+    let bits: u32 = match bits {
+        "8" | "16" | "32" | "64"  => bits.parse().unwrap(),
+        _                         => return None,
+    };
+
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) if signed  => (true, digits),
+        _                       => (false, number),
+    };
+
+    let (digits, radix) = match digits.strip_prefix("0x") {
+        Some(hex)  => (hex, 16),
+        None       => (digits, 10),
+    };
+
+    // there must be at least one digit, so that ‘[u8:]’ is not mistaken
+    // for a number too large to fit
+    if digits.is_empty() || ! digits.chars().all(|c| c.is_digit(radix)) {
+        return Some(Err(Error::InvalidForm(span)));
+    }
+
+    let magnitude = u64::from_str_radix(digits, radix);
+
+    // the largest magnitude that fits, which is one more for negative
+    // numbers than positive ones when the type is signed
+    let max = match (signed, negative) {
+        (false, _)     => u64::MAX >> (64 - bits),
+        (true, false)  => u64::MAX >> (65 - bits),
+        (true, true)   => 1 << (bits - 1),
+    };
+
+    let magnitude = match magnitude {
+        Ok(m) if m <= max  => m,
+        _                  => return Some(Err(Error::TypedFormOutOfRange(span))),
+    };
+
+    let num = if negative { magnitude.wrapping_neg() } else { magnitude };
+    let count = (bits / 8) as usize;
+    let bytes = if big_endian {
+        num.to_be_bytes()[8 - count ..].to_vec()
+    }
+    else {
+        num.to_le_bytes()[.. count].to_vec()
+    };
+
+    Some(Ok(Exp::Typed { bytes }))
+}
+
+/// Examines the contents of a form to see if it looks like a percentage,
+/// such as `[50%]`, returning `None` if it does not. A percentage scales the
+/// byte range, so `[0%]` is `00` and `[100%]` is `FF`, and anything between
+/// gets rounded to the nearest byte, with halves rounded up, so `[50%]` is
+/// `80`. As the result is always exactly one byte, it cannot be widened.
+fn parse_percent(span: Placed<&'_ str>) -> Option<Result<Exp<'_>, Error<'_>>> {
+    let digits = span.contents.strip_suffix('%')?;
+
+    if digits.is_empty() || ! digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(Err(Error::InvalidForm(span)));
+    }
+
+    let percent = match digits.parse::<u32>() {
+        Ok(p) if p <= 100  => p,
+        _                  => return Some(Err(Error::PercentOutOfRange(span))),
+    };
+
+    let byte = u8::try_from((percent * 255 + 50) / 100).unwrap();
+    Some(Ok(Exp::Typed { bytes: vec![ byte ] }))
+}
+
+/// Returns the contents of a quoted string token, placed where they start.
+/// The token itself is placed at its opening quote, which would put the
+/// position of anything within the string one column too far to the left.
+fn quoted_contents(span: Placed<&'_ str>) -> Placed<&'_ str> {
+    Placed { column_number: span.column_number + 1, ..span }
+}
+
+/// Returns the contents of a prefixed string token, placed where they start.
+/// The token itself is placed at the start of its prefix, so the position
+/// needs to skip past both the prefix and the opening quote.
+fn encoded_contents(encoding: StringEncoding, span: Placed<&'_ str>) -> Placed<&'_ str> {
+    Placed { column_number: span.column_number + encoding.prefix().len() + 1, ..span }
+}
+
+/// Decodes the contents of a prefixed string into bytes, using whichever
+/// encoding its prefix gave.
+fn decode_string(encoding: StringEncoding, span: Placed<&'_ str>) -> Result<Vec<u8>, Error<'_>> {
+    match encoding {
+        StringEncoding::Base32  => decode_base32(span),
+        StringEncoding::Hex     => decode_hex(span),
+    }
+}
+
+/// Decodes a base32 string using the RFC 4648 alphabet, accepting letters
+/// in either case. Padding at the end is optional, but if it is there, it
+/// has to make the string up to a multiple of eight characters.
+#[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate)]
+fn decode_base32(span: Placed<&'_ str>) -> Result<Vec<u8>, Error<'_>> {
+    let input = span.contents;
+    let data = input.trim_end_matches('=');
+
+    let mut result = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer = 0_u32;
+    let mut bits = 0;
+
+    for (count, (i, c)) in data.char_indices().enumerate() {
+        let value = match c.to_ascii_uppercase() {
+            'A' ..= 'Z'  => u32::from(c.to_ascii_uppercase()) - u32::from('A'),
+            '2' ..= '7'  => u32::from(c) - u32::from('2') + 26,
+            _            => return Err(Error::InvalidBase32Character(span.substring_mb(i, count, i + c.len_utf8()))),
+        };
+
+        // each character holds five bits, and a byte gets written out
+        // whenever there are eight of them waiting
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            result.push(u8::try_from((buffer >> bits) & 0xFF).unwrap());
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // these lengths would leave a character with no byte to end up in
+    let padded = data.len() != input.len();
+    if matches!(data.len() % 8, 1 | 3 | 6) || (padded && input.len() % 8 != 0) {
+        return Err(Error::InvalidBase32Length(span));
+    }
+
+    Ok(result)
+}
+
+/// Decodes a string of hex characters, which can have whitespace anywhere
+/// between them, so that hex dumps can be pasted in as they are. The bytes
+/// do not have to line up with the whitespace, but there has to be an even
+/// number of hex characters overall.
+#[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate)]
+fn decode_hex(span: Placed<&'_ str>) -> Result<Vec<u8>, Error<'_>> {
+    let mut result = Vec::with_capacity(span.contents.len() / 2);
+    let mut high_nibble = None;
+
+    for (count, (i, c)) in span.contents.char_indices().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let nibble = match c.to_digit(16) {
+            Some(n)  => u8::try_from(n).unwrap(),
+            None     => return Err(Error::InvalidHexStringCharacter(span.substring_mb(i, count, i + c.len_utf8()))),
+        };
+
+        match high_nibble.take() {
+            Some(high)  => result.push((high << 4) | nibble),
+            None        => high_nibble = Some(nibble),
+        }
+    }
+
+    if high_nibble.is_some() {
+        return Err(Error::OddHexStringDigits(span));
+    }
+
+    Ok(result)
+}
+
 /// Parse the contents of a quoted string into its canonical form by handling
-/// escaped backslashes and quotes. This returns a copy of the original string
-/// slice if it does not need to be modified; otherwise, it allocates and
-/// returns a new string.
+/// escaped backslashes and quotes, as well as the C escapes for control
+/// characters and octal escapes such as `\101`. This returns the original
+/// string slice’s bytes if it does not need to be modified; otherwise, it
+/// allocates and returns a new vector of bytes.
 #[cfg_attr(all(test, feature = "with_mutagen"), ::mutagen::mutate(mutators = not(lit_int, binop_num)))]
-fn parse_backslashes<'src>(span: Placed<&'src str>) -> Result<Cow<'src, str>, Error<'src>> {
+fn parse_backslashes<'src>(span: Placed<&'src str>) -> Result<Cow<'src, [u8]>, Error<'src>> {
     let input = span.contents;
 
     if ! input.contains('\\') {
-        return Ok(input.into());
+        return Ok(input.as_bytes().into());
     }
 
     // The resulting string must be, at a minimum, half the length of the
     // original (as "\n" will turn into one byte).
-    let mut result = String::with_capacity(input.len() / 2);  // this doesn’t need mutation testing
+    let mut result = Vec::with_capacity(input.len() / 2);  // this doesn’t need mutation testing
 
-    let mut chars = input.char_indices().enumerate();
+    let mut chars = input.char_indices().enumerate().peekable();
     while let Some((count, (i, c))) = chars.next() {
         if c != '\\' {
-            result.push(c);
+            let mut buf = [0; 4];
+            result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
             continue;
         }
 
         match chars.next().map(|t| t.1.1) {
-            Some('n')  => result.push('\n'),
-            Some('r')  => result.push('\r'),
-            Some('t')  => result.push('\t'),
-            Some('"')  => result.push('"'),
-            Some('\\') => result.push('\\'),
+            Some('n')  => result.push(b'\n'),
+            Some('r')  => result.push(b'\r'),
+            Some('t')  => result.push(b'\t'),
+            Some('a')  => result.push(0x07),
+            Some('b')  => result.push(0x08),
+            Some('f')  => result.push(0x0C),
+            Some('v')  => result.push(0x0B),
+            Some('e')  => result.push(0x1B),
+            Some('"')  => result.push(b'"'),
+            Some('\\') => result.push(b'\\'),
+            Some(nc) if nc.is_digit(8) => {
+                // up to three octal digits, with the first already read
+                let mut value = nc.to_digit(8).unwrap();
+                let mut end = i + 2;
+
+                while end - i < 4 {
+                    match chars.peek() {
+                        Some(&(_, (_, oc))) if oc.is_digit(8) => {
+                            value = value * 8 + oc.to_digit(8).unwrap();
+                            end += 1;
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+
+                match u8::try_from(value) {
+                    Ok(byte) => result.push(byte),
+                    Err(_)   => return Err(Error::EscapeOutOfRange(span.substring_mb(i, count, end))),
+                }
+            }
             Some(nc)   => return Err(Error::InvalidEscape(span.substring_mb(i, count, i + 1 + nc.len_utf8()))),
             None       => unreachable!("String ends with backslash"),
         }
@@ -463,14 +1030,61 @@ pub enum Error<'src> {
     /// such as `[plum pudding]`.
     InvalidForm(Placed<&'src str>),
 
+    /// A form contained a number with an explicit type, but the number does
+    /// not fit in that type, such as `[u8:256]` or `[i8:-129]`.
+    TypedFormOutOfRange(Placed<&'src str>),
+
+    /// A form contained a percentage that was over 100, such as `[150%]`.
+    PercentOutOfRange(Placed<&'src str>),
+
     /// The parser saw an opening `(` token and started reading
     /// sub-expressions for the function’s arguments, but before reading a
     /// closing `)` token, the stream of tokens ran out.
     UnclosedFunction(Placed<&'src str>),
 
+    /// The parser saw an opening `(` token with no function name before it,
+    /// and started reading the sub-expressions in the group, but before
+    /// reading a closing `)` token, the stream of tokens ran out.
+    UnclosedGroup(Placed<&'src str>),
+
+    /// A bit field was not a width from 1 to 64, a colon, and a value that
+    /// fits in that width, such as `4:16` or `0:0`.
+    InvalidBitField(Placed<&'src str>),
+
+    /// A bit field, such as `4:5`, was used somewhere other than in the
+    /// arguments to the `bits` function, such as `be16(12:0xABC)`.
+    MisplacedBitField(Placed<&'src str>),
+
+    /// A block of alphanumeric characters started with a namespace and a
+    /// dot, but what came after it was not a constant name, such as `dns.`
+    /// or `dns.a`.
+    InvalidNamespacedConstant(Placed<&'src str>),
+
     /// A quoted string contained an escape character that was not one of the
-    /// five valid escape characters (‘n’, ‘r’, ‘t’, ‘"’, and ‘\’.).
+    /// valid escape characters (‘n’, ‘r’, ‘t’, ‘a’, ‘b’, ‘f’, ‘v’, ‘e’, ‘"’,
+    /// and ‘\’.) or an octal digit.
     InvalidEscape(Placed<&'src str>),
+
+    /// A quoted string contained an octal escape for a value too big to fit
+    /// in a byte, such as `\400`.
+    EscapeOutOfRange(Placed<&'src str>),
+
+    /// A base32 string contained a character outside of the base32 alphabet,
+    /// such as `1` or `!`, or padding somewhere other than the end.
+    InvalidBase32Character(Placed<&'src str>),
+
+    /// A base32 string had a number of characters that cannot make up a
+    /// whole number of bytes, or padding that did not fill out its last
+    /// group of eight.
+    InvalidBase32Length(Placed<&'src str>),
+
+    /// A hex string contained a character that was neither a hex character
+    /// nor whitespace, such as `G` or `,`.
+    InvalidHexStringCharacter(Placed<&'src str>),
+
+    /// A hex string contained an odd number of hex characters, leaving the
+    /// last one without a pair.
+    OddHexStringDigits(Placed<&'src str>),
 }
 
 impl<'src> fmt::Display for Error<'src> {
@@ -482,8 +1096,19 @@ impl<'src> fmt::Display for Error<'src> {
             Self::InvalidFunctionName(name)   => write!(f, "Invalid function name {:?}", name.contents),
             Self::InvalidRepeatAmount(ra)     => write!(f, "Invalid repeat amount {:?}", ra.contents),
             Self::InvalidForm(form)           => write!(f, "Could not interpret form {:?}", form.contents),
+            Self::TypedFormOutOfRange(form)   => write!(f, "Number in form {:?} does not fit its type", form.contents),
+            Self::PercentOutOfRange(form)     => write!(f, "Percentage in form {:?} is over 100%", form.contents),
             Self::UnclosedFunction(fname)     => write!(f, "Unclosed function {:?}", fname.contents),
+            Self::UnclosedGroup(_)            => write!(f, "Unclosed parenthesis"),
+            Self::InvalidBitField(field)      => write!(f, "Invalid bit field {:?}", field.contents),
+            Self::MisplacedBitField(field)    => write!(f, "Bit field {:?} outside of bits()", field.contents),
+            Self::InvalidNamespacedConstant(c)  => write!(f, "Incomplete or invalid namespaced constant {:?}", c.contents),
             Self::InvalidEscape(c)            => write!(f, "String contains invalid escape character \"{}\"", c.contents),
+            Self::EscapeOutOfRange(c)         => write!(f, "String contains escape \"{}\" that is too big for a byte", c.contents),
+            Self::InvalidBase32Character(c)   => write!(f, "Base32 string contains invalid character \"{}\"", c.contents),
+            Self::InvalidBase32Length(s)      => write!(f, "Base32 string {:?} has an invalid length", s.contents),
+            Self::InvalidHexStringCharacter(c)  => write!(f, "Hex string contains invalid character \"{}\"", c.contents),
+            Self::OddHexStringDigits(s)       => write!(f, "Hex string {:?} has an odd number of hex characters", s.contents),
         }
     }
 }
@@ -500,8 +1125,57 @@ impl<'src> Error<'src> {
             Self::InvalidFunctionName(name)   => name,
             Self::InvalidRepeatAmount(ra)     => ra,
             Self::InvalidForm(form)           => form,
+            Self::TypedFormOutOfRange(form)   => form,
+            Self::PercentOutOfRange(form)     => form,
             Self::UnclosedFunction(open)      => open,
+            Self::UnclosedGroup(open)         => open,
+            Self::InvalidBitField(field)      => field,
+            Self::MisplacedBitField(field)    => field,
+            Self::InvalidNamespacedConstant(c)  => c,
             Self::InvalidEscape(c)            => c,
+            Self::EscapeOutOfRange(c)         => c,
+            Self::InvalidBase32Character(c)   => c,
+            Self::InvalidBase32Length(s)      => s,
+            Self::InvalidHexStringCharacter(c)  => c,
+            Self::OddHexStringDigits(s)       => s,
+        }
+    }
+
+    /// Returns the name of this kind of error, for tokens that could not be
+    /// turned into expressions.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SingleHex(_)            => "single_hex",
+            Self::StrayCharacter(_)       => "stray_character",
+            Self::StrayFunctionName(_)    => "stray_function_name",
+            Self::InvalidFunctionName(_)  => "invalid_function_name",
+            Self::InvalidRepeatAmount(_)  => "invalid_repeat_amount",
+            Self::InvalidForm(_)          => "invalid_form",
+            Self::TypedFormOutOfRange(_)  => "typed_form_out_of_range",
+            Self::PercentOutOfRange(_)    => "percent_out_of_range",
+            Self::UnclosedFunction(_)     => "unclosed_function",
+            Self::UnclosedGroup(_)        => "unclosed_group",
+            Self::InvalidBitField(_)      => "invalid_bit_field",
+            Self::MisplacedBitField(_)    => "misplaced_bit_field",
+            Self::InvalidNamespacedConstant(_)  => "invalid_namespaced_constant",
+            Self::InvalidEscape(_)        => "invalid_escape",
+            Self::EscapeOutOfRange(_)     => "escape_out_of_range",
+            Self::InvalidBase32Character(_)  => "invalid_base32_character",
+            Self::InvalidBase32Length(_)  => "invalid_base32_length",
+            Self::InvalidHexStringCharacter(_)  => "invalid_hex_string_character",
+            Self::OddHexStringDigits(_)   => "odd_hex_string_digits",
+        }
+    }
+
+    /// Returns the number of characters in the source line that the error
+    /// covers, starting from its position. Forms are placed at their opening
+    /// bracket, but hold only what’s inside, so their brackets are added on.
+    pub fn span_length(&self) -> usize {
+        match self {
+            Self::InvalidForm(form)          => form.contents.chars().count() + 2,
+            Self::TypedFormOutOfRange(form)  => form.contents.chars().count() + 2,
+            Self::PercentOutOfRange(form)    => form.contents.chars().count() + 2,
+            otherwise                        => otherwise.source_pos().contents.chars().count(),
         }
     }
 }
@@ -516,73 +1190,202 @@ mod test_parse_alphanums {
     #[test]
     #[should_panic]
     fn utf8_byte() {
-        let _ = parse_alphanums("é".at(1, 0));
+        let _ = parse_alphanums("é".at(1, 0), ParseOptions::default());
     }
 
     #[test]
     fn one_byte() {
-        assert_eq!(parse_alphanums("EF".at(1, 0)),
+        assert_eq!(parse_alphanums("EF".at(1, 0), ParseOptions::default()),
                    Ok(Alphanums::Bytes(vec![ 0xEF ])));
     }
 
     #[test]
     fn two_bytes() {
-        assert_eq!(parse_alphanums("EF12".at(1, 0)),
+        assert_eq!(parse_alphanums("EF12".at(1, 0), ParseOptions::default()),
                    Ok(Alphanums::Bytes(vec![ 0xEF, 0x12 ])));
     }
 
     #[test]
     fn half_a_byte() {
-        assert_eq!(parse_alphanums("E".at(1, 0)),
+        assert_eq!(parse_alphanums("E".at(1, 0), ParseOptions::default()),
                    Err(Error::SingleHex("E".at(1, 0))));
     }
 
     #[test]
     fn not_a_byte() {
-        assert_eq!(parse_alphanums("Ex".at(1, 0)),
+        assert_eq!(parse_alphanums("Ex".at(1, 0), ParseOptions::default()),
                    Err(Error::StrayCharacter("x".at(1, 1))));
     }
 
     #[test]
     fn first_g() {
-        assert_eq!(parse_alphanums("FG".at(1, 0)),
+        assert_eq!(parse_alphanums("FG".at(1, 0), ParseOptions::default()),
                    Err(Error::StrayCharacter("G".at(1, 1))));
     }
 
     #[test]
     fn second_g() {
-        assert_eq!(parse_alphanums("GF".at(1, 0)),
+        assert_eq!(parse_alphanums("GF".at(1, 0), ParseOptions::default()),
                    Err(Error::StrayCharacter("G".at(1, 0))));
     }
 
     #[test]
     fn constant_name() {
-        assert_eq!(parse_alphanums("DNS_AAAA".at(1, 0)),
+        assert_eq!(parse_alphanums("DNS_AAAA".at(1, 0), ParseOptions::default()),
                    Ok(Alphanums::ConstantName("DNS_AAAA")));
     }
 
     #[test]
     fn shortest_possible_constant() {
-        assert_eq!(parse_alphanums("A_B".at(1, 0)),
-                   Ok(Alphanums::ConstantName("A_B")));
+        assert_eq!(parse_alphanums("A_Z".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::ConstantName("A_Z")));
+    }
+
+    #[test]
+    fn separated_bytes() {
+        assert_eq!(parse_alphanums("DE_AD_BE_EF".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::Bytes(vec![ 0xDE, 0xAD, 0xBE, 0xEF ])));
+    }
+
+    #[test]
+    fn hex_constant_lookalike() {
+        assert_eq!(parse_alphanums("A_B".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::Bytes(vec![ 0xAB ])));
+    }
+
+    #[test]
+    fn lowercase_separated_bytes() {
+        assert_eq!(parse_alphanums("de_ad".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::Bytes(vec![ 0xDE, 0xAD ])));
+    }
+
+    #[test]
+    fn separated_half_a_byte() {
+        assert_eq!(parse_alphanums("DE_A".at(1, 0), ParseOptions::default()),
+                   Err(Error::SingleHex("A".at(1, 3))));
+    }
+
+    #[test]
+    fn separated_odd_length() {
+        assert_eq!(parse_alphanums("AB_C".at(1, 0), ParseOptions::default()),
+                   Err(Error::SingleHex("C".at(1, 3))));
+    }
+
+    #[test]
+    fn separated_stray_after_underscore() {
+        assert_eq!(parse_alphanums("ab_cg".at(1, 0), ParseOptions::default()),
+                   Err(Error::StrayCharacter("g".at(1, 4))));
+    }
+
+    #[test]
+    fn bit_field() {
+        assert_eq!(parse_alphanums("4:5".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::BitField(vec![ false, true, false, true ])));
+    }
+
+    #[test]
+    fn bit_field_hex() {
+        assert_eq!(parse_alphanums("6:0x2A".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::BitField(vec![ true, false, true, false, true, false ])));
+    }
+
+    #[test]
+    fn bit_field_64() {
+        assert_eq!(parse_alphanums("64:18446744073709551615".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::BitField(vec![ true; 64 ])));
+    }
+
+    #[test]
+    fn bit_field_too_big() {
+        assert_eq!(parse_alphanums("4:16".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidBitField("4:16".at(1, 0))));
+    }
+
+    #[test]
+    fn bit_field_zero_width() {
+        assert_eq!(parse_alphanums("0:0".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidBitField("0:0".at(1, 0))));
+    }
+
+    #[test]
+    fn bit_field_too_wide() {
+        assert_eq!(parse_alphanums("65:0".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidBitField("65:0".at(1, 0))));
+    }
+
+    #[test]
+    fn bit_field_two_colons() {
+        assert_eq!(parse_alphanums("4:1:1".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidBitField("4:1:1".at(1, 0))));
+    }
+
+    #[test]
+    fn bit_field_no_value() {
+        assert_eq!(parse_alphanums("4:".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidBitField("4:".at(1, 0))));
+    }
+
+    #[test]
+    fn only_underscores() {
+        assert_eq!(parse_alphanums("__".at(1, 0), ParseOptions::default()),
+                   Err(Error::StrayCharacter("_".at(1, 0))));
+    }
+
+    #[test]
+    fn positions_of_separated_bytes() {
+        let tokens = vec![ Token::Alphanum("AB__C_D".at(1, 0)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Char(0xAB), "AB".at(1, 0)),
+                            (Exp::Char(0xCD), "C_D".at(1, 4)) ]));
     }
 
     #[test]
     fn constant_ending_with_numbers() {
-        assert_eq!(parse_alphanums("DNS_EUI48".at(1, 0)),
+        assert_eq!(parse_alphanums("DNS_EUI48".at(1, 0), ParseOptions::default()),
                    Ok(Alphanums::ConstantName("DNS_EUI48")));
     }
 
+    #[test]
+    fn namespaced_constant() {
+        assert_eq!(parse_alphanums("dns.A".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::ConstantName("dns.A")));
+    }
+
+    #[test]
+    fn namespaced_constant_with_underscores() {
+        assert_eq!(parse_alphanums("png.MAGIC_END".at(1, 0), ParseOptions::default()),
+                   Ok(Alphanums::ConstantName("png.MAGIC_END")));
+    }
+
+    #[test]
+    fn namespace_without_name() {
+        assert_eq!(parse_alphanums("dns.".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidNamespacedConstant("dns.".at(1, 0))));
+    }
+
+    #[test]
+    fn namespace_with_lowercase_name() {
+        assert_eq!(parse_alphanums("dns.a".at(1, 0), ParseOptions::default()),
+                   Err(Error::InvalidNamespacedConstant("dns.a".at(1, 0))));
+    }
+
+    #[test]
+    fn dotted_bytes() {
+        assert_eq!(parse_alphanums("AB.CD".at(1, 0), ParseOptions::default()),
+                   Err(Error::StrayCharacter(".".at(1, 2))));
+    }
+
     #[test]
     fn constant_too_short() {
-        assert_eq!(parse_alphanums("_A".at(1, 0)),
-                   Err(Error::StrayCharacter("_".at(1, 0))));
+        assert_eq!(parse_alphanums("_A".at(1, 0), ParseOptions::default()),
+                   Err(Error::SingleHex("A".at(1, 1))));
     }
 
     #[test]
     fn constant_still_too_short() {
-        assert_eq!(parse_alphanums("A_".at(1, 0)),
-                   Err(Error::StrayCharacter("_".at(1, 1))));
+        assert_eq!(parse_alphanums("A_".at(1, 0), ParseOptions::default()),
+                   Err(Error::SingleHex("A".at(1, 0))));
     }
 }
 
@@ -629,6 +1432,30 @@ mod test_parse_form {
                    Ok(Exp::Bits(vec![false, true, true, false, true, true, false])));
     }
 
+    #[test]
+    fn bits_spaces() {
+        assert_eq!(parse_form("b 011 0110".at(1, 0)),
+                   Ok(Exp::Bits(vec![false, true, true, false, true, true, false])));
+    }
+
+    #[test]
+    fn only_spaces_bits() {
+        assert_eq!(parse_form("b  ".at(1, 0)),
+                   Err(Error::InvalidForm("b  ".at(1, 0))));
+    }
+
+    #[test]
+    fn no_spaced_decimals() {
+        assert_eq!(parse_form("12 34".at(1, 0)),
+                   Err(Error::InvalidForm("12 34".at(1, 0))));
+    }
+
+    #[test]
+    fn no_spaced_ipv4() {
+        assert_eq!(parse_form("127.0. 0.1".at(1, 0)),
+                   Err(Error::InvalidForm("127.0. 0.1".at(1, 0))));
+    }
+
     #[test]
     fn no_bits() {
         assert_eq!(parse_form("b".at(1, 0)),
@@ -659,11 +1486,131 @@ mod test_parse_form {
                    Ok(Exp::Float("1.5")));
     }
 
+    #[test]
+    fn float_without_prefix() {
+        assert_eq!(parse_form("1.5".at(1, 0)),
+                   Err(Error::InvalidForm("1.5".at(1, 0))));
+    }
+
+    #[test]
+    fn fixed_point_decimal() {
+        assert_eq!(parse_fixed_point_form("1.5".at(1, 0)),
+                   Ok(Exp::Float("1.5")));
+    }
+
+    #[test]
+    fn fixed_point_other_form() {
+        assert_eq!(parse_fixed_point_form("f-1.5".at(1, 0)),
+                   Ok(Exp::Float("-1.5")));
+    }
+
+    #[test]
+    fn fixed_point_without_fraction() {
+        assert_eq!(parse_fixed_point_form("1.".at(1, 0)),
+                   Err(Error::InvalidForm("1.".at(1, 0))));
+    }
+
+    #[test]
+    fn float_without_fraction() {
+        assert_eq!(parse_form("1.".at(1, 0)),
+                   Err(Error::InvalidForm("1.".at(1, 0))));
+    }
+
     #[test]
     fn float_badly() {
         assert_eq!(parse_form("foo".at(1, 0)),
                    Err(Error::InvalidForm("foo".at(1, 0))));
     }
+
+    #[test]
+    fn typed_unsigned() {
+        assert_eq!(parse_form("u16:256".at(1, 0)),
+                   Ok(Exp::Typed { bytes: vec![ 0x01, 0x00 ] }));
+    }
+
+    #[test]
+    fn typed_signed() {
+        assert_eq!(parse_form("i16:-1".at(1, 0)),
+                   Ok(Exp::Typed { bytes: vec![ 0xFF, 0xFF ] }));
+    }
+
+    #[test]
+    fn typed_little_endian() {
+        assert_eq!(parse_form("u32le:0x1234".at(1, 0)),
+                   Ok(Exp::Typed { bytes: vec![ 0x34, 0x12, 0x00, 0x00 ] }));
+    }
+
+    #[test]
+    fn typed_smallest_signed() {
+        assert_eq!(parse_form("i8:-128".at(1, 0)),
+                   Ok(Exp::Typed { bytes: vec![ 0x80 ] }));
+    }
+
+    #[test]
+    fn typed_too_big() {
+        assert_eq!(parse_form("i8:128".at(1, 0)),
+                   Err(Error::TypedFormOutOfRange("i8:128".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_negative_unsigned() {
+        assert_eq!(parse_form("u8:-1".at(1, 0)),
+                   Err(Error::InvalidForm("u8:-1".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_unknown_width() {
+        assert_eq!(parse_form("u24:1".at(1, 0)),
+                   Err(Error::InvalidForm("u24:1".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_no_digits() {
+        assert_eq!(parse_form("u8:".at(1, 0)),
+                   Err(Error::InvalidForm("u8:".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_no_hex_digits() {
+        assert_eq!(parse_form("u16:0x".at(1, 0)),
+                   Err(Error::InvalidForm("u16:0x".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_only_minus() {
+        assert_eq!(parse_form("i8:-".at(1, 0)),
+                   Err(Error::InvalidForm("i8:-".at(1, 0))));
+    }
+
+    #[test]
+    fn typed_hex_with_plus() {
+        assert_eq!(parse_form("u8:0x+5".at(1, 0)),
+                   Err(Error::InvalidForm("u8:0x+5".at(1, 0))));
+    }
+
+    #[test]
+    fn percent() {
+        assert_eq!(parse_form("50%".at(1, 0)),
+                   Ok(Exp::Typed { bytes: vec![ 0x80 ] }));
+    }
+
+    #[test]
+    fn percent_too_big() {
+        assert_eq!(parse_form("101%".at(1, 0)),
+                   Err(Error::PercentOutOfRange("101%".at(1, 0))));
+    }
+
+    #[test]
+    fn percent_without_number() {
+        assert_eq!(parse_form("%".at(1, 0)),
+                   Err(Error::InvalidForm("%".at(1, 0))));
+    }
+
+    #[test]
+    fn percent_with_fraction() {
+        assert_eq!(parse_form("12.5%".at(1, 0)),
+                   Err(Error::InvalidForm("12.5%".at(1, 0))));
+    }
 }
 
 
@@ -676,72 +1623,140 @@ mod test_parse_function_name {
     #[test]
     #[should_panic]
     fn empty() {
-        let _ = parse_function_name("".at(1, 0));
+        let _ = parse_function_name("".at(1, 0), ParseOptions::default());
     }
 
     #[test]
     fn once() {
-        assert_eq!(parse_function_name("x1".at(1, 0)),
+        assert_eq!(parse_function_name("x1".at(1, 0), ParseOptions::default()),
                    Ok(Some(FunctionName::Repeat(1))));
     }
 
     #[test]
     fn eleven_times() {
-        assert_eq!(parse_function_name("x11".at(1, 0)),
+        assert_eq!(parse_function_name("x11".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::Repeat(11))));
+    }
+
+    #[test]
+    fn eleven_times_uppercase() {
+        assert_eq!(parse_function_name("X11".at(1, 0), ParseOptions::default()),
                    Ok(Some(FunctionName::Repeat(11))));
     }
 
     #[test]
     fn nonce() {
-        assert_eq!(parse_function_name("x0".at(1, 0)),
+        assert_eq!(parse_function_name("x0".at(1, 0), ParseOptions::default()),
                    Err(Error::InvalidRepeatAmount("x0".at(1, 0))));
     }
 
+    #[test]
+    fn nonce_allowed() {
+        let options = ParseOptions { allow_zero_repeats: true, ..ParseOptions::default() };
+        assert_eq!(parse_function_name("x0".at(1, 0), options),
+                   Ok(Some(FunctionName::Repeat(0))));
+    }
+
     #[test]
     fn too_many_times() {
-        assert_eq!(parse_function_name("x99999999999".at(1, 0)),
+        assert_eq!(parse_function_name("x99999999999".at(1, 0), ParseOptions::default()),
                    Err(Error::InvalidRepeatAmount("x99999999999".at(1, 0))));
     }
 
+    #[test]
+    fn repeat_dynamic() {
+        assert_eq!(parse_function_name("repeat".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::RepeatDynamic { allow_zero: false })));
+    }
+
+    #[test]
+    fn repeat_dynamic_allowing_zero() {
+        let options = ParseOptions { allow_zero_repeats: true, ..ParseOptions::default() };
+        assert_eq!(parse_function_name("repeat".at(1, 0), options),
+                   Ok(Some(FunctionName::RepeatDynamic { allow_zero: true })));
+    }
+
+    #[test]
+    fn joined() {
+        assert_eq!(parse_function_name("joined".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::Joined { allow_zero: false })));
+    }
+
     #[test]
     fn be16() {
-        assert_eq!(parse_function_name("be16".at(1, 0)),
+        assert_eq!(parse_function_name("be16".at(1, 0), ParseOptions::default()),
                    Ok(Some(FunctionName::MultiByte(MultiByteType::Be16))));
     }
 
     #[test]
     fn be32() {
-        assert_eq!(parse_function_name("be32".at(1, 0)),
+        assert_eq!(parse_function_name("be32".at(1, 0), ParseOptions::default()),
                    Ok(Some(FunctionName::MultiByte(MultiByteType::Be32))));
     }
 
     #[test]
     fn le64() {
-        assert_eq!(parse_function_name("le64".at(1, 0)),
+        assert_eq!(parse_function_name("le64".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::MultiByte(MultiByteType::Le64))));
+    }
+
+    #[test]
+    fn alias_u16be() {
+        assert_eq!(parse_function_name("u16be".at(1, 0), ParseOptions::default()),
+                   parse_function_name("be16".at(1, 0), ParseOptions::default()));
+    }
+
+    #[test]
+    fn alias_u32le() {
+        assert_eq!(parse_function_name("u32le".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::MultiByte(MultiByteType::Le32))));
+    }
+
+    #[test]
+    fn alias_u64be() {
+        assert_eq!(parse_function_name("u64be".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::MultiByte(MultiByteType::Be64))));
+    }
+
+    #[test]
+    fn alias_wordle() {
+        assert_eq!(parse_function_name("wordle".at(1, 0), ParseOptions::default()),
+                   parse_function_name("le16".at(1, 0), ParseOptions::default()));
+    }
+
+    #[test]
+    fn alias_dwordbe() {
+        assert_eq!(parse_function_name("dwordbe".at(1, 0), ParseOptions::default()),
+                   Ok(Some(FunctionName::MultiByte(MultiByteType::Be32))));
+    }
+
+    #[test]
+    fn alias_qwordle() {
+        assert_eq!(parse_function_name("qwordle".at(1, 0), ParseOptions::default()),
                    Ok(Some(FunctionName::MultiByte(MultiByteType::Le64))));
     }
 
     #[test]
     fn missing_repeat_amount() {
-        assert_eq!(parse_function_name("x".at(1, 0)),
+        assert_eq!(parse_function_name("x".at(1, 0), ParseOptions::default()),
                    Ok(None));
     }
 
     #[test]
     fn two_xs() {
-        assert_eq!(parse_function_name("xx11".at(1, 0)),
+        assert_eq!(parse_function_name("xx11".at(1, 0), ParseOptions::default()),
                    Ok(None));
     }
 
     #[test]
     fn nonsense() {
-        assert_eq!(parse_function_name("fhqwhgads".at(1, 0)),
+        assert_eq!(parse_function_name("fhqwhgads".at(1, 0), ParseOptions::default()),
                    Ok(None));
     }
 
     #[test]
     fn nonsense_numbers() {
-        assert_eq!(parse_function_name("0123456789".at(1, 0)),
+        assert_eq!(parse_function_name("0123456789".at(1, 0), ParseOptions::default()),
                    Ok(None));
     }
 }
@@ -756,43 +1771,73 @@ mod test_parse_quotes {
     #[test]
     fn empty() {
         assert_eq!(parse_backslashes("".at(1, 0)),
-                   Ok(Cow::Borrowed("")));
+                   Ok(Cow::Borrowed(&b""[..])));
     }
 
     #[test]
     fn longer() {
         assert_eq!(parse_backslashes("longer".at(1, 0)),
-                   Ok(Cow::Borrowed("longer")));
+                   Ok(Cow::Borrowed(&b"longer"[..])));
     }
 
     #[test]
     fn backslash_slash() {
         assert_eq!(parse_backslashes("back\\\\slash".at(1, 0)),
-                   Ok(Cow::from("back\\slash".to_string())));
+                   Ok(Cow::from(b"back\\slash".to_vec())));
     }
 
     #[test]
     fn backslash_quote() {
         assert_eq!(parse_backslashes("back\\\"slash".at(1, 0)),
-                   Ok(Cow::from("back\"slash".to_string())));
+                   Ok(Cow::from(b"back\"slash".to_vec())));
     }
 
     #[test]
     fn backslash_n() {
         assert_eq!(parse_backslashes("back\\nslash".at(1, 0)),
-                   Ok(Cow::from("back\nslash".to_string())));
+                   Ok(Cow::from(b"back\nslash".to_vec())));
     }
 
     #[test]
     fn backslash_r() {
         assert_eq!(parse_backslashes("back\\rslash".at(1, 0)),
-                   Ok(Cow::from("back\rslash".to_string())));
+                   Ok(Cow::from(b"back\rslash".to_vec())));
     }
 
     #[test]
     fn backslash_t() {
         assert_eq!(parse_backslashes("back\\tslash".at(1, 0)),
-                   Ok(Cow::from("back\tslash".to_string())));
+                   Ok(Cow::from(b"back\tslash".to_vec())));
+    }
+
+    #[test]
+    fn backslash_controls() {
+        assert_eq!(parse_backslashes("\\a\\b\\f\\v\\e".at(1, 0)),
+                   Ok(Cow::from(b"\x07\x08\x0C\x0B\x1B".to_vec())));
+    }
+
+    #[test]
+    fn backslash_octal() {
+        assert_eq!(parse_backslashes("\\101\\0\\12\\377".at(1, 0)),
+                   Ok(Cow::from(b"A\x00\x0A\xFF".to_vec())));
+    }
+
+    #[test]
+    fn backslash_octal_then_digits() {
+        assert_eq!(parse_backslashes("\\1018".at(1, 0)),
+                   Ok(Cow::from(b"A8".to_vec())));
+    }
+
+    #[test]
+    fn backslash_octal_too_big() {
+        assert_eq!(parse_backslashes("back\\400slash".at(1, 0)),
+                   Err(Error::EscapeOutOfRange("\\400".at(1, 4))));
+    }
+
+    #[test]
+    fn backslash_not_octal() {
+        assert_eq!(parse_backslashes("\\8".at(1, 0)),
+                   Err(Error::InvalidEscape("\\8".at(1, 0))));
     }
 
     #[test]
@@ -825,16 +1870,16 @@ mod test_edge_cases {
     fn just_a_form() {
         let tokens = vec![ Token::Form("32".at(1, 5)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Dec("32") ]));
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Dec("32"), "32".at(1, 5)) ]));
     }
 
     #[test]
     fn a_content_constant() {
         let tokens = vec![ Token::Alphanum("GPS_QUERY".at(1, 5)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Constant { name: "GPS_QUERY" } ]));
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Constant { name: "GPS_QUERY".at(1, 5) }, "GPS_QUERY".at(1, 5)) ]));
     }
 
     #[test]
@@ -842,11 +1887,25 @@ mod test_edge_cases {
         let tokens = vec![ Token::Alphanum("le32".at(1, 0)),
                            Token::Form("32".at(1, 5)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Function {
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Function {
                        name: FunctionName::MultiByte(MultiByteType::Le32),
+                       position: "le32".at(1, 0),
                        args: vec![ Exp::Dec("32") ],
-                   } ]));
+                   }, "le32".at(1, 0)) ]));
+    }
+
+    #[test]
+    fn form_function_constant() {
+        let tokens = vec![ Token::Alphanum("be16".at(1, 0)),
+                           Token::Form("PORT_HTTPS".at(1, 5)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Function {
+                       name: FunctionName::MultiByte(MultiByteType::Be16),
+                       position: "be16".at(1, 0),
+                       args: vec![ Exp::Constant { name: "PORT_HTTPS".at(1, 5) } ],
+                   }, "be16".at(1, 0)) ]));
     }
 
     #[test]
@@ -856,11 +1915,12 @@ mod test_edge_cases {
                            Token::Alphanum("AB".at(1, 4)),
                            Token::Close(")".at(1, 6)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Function {
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Function {
                        name: FunctionName::Repeat(11),
+                       position: "x11".at(1, 0),
                        args: vec![ Exp::Char(0xAB) ],
-                   } ]));
+                   }, "x11".at(1, 0)) ]));
     }
 
     #[test]
@@ -869,28 +1929,69 @@ mod test_edge_cases {
                            Token::Open("(".at(1, 3)),
                            Token::Close(")".at(1, 4)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Function {
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Function {
                        name: FunctionName::Repeat(11),
+                       position: "x11".at(1, 0),
                        args: vec![],
-                   } ]));
+                   }, "x11".at(1, 0)) ]));
     }
 
     #[test]
     fn suddenly_close() {
-        assert_eq!(parse_tokens(vec![ Token::Close(")".at(1, 0)) ]),
+        assert_eq!(parse_tokens(vec![ Token::Close(")".at(1, 0)) ], ParseOptions::default()),
                    Err(Error::StrayCharacter(")".at(1, 0))));
     }
 
     #[test]
     fn suddenly_open() {
-        assert_eq!(parse_tokens(vec![ Token::Open("(".at(1, 0)) ]),
-                   Err(Error::StrayCharacter("(".at(1, 0))));
+        assert_eq!(parse_tokens(vec![ Token::Open("(".at(1, 0)) ], ParseOptions::default()),
+                   Err(Error::UnclosedGroup("(".at(1, 0))));
+    }
+
+    #[test]
+    fn a_group() {
+        let tokens = vec![ Token::Open("(".at(1, 0)),
+                           Token::Alphanum("AB".at(1, 1)),
+                           Token::Whitespace,
+                           Token::Alphanum("CD".at(1, 4)),
+                           Token::Close(")".at(1, 6)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Group(vec![ Exp::Char(0xAB), Exp::Char(0xCD) ]), "(".at(1, 0)) ]));
+    }
+
+    #[test]
+    fn group_in_function() {
+        let tokens = vec![ Token::Alphanum("x2".at(1, 0)),
+                           Token::Open("(".at(1, 2)),
+                           Token::Open("(".at(1, 3)),
+                           Token::Alphanum("AB".at(1, 4)),
+                           Token::Close(")".at(1, 6)),
+                           Token::Close(")".at(1, 7)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Function {
+                       name: FunctionName::Repeat(2),
+                       position: "x2".at(1, 0),
+                       args: vec![ Exp::Group(vec![ Exp::Char(0xAB) ]) ],
+                   }, "x2".at(1, 0)) ]));
+    }
+
+    #[test]
+    fn unclosed_group() {
+        let tokens = vec![ Token::Alphanum("AB".at(1, 0)),
+                           Token::Whitespace,
+                           Token::Open("(".at(1, 3)),
+                           Token::Alphanum("CD".at(1, 4)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Err(Error::UnclosedGroup("(".at(1, 3))));
     }
 
     #[test]
     fn stray_function_name() {
-        assert_eq!(parse_tokens(vec![ Token::Alphanum("le32".at(1, 0)) ]),
+        assert_eq!(parse_tokens(vec![ Token::Alphanum("le32".at(1, 0)) ], ParseOptions::default()),
                    Err(Error::StrayFunctionName("le32".at(1, 0))));
     }
 
@@ -900,16 +2001,45 @@ mod test_edge_cases {
                            Token::Open("(".at(1, 3)),
                            Token::Alphanum("AB".at(1, 4)) ];
 
-        assert_eq!(parse_tokens(tokens),
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
                    Err(Error::UnclosedFunction("x11".at(1, 0))));
     }
 
+    #[test]
+    fn invalid_escape_position() {
+        let tokens = vec![ Token::Quoted("ok\\q".at(1, 3)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Err(Error::InvalidEscape("\\q".at(1, 6))));
+    }
+
+    #[test]
+    fn comments_ignored() {
+        let tokens = vec![ Token::Comment("Size:".at(1, 0)),
+                           Token::Alphanum("11".at(1, 6)),
+                           Token::Comment("# eleven".at(1, 9)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Char(0x11), "11".at(1, 6)) ]));
+    }
+
     #[test]
     fn byte_string() {
         let tokens = vec![ Token::Alphanum("11".at(1, 0)),
                            Token::Quoted("bytes".at(1, 2)) ];
 
-        assert_eq!(parse_tokens(tokens),
-                   Ok(vec![ Exp::Char(0x11), Exp::StringLiteral { chars: "bytes".into() } ]));
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Char(0x11), "11".at(1, 0)),
+                            (Exp::StringLiteral { bytes: b"bytes"[..].into() }, "bytes".at(1, 2)) ]));
+    }
+
+    #[test]
+    fn positions_of_bytes() {
+        let tokens = vec![ Token::Alphanum("AABBCC".at(1, 4)) ];
+
+        assert_eq!(parse_tokens(tokens, ParseOptions::default()),
+                   Ok(vec![ (Exp::Char(0xAA), "AA".at(1, 4)),
+                            (Exp::Char(0xBB), "BB".at(1, 6)),
+                            (Exp::Char(0xCC), "CC".at(1, 8)) ]));
     }
 }
@@ -26,6 +26,7 @@ impl<'a> Placed<&'a str> {
     /// error occurs parsing it, so that the part that errored can be shown to
     /// the user in isolation. It is safe to index as all the characters in
     /// the source string will have been shown to be ASCII already.
+    #[must_use]
     pub fn substring_ascii(self, from: usize, to: usize) -> Self {
         self.contents[ from .. to ].at(self.line_number, self.column_number + from)
     }
@@ -38,6 +39,7 @@ impl<'a> Placed<&'a str> {
     /// occurs parsing a backslash-escaped character, so the part that errored
     /// can be shown to the user. As characters in the string may be
     /// multi-byte, the ‘from’ index may be larger than the number of columns.
+    #[must_use]
     pub fn substring_mb(self, from: usize, from_column: usize, to: usize) -> Self {
         self.contents[ from .. to ].at(self.line_number, self.column_number + from_column)
     }
@@ -2,40 +2,194 @@
 //! through the lexing and parsing stages, and removing front and back
 //! comments before returning it as an `Exp`.
 
-use std::fmt;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt;
 
 use log::*;
 
-use crate::{ast, lex, parse, pos, tokens};
+use crate::{ast, lex, parse, pos, tokens, FrontComments, ParseOptions};
 
 
 /// Reads a Hexit program into a vector of expressions, by splitting the input
 /// into a sequence of lines, lexing and parsing each line. An error is
-/// returned as soon as something fails to be lexed or parsed.
-pub fn tokenise_and_parse<'src>(input_line: &'src str, line_number: usize) -> Result<Vec<ast::Exp<'src>>, Error<'src>> {
-    let mut line_tokens = lex::lex_source(line_number, input_line).map_err(Error::Lex)?;
+/// returned as soon as something fails to be lexed or parsed. Each
+/// expression is paired with the position it originated from.
+///
+/// This ignores any warnings; programs get read using `read_line` instead,
+/// so this is only used in tests.
+#[cfg(test)]
+pub fn tokenise_and_parse<'src>(input_line: &'src str, line_number: usize, options: ParseOptions) -> Result<Vec<(ast::Exp<'src>, pos::Placed<&'src str>)>, Error<'src>> {
+    read_line(input_line, line_number, options).map(|(exps, _)| exps)
+}
+
+/// Reads one line of a Hexit program into a vector of expressions, in the
+/// same way as `tokenise_and_parse`, also returning warnings if the front
+/// comment hid anything that looked like it was meant to be output, or, if
+/// linting has been turned on, for anything else that looks like a mistake.
+#[allow(clippy::type_complexity)]
+pub fn read_line<'src>(input_line: &'src str, line_number: usize, options: ParseOptions) -> Result<(Vec<(ast::Exp<'src>, pos::Placed<&'src str>)>, Vec<Warning<'src>>), Error<'src>> {
+    let mut line_tokens = lex::lex_source(line_number, input_line, options).map_err(Error::Lex)?;
     trace!("Lexed into tokens → {:#?}", line_tokens);
 
-    strip_front_comment(&mut line_tokens);
+    let front_colon = if options.front_comments == FrontComments::Off {
+        None
+    }
+    else {
+        front_comment_colon_index(&line_tokens).and_then(|index| line_tokens[index].as_stray())
+    };
+
+    let hidden = if options.front_comments == FrontComments::Off {
+        Vec::new()
+    }
+    else {
+        strip_front_comment(&mut line_tokens, options)
+    };
+    let mut warnings: Vec<_> = hidden.first().map(|first| Warning::HiddenByFrontComment { first: *first, count: hidden.len() }).into_iter().collect();
+
+    if let Some(first_colon) = find_double_colon(&line_tokens) {
+        return Err(Error::UnbracketedIPv6(first_colon));
+    }
 
     if let Some(first_invalid_char) = line_tokens.iter().find_map(|t| t.as_stray()) {
         return Err(Error::StraySymbol(first_invalid_char));
     }
 
+    if options.lint {
+        warnings.extend(find_word_like_hex(&line_tokens).into_iter().map(Warning::WordLikeHex));
+    }
+
     line_tokens.push(tokens::Token::Whitespace);
 
-    let exps = parse::parse_tokens(&mut line_tokens.into_iter()).map_err(Error::Parse)?;
-    Ok(exps)
+    let exps = parse::parse_tokens(&mut line_tokens.into_iter(), options).map_err(Error::Parse)?;
+
+    if let Some(colon) = front_colon.filter(|_| options.lint && exps.is_empty()) {
+        warnings.push(Warning::NothingAfterFrontComment(colon));
+    }
+
+    Ok((exps, warnings))
+}
+
+
+/// Removes the byte order mark that some editors on Windows save at the
+/// start of a file, if this is the first line. Anywhere else, it gets left
+/// in to be reported as a stray symbol.
+pub(crate) fn skip_byte_order_mark(input_line: &str, line_number: usize) -> &str {
+    if line_number == 1 {
+        input_line.strip_prefix('\u{FEFF}').unwrap_or(input_line)
+    }
+    else {
+        input_line
+    }
+}
+
+/// Reads the comments from a line of Hexit, including the front comment, in
+/// the order they appear. An error is returned if the line fails to be lexed,
+/// but as the line does not get parsed, parse errors are not checked for.
+pub fn comments<'src>(input_line: &'src str, line_number: usize, options: ParseOptions) -> Result<Vec<pos::Placed<&'src str>>, Error<'src>> {
+    let line_tokens = lex::lex_source_with_comments(line_number, input_line, options).map_err(Error::Lex)?;
+    let comments = line_tokens.iter().filter_map(tokens::Token::as_comment).collect();
+    Ok(comments)
+}
+
+
+/// Searches for two colons right next to each other outside of a form that
+/// run straight into hex digits, such as in `::1`, returning the position of
+/// the first one. This is almost certainly an IPv6 address that is missing
+/// its brackets. It only gets called on the tokens left after the front
+/// comment has been removed, so a `::` in a label such as `Foo::Bar:` is fine.
+fn find_double_colon<'src>(line_tokens: &[tokens::Token<'src>]) -> Option<pos::Placed<&'src str>> {
+    (0 .. line_tokens.len()).find_map(|index| {
+        if starts_ipv6_colons(line_tokens, index) { line_tokens[index].as_stray() }
+                                               else { None }
+    })
+}
+
+/// Returns the index of the colon that ends the front comment: the last
+/// colon in the line that is not one half of a `::` that runs into hex
+/// digits. Skipping over those means that an unbracketed IPv6 address such
+/// as `::1` does not get swallowed by the front comment, and gets reported
+/// instead, while a label that ends in `::`, such as `Header:: 03`, is still
+/// a front comment.
+fn front_comment_colon_index(line_tokens: &[tokens::Token<'_>]) -> Option<usize> {
+    line_tokens.iter().enumerate().rposition(|(index, token)| {
+        token.is_colon()
+            && ! starts_ipv6_colons(line_tokens, index)
+            && ! (index > 0 && starts_ipv6_colons(line_tokens, index - 1))
+    })
+}
+
+/// Whether the token at the given index is the first of two colons right
+/// next to each other, with nothing in between, followed straight away by
+/// a run of hex digits, as in `::1` or the `::` in `fe80::1`.
+fn starts_ipv6_colons(line_tokens: &[tokens::Token<'_>], index: usize) -> bool {
+    match line_tokens.get(index .. index + 3) {
+        Some([ tokens::Token::Stray(first), tokens::Token::Stray(second), tokens::Token::Alphanum(after) ]) => {
+            first.contents == ":" && second.contents == ":"
+                && first.column_number + 1 == second.column_number
+                && second.column_number + 1 == after.column_number
+                && after.contents.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        _ => false,
+    }
+}
+
+
+/// Searches for runs of hex characters made up of nothing but the letters
+/// A to F, such as `DEAD` or `FACADE`. These get output as bytes, but they
+/// could just as easily be a word that was meant to be something else, such
+/// as a constant with a typo in its name. Runs of fewer than four letters
+/// are too common to be worth pointing out, and runs that are used as
+/// function names, such as the `dead` in `dead(AB)`, are left alone.
+fn find_word_like_hex<'src>(line_tokens: &[tokens::Token<'src>]) -> Vec<pos::Placed<&'src str>> {
+    line_tokens.iter().enumerate().filter_map(|(index, token)| {
+        match (token, line_tokens.get(index + 1)) {
+            (tokens::Token::Alphanum(_), Some(tokens::Token::Open(_) | tokens::Token::Form(_) | tokens::Token::Quoted(_) | tokens::Token::Encoded(_, _))) => None,
+            (tokens::Token::Alphanum(span), _) if span.contents.len() >= 4
+                                               && span.contents.chars().all(|c| matches!(c, 'A' ..= 'F' | 'a' ..= 'f')) => Some(*span),
+            _ => None,
+        }
+    }).collect()
 }
 
 
 /// Removes the front comment part of a vector of tokens by finding the last
-/// colon token in the line, and if one exists, draining the vector of every
-/// token up to and including that point.
-fn strip_front_comment<'src>(line_tokens: &mut Vec<tokens::Token<'src>>) {
-    if let Some(last_colon_index) = line_tokens.iter().rposition(|t| t.is_colon()) {
-        line_tokens.drain(..=last_colon_index);
+/// colon token in the line (that is not part of a `::`), and if one exists,
+/// draining the vector of every token up to and including that point.
+///
+/// If the warning has been asked for, and the front comment is made up of
+/// nothing but values, such as the `03` in `03: 04`, it was probably not
+/// meant to be a comment at all, so the positions of those values get
+/// returned. A front comment with any words or strings in it, such as
+/// `EC point format:`, is taken to be a label, and nothing is returned.
+fn strip_front_comment<'src>(line_tokens: &mut Vec<tokens::Token<'src>>, options: ParseOptions) -> Vec<pos::Placed<&'src str>> {
+    let mut hidden = Vec::new();
+
+    if let Some(last_colon_index) = front_comment_colon_index(line_tokens) {
+        // working out what was hidden means parsing each token, so this is
+        // only done when it will be reported
+        if options.front_comments != FrontComments::Checked {
+            line_tokens.drain(..=last_colon_index);
+            return hidden;
+        }
+
+        for token in line_tokens.drain(..=last_colon_index) {
+            match token {
+                tokens::Token::Alphanum(span) | tokens::Token::Form(span) | tokens::Token::Encoded(_, span) => {
+                    match parse::parse_tokens(vec![ token ], options) {
+                        Ok(exps) if ! exps.is_empty()  => hidden.push(span),
+                        _                              => return Vec::new(),
+                    }
+                }
+                tokens::Token::Quoted(_) => {
+                    return Vec::new();
+                }
+                _ => {}
+            }
+        }
     }
+
+    hidden
 }
 
 
@@ -43,6 +197,7 @@ fn strip_front_comment<'src>(line_tokens: &mut Vec<tokens::Token<'src>>) {
 #[derive(PartialEq, Debug)]
 pub enum Error<'src> {
     StraySymbol(pos::Placed<&'src str>),
+    UnbracketedIPv6(pos::Placed<&'src str>),
     Lex(lex::Error<'src>),
     Parse(parse::Error<'src>),
 }
@@ -50,9 +205,10 @@ pub enum Error<'src> {
 impl<'src> fmt::Display for Error<'src> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::StraySymbol(c)  => write!(f, "Stray symbol {:?}", c.contents),
-            Self::Lex(le)         => le.fmt(f),
-            Self::Parse(pe)       => pe.fmt(f),
+            Self::StraySymbol(c)      => write!(f, "Stray symbol {:?}", c.contents),
+            Self::UnbracketedIPv6(_)  => write!(f, "Stray \"::\" outside of a form (IPv6 addresses must be in [brackets])"),
+            Self::Lex(le)             => le.fmt(f),
+            Self::Parse(pe)           => pe.fmt(f),
         }
     }
 }
@@ -60,13 +216,102 @@ impl<'src> fmt::Display for Error<'src> {
 impl<'src> Error<'src> {
     pub fn source_pos(&self) -> &pos::Placed<&'src str> {
         match self {
-            Self::StraySymbol(c)  => c,
-            Self::Lex(le)         => le.source_pos(),
-            Self::Parse(pe)       => pe.source_pos(),
+            Self::StraySymbol(c)      => c,
+            Self::UnbracketedIPv6(c)  => c,
+            Self::Lex(le)             => le.source_pos(),
+            Self::Parse(pe)           => pe.source_pos(),
+        }
+    }
+
+    /// Returns the name of this kind of error, passing on the name of any
+    /// lex or parse error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::StraySymbol(_)      => "stray_symbol",
+            Self::UnbracketedIPv6(_)  => "unbracketed_ipv6",
+            Self::Lex(le)             => le.kind(),
+            Self::Parse(pe)           => pe.kind(),
+        }
+    }
+
+    /// Returns the number of characters in the source line that the error
+    /// covers, starting from its position, for underlining it.
+    pub fn span_length(&self) -> usize {
+        match self {
+            Self::StraySymbol(c)      => c.contents.chars().count(),
+            Self::UnbracketedIPv6(c)  => c.contents.chars().count(),
+            Self::Lex(le)             => le.span_length(),
+            Self::Parse(pe)           => pe.span_length(),
+        }
+    }
+}
+
+
+/// Something about the user’s input that’s not an error, but that is likely
+/// to be a mistake.
+#[derive(PartialEq, Debug)]
+pub enum Warning<'src> {
+
+    /// The front comment of a line contained tokens that would have been
+    /// output had they not been part of the comment, such as the `03` in
+    /// `03: 04`. This holds the position of the first one, and how many
+    /// there were.
+    HiddenByFrontComment { first: pos::Placed<&'src str>, count: usize },
+
+    /// A run of hex characters was made up of only letters, such as `DEAD`,
+    /// so it could have been meant as a word rather than as bytes. This is
+    /// only checked for when linting.
+    WordLikeHex(pos::Placed<&'src str>),
+
+    /// A line had a front comment, but nothing after it, such as `Length:`
+    /// on its own, which could mean that the value after the colon was
+    /// forgotten. This holds the position of the colon, and is only checked
+    /// for when linting.
+    NothingAfterFrontComment(pos::Placed<&'src str>),
+
+    /// A repeat was estimated to produce a suspiciously large amount of
+    /// output, such as a count with one digit too many. This holds the
+    /// position of the repeat, and the fewest bytes it would produce. This is
+    /// only checked for when linting, as it needs the constants to work out.
+    LargeRepeat { position: pos::Placed<&'src str>, length: usize },
+}
+
+impl<'src> fmt::Display for Warning<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HiddenByFrontComment { count: 1, .. }  => write!(f, "Front comment hides a value that will not be output"),
+            Self::HiddenByFrontComment { count, .. }     => write!(f, "Front comment hides {} values that will not be output", count),
+            Self::WordLikeHex(span)                      => write!(f, "Hex run {:?} looks like a word, but will be output as bytes", span.contents),
+            Self::NothingAfterFrontComment(_)            => write!(f, "Nothing follows the front comment, so this line outputs no bytes"),
+            Self::LargeRepeat { length, .. }             => write!(f, "Repeat will output at least {} bytes", length),
         }
     }
 }
 
+impl<'src> Warning<'src> {
+
+    /// Returns the `Placed` token at the heart of the warning, to tell the
+    /// user at which point in the source file it occurred.
+    pub fn source_pos(&self) -> &pos::Placed<&'src str> {
+        match self {
+            Self::HiddenByFrontComment { first, .. }  => first,
+            Self::WordLikeHex(span)                   => span,
+            Self::NothingAfterFrontComment(colon)     => colon,
+            Self::LargeRepeat { position, .. }        => position,
+        }
+    }
+
+    /// Returns the name of this kind of warning, whether it came from a
+    /// front comment or a lint.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::HiddenByFrontComment { .. }  => "hidden_by_front_comment",
+            Self::WordLikeHex(_)               => "word_like_hex",
+            Self::NothingAfterFrontComment(_)  => "nothing_after_front_comment",
+            Self::LargeRepeat { .. }           => "large_repeat",
+        }
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -77,19 +322,19 @@ mod test {
 
     #[test]
     fn empty() {
-        assert_eq!(tokenise_and_parse("", 1),
+        assert_eq!(tokenise_and_parse("", 1, ParseOptions::default()),
                    Ok(vec![]));
     }
 
     #[test]
     fn space() {
-        assert_eq!(tokenise_and_parse(" ", 1),
+        assert_eq!(tokenise_and_parse(" ", 1, ParseOptions::default()),
                    Ok(vec![]));
     }
 
     #[test]
     fn spaces() {
-        assert_eq!(tokenise_and_parse("  ", 1),
+        assert_eq!(tokenise_and_parse("  ", 1, ParseOptions::default()),
                    Ok(vec![]));
     }
 
@@ -97,75 +342,372 @@ mod test {
 
     #[test]
     fn lonely() {
-        assert_eq!(tokenise_and_parse("0", 1),
+        assert_eq!(tokenise_and_parse("0", 1, ParseOptions::default()),
                    Err(Error::Parse(parse::Error::SingleHex("0".at(1, 0)))));
     }
 
     #[test]
     fn meme() {
-        assert_eq!(tokenise_and_parse("E", 2),
+        assert_eq!(tokenise_and_parse("E", 2, ParseOptions::default()),
                    Err(Error::Parse(parse::Error::SingleHex("E".at(2, 0)))));
     }
 
     #[test]
     fn otherwise() {
-        assert_eq!(tokenise_and_parse("q", 3),
+        assert_eq!(tokenise_and_parse("q", 3, ParseOptions::default()),
                    Err(Error::Parse(parse::Error::StrayCharacter("q".at(3, 0)))));
     }
 
     #[test]
     fn closure() {
-        assert_eq!(tokenise_and_parse(")", 4),
+        assert_eq!(tokenise_and_parse(")", 4, ParseOptions::default()),
                    Err(Error::Parse(parse::Error::StrayCharacter(")".at(4, 0)))));
     }
 
     #[test]
     fn exordium() {
-        assert_eq!(tokenise_and_parse("[", 5),
+        assert_eq!(tokenise_and_parse("[", 5, ParseOptions::default()),
                    Err(Error::Lex(lex::Error::UnclosedForm("[".at(5, 0)))));
     }
 
     #[test]
     fn weird_nested_form() {
-        assert_eq!(tokenise_and_parse("[[:alpha:]]", 6),
-                   Err(Error::StraySymbol("]".at(6, 10))));
+        assert_eq!(tokenise_and_parse("[[:alpha:]]", 6, ParseOptions::default()),
+                   Err(Error::Parse(parse::Error::InvalidForm("[:alpha:]".at(6, 0)))));
+    }
+
+    // windows line tests
+
+    #[test]
+    fn byte_order_mark() {
+        assert_eq!(skip_byte_order_mark("\u{FEFF}03", 1),
+                   "03");
+    }
+
+    #[test]
+    fn byte_order_mark_after_the_first_line() {
+        assert_eq!(skip_byte_order_mark("\u{FEFF}03", 2),
+                   "\u{FEFF}03");
+    }
+
+    #[test]
+    fn carriage_return() {
+        assert_eq!(tokenise_and_parse("03 \"a\"\r", 1, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(1, 0)),
+                            (ast::Exp::StringLiteral { bytes: b"a"[..].into() }, "a".at(1, 3)) ]));
+    }
+
+    #[test]
+    fn carriage_return_after_function() {
+        assert_eq!(tokenise_and_parse("x2(03)\r", 1, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Function { name: ast::FunctionName::Repeat(2), position: "x2".at(1, 0), args: vec![ ast::Exp::Char(3) ] }, "x2".at(1, 0)) ]));
+    }
+
+    // comment reading tests
+
+    #[test]
+    fn no_comments() {
+        assert_eq!(comments("AB CD", 1, ParseOptions::default()),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn both_comments() {
+        assert_eq!(comments("\"Magic:\" number: 03  # three", 13, ParseOptions::default()),
+                   Ok(vec![ "\"Magic:\" number:".at(13, 0), "# three".at(13, 21) ]));
+    }
+
+    #[test]
+    fn comments_do_not_change_exps() {
+        assert_eq!(tokenise_and_parse("Magic: 03  # three", 14, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(14, 7)) ]));
     }
 
     // front comment stripping tests
 
     #[test]
     fn front_comment() {
-        assert_eq!(tokenise_and_parse("Magic number: 03", 7),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("Magic number: 03", 7, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(7, 14)) ]));
     }
 
     #[test]
     fn front_comment_containing_chars() {
-        assert_eq!(tokenise_and_parse("Magic••••number: 03", 8),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("Magic••••number: 03", 8, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(8, 17)) ]));
     }
 
     #[test]
     fn front_comment_containing_form() {
-        assert_eq!(tokenise_and_parse("[Magic] number: 03", 9),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("[Magic] number: 03", 9, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(9, 16)) ]));
     }
 
     #[test]
     fn front_comment_containing_form_containing_colon() {
-        assert_eq!(tokenise_and_parse("[[:alpha:]] number: 03", 10),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("[[:alpha:]] number: 03", 10, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(10, 20)) ]));
     }
 
     #[test]
     fn front_comment_containing_string() {
-        assert_eq!(tokenise_and_parse("\"Magic\" number: 03", 11),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("\"Magic\" number: 03", 11, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(11, 16)) ]));
     }
 
     #[test]
     fn front_comment_containing_string_containing_colon() {
-        assert_eq!(tokenise_and_parse("\"Magic:::number\": 03", 12),
-                   Ok(vec![ ast::Exp::Char(3) ]));
+        assert_eq!(tokenise_and_parse("\"Magic:::number\": 03", 12, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(12, 18)) ]));
+    }
+
+    #[test]
+    fn front_comments_off() {
+        let options = ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() };
+        assert_eq!(tokenise_and_parse("Magic number: 03", 7, options),
+                   Err(Error::StraySymbol(":".at(7, 12))));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn front_comments_off_forms_keep_colons() {
+        let options = ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() };
+        assert_eq!(tokenise_and_parse("[::1] be32[2017-12-31T21:36:45]", 7, options).map(|exps| exps.len()),
+                   Ok(2));
+    }
+
+    #[test]
+    fn front_comments_off_not_kept() {
+        let options = ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() };
+        assert_eq!(comments("Magic: 03  # three", 13, options),
+                   Ok(vec![ "# three".at(13, 11) ]));
+    }
+
+    // IPv6 without brackets tests
+
+    #[test]
+    fn unbracketed_ipv6() {
+        assert_eq!(tokenise_and_parse("::1", 19, ParseOptions::default()),
+                   Err(Error::UnbracketedIPv6(":".at(19, 0))));
+    }
+
+    #[test]
+    fn unbracketed_ipv6_in_the_middle() {
+        assert_eq!(tokenise_and_parse("AB fe80::1", 19, ParseOptions::default()),
+                   Err(Error::UnbracketedIPv6(":".at(19, 7))));
+    }
+
+    #[test]
+    fn unbracketed_ipv6_without_front_comments() {
+        let options = ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() };
+        assert_eq!(tokenise_and_parse("::1", 19, options),
+                   Err(Error::UnbracketedIPv6(":".at(19, 0))));
+    }
+
+    #[test]
+    fn bracketed_ipv6_after_front_comment() {
+        assert_eq!(tokenise_and_parse("Address: [::1]", 19, ParseOptions::default()).map(|exps| exps.len()),
+                   Ok(1));
+    }
+
+    #[test]
+    fn parenthesised_label_in_front_comment() {
+        assert_eq!(tokenise_and_parse("(a:b): 03", 19, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(19, 7)) ]));
+    }
+
+    #[test]
+    fn colon_in_parentheses_is_a_bit_field() {
+        // a colon inside parentheses is read as a bit field, rather than the
+        // end of a front comment, even where a bit field is not allowed
+        assert_eq!(tokenise_and_parse("(a:b) 03", 19, ParseOptions::default()),
+                   Err(Error::Parse(parse::Error::MisplacedBitField("a:b".at(19, 1)))));
+    }
+
+    #[test]
+    fn double_colon_in_front_comment() {
+        assert_eq!(tokenise_and_parse("Foo::Bar: 03", 19, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(19, 10)) ]));
+    }
+
+    #[test]
+    fn double_colon_ending_front_comment() {
+        assert_eq!(tokenise_and_parse("Header:: 03", 19, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(19, 9)) ]));
+    }
+
+    #[test]
+    fn unbracketed_ipv6_after_front_comment() {
+        assert_eq!(tokenise_and_parse("Address: fe80::1", 19, ParseOptions::default()),
+                   Err(Error::UnbracketedIPv6(":".at(19, 13))));
+    }
+
+    #[test]
+    fn separate_colons_are_front_comments() {
+        assert_eq!(tokenise_and_parse("Type: A: 03", 19, ParseOptions::default()),
+                   Ok(vec![ (ast::Exp::Char(3), "03".at(19, 9)) ]));
+    }
+
+    // front comment warning tests
+
+    fn warn_options() -> ParseOptions {
+        ParseOptions { front_comments: FrontComments::Checked, ..ParseOptions::default() }
+    }
+
+    #[test]
+    fn front_comment_words_not_warned() {
+        assert_eq!(read_line("Magic number: 03", 15, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(3), "03".at(15, 14)) ], vec![])));
+    }
+
+    #[test]
+    fn front_comment_label_not_warned() {
+        assert_eq!(read_line("EC point [1]: 03", 15, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(3), "03".at(15, 14)) ], vec![])));
+    }
+
+    #[test]
+    fn front_comment_string_not_warned() {
+        assert_eq!(read_line("AB \"CD\": 03", 15, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(3), "03".at(15, 9)) ], vec![])));
+    }
+
+    #[test]
+    fn front_comment_hides_a_byte_not_warned_by_default() {
+        assert_eq!(read_line("03: 04", 16, ParseOptions::default()),
+                   Ok((vec![ (ast::Exp::Char(4), "04".at(16, 4)) ], vec![])));
+    }
+
+    #[test]
+    fn front_comment_hides_a_byte() {
+        assert_eq!(read_line("03: 04", 16, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(4), "04".at(16, 4)) ],
+                       vec![ Warning::HiddenByFrontComment { first: "03".at(16, 0), count: 1 } ])));
+    }
+
+    #[test]
+    fn front_comment_hides_several() {
+        assert_eq!(read_line(" 01 [2] IP_UDP: 04", 17, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(4), "04".at(17, 16)) ],
+                       vec![ Warning::HiddenByFrontComment { first: "01".at(17, 1), count: 3 } ])));
+    }
+
+    #[test]
+    fn front_comment_hides_before_each_colon() {
+        assert_eq!(read_line("AB: CD: EF", 18, warn_options()),
+                   Ok((vec![ (ast::Exp::Char(0xEF), "EF".at(18, 8)) ],
+                       vec![ Warning::HiddenByFrontComment { first: "AB".at(18, 0), count: 2 } ])));
+    }
+
+
+    // lint tests
+
+    fn lint_options() -> ParseOptions {
+        ParseOptions { lint: true, ..ParseOptions::default() }
+    }
+
+    #[test]
+    fn word_like_hex_not_warned_by_default() {
+        assert_eq!(read_line("DEAD", 19, ParseOptions::default()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn word_like_hex_warned() {
+        assert_eq!(read_line("01 FACADE 02", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![ Warning::WordLikeHex("FACADE".at(19, 3)) ]));
+    }
+
+    #[test]
+    fn word_like_hex_lowercase_warned() {
+        assert_eq!(read_line("beef", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![ Warning::WordLikeHex("beef".at(19, 0)) ]));
+    }
+
+    #[test]
+    fn word_like_hex_too_short() {
+        assert_eq!(read_line("AB CD EF", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn word_like_hex_with_digits() {
+        assert_eq!(read_line("DEAD0F", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn word_like_hex_separated() {
+        assert_eq!(read_line("DE_AD", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn word_like_hex_in_front_comment() {
+        assert_eq!(read_line("Beef count: 01", 19, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+
+    #[test]
+    fn nothing_after_front_comment_warned() {
+        assert_eq!(read_line("Length:", 20, lint_options()),
+                   Ok((vec![], vec![ Warning::NothingAfterFrontComment(":".at(20, 6)) ])));
+    }
+
+    #[test]
+    fn nothing_after_front_comment_but_a_comment() {
+        assert_eq!(read_line("Cipher suites:  # see below", 20, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![ Warning::NothingAfterFrontComment(":".at(20, 13)) ]));
+    }
+
+    #[test]
+    fn nothing_after_front_comment_not_warned_by_default() {
+        assert_eq!(read_line("Length:", 20, ParseOptions::default()),
+                   Ok((vec![], vec![])));
+    }
+
+    #[test]
+    fn something_after_front_comment() {
+        assert_eq!(read_line("Length: 04", 20, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+    #[test]
+    fn nothing_without_front_comment() {
+        assert_eq!(read_line("# just a comment", 20, lint_options()).map(|(_, ws)| ws),
+                   Ok(vec![]));
+    }
+
+
+    // span length tests
+
+    fn error_span_length(input_line: &str) -> usize {
+        tokenise_and_parse(input_line, 1, ParseOptions::default()).unwrap_err().span_length()
+    }
+
+    #[test]
+    fn span_of_single_character() {
+        assert_eq!(error_span_length("AB C"), 1);
+    }
+
+    #[test]
+    fn span_of_whole_token() {
+        assert_eq!(error_span_length("AB x0(CD)"), 2);
+    }
+
+    #[test]
+    fn span_of_form_includes_brackets() {
+        assert_eq!(error_span_length("AB [NEE NAW]"), 9);
+    }
+
+    #[test]
+    fn span_of_unclosed_form() {
+        assert_eq!(error_span_length("AB [b_01"), 5);
+    }
+
+    #[test]
+    fn span_in_characters_not_bytes() {
+        assert_eq!(error_span_length("[🥑é]"), 4);
     }
 }
@@ -0,0 +1,153 @@
+//! Reading and running a program one line at a time, rather than holding
+//! every line and every expression in memory at once.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::constants::Table;
+use crate::eval::{Evaluator, LocatedError};
+use crate::{read, ParseOptions, Program};
+
+
+/// A line runner reads and runs a Hexit program one line at a time. Only the
+/// line being run, and the bytes it produces, need to be in memory, so
+/// programs that are too large to read in all at once can still be run.
+///
+/// As each line gets run before the next one is read, the bytes from the
+/// lines before an error will already have been produced by the time the
+/// error is found, and only the first line with an error gets reported.
+pub struct LineRunner<'consts> {
+
+    /// The evaluator that runs each line.
+    evaluator: Evaluator<'consts>,
+
+    /// The options to read each line with.
+    options: ParseOptions,
+
+    /// The number of the next line to be run.
+    line_number: usize,
+}
+
+impl<'consts> LineRunner<'consts> {
+
+    /// Creates a new line runner that looks up constants in the given table,
+    /// and reads each line with the given options. The output can be limited
+    /// for each top-level expression, just as when running a `Program`.
+    pub fn new(constants: &'consts Table, limit: Option<usize>, line_limit: Option<usize>, options: ParseOptions) -> Self {
+        let evaluator = Evaluator::new(constants, limit, line_limit);
+        Self { evaluator, options, line_number: 1 }
+    }
+
+    /// Reads and runs the next line of the program, returning the bytes it
+    /// produced along with anything suspicious found while reading it.
+    pub fn run_line<'src>(&mut self, input_line: &'src str) -> Result<(Vec<u8>, Vec<read::Warning<'src>>), LineError<'src>> {
+        let line_number = self.line_number;
+        self.line_number += 1;
+
+        let input_line = read::skip_byte_order_mark(input_line, line_number);
+        let (placed_exps, mut warnings) = read::read_line(input_line, line_number, self.options).map_err(LineError::Read)?;
+        let (exps, positions) = placed_exps.into_iter().unzip();
+        let program = Program { exps, positions, warnings: Vec::new() };
+
+        if self.options.lint {
+            warnings.extend(self.evaluator.lint(&program));
+        }
+
+        let bytes = self.evaluator.evaluate(&program).map_err(LineError::Run)?;
+        Ok((bytes, warnings))
+    }
+}
+
+
+/// Something that can go wrong running a single line of a program.
+#[derive(PartialEq, Debug)]
+pub enum LineError<'src> {
+
+    /// The line could not be read.
+    Read(read::Error<'src>),
+
+    /// The line was read, but it could not be run.
+    Run(LocatedError<'src>),
+}
+
+impl<'src> fmt::Display for LineError<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(e)  => e.fmt(f),
+            Self::Run(e)   => e.fmt(f),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn lines_in_order() {
+        let table = Table::builtin_set();
+        let mut runner = LineRunner::new(&table, None, None, ParseOptions::default());
+
+        assert_eq!(runner.run_line("AB CD"), Ok((vec![ 0xAB, 0xCD ], vec![])));
+        assert_eq!(runner.run_line("be16[1] IP_UDP"), Ok((vec![ 0x00, 0x01, 0x11 ], vec![])));
+        assert_eq!(runner.run_line("# nothing"), Ok((vec![], vec![])));
+    }
+
+    #[test]
+    fn read_error_line_number() {
+        let table = Table::builtin_set();
+        let mut runner = LineRunner::new(&table, None, None, ParseOptions::default());
+
+        assert!(runner.run_line("AB").is_ok());
+        match runner.run_line("CD ZZ") {
+            Err(LineError::Read(e))  => assert_eq!(e.source_pos().line_number, 2),
+            other                    => panic!("Expected read error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn byte_order_mark_on_the_first_line_only() {
+        let table = Table::builtin_set();
+        let mut runner = LineRunner::new(&table, None, None, ParseOptions::default());
+
+        assert_eq!(runner.run_line("\u{FEFF}AB"), Ok((vec![ 0xAB ], vec![])));
+        match runner.run_line("\u{FEFF}CD") {
+            Err(LineError::Read(e))  => assert_eq!((e.source_pos().line_number, e.source_pos().column_number), (2, 0)),
+            other                    => panic!("Expected read error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn run_error_line_number() {
+        let table = Table::builtin_set();
+        let mut runner = LineRunner::new(&table, None, None, ParseOptions::default());
+
+        assert!(runner.run_line("AB").is_ok());
+        assert!(runner.run_line("CD").is_ok());
+        match runner.run_line("EF BGP_CLOSE") {
+            Err(LineError::Run(e))  => assert_eq!((e.source_pos().line_number, e.source_pos().column_number), (3, 3)),
+            other                   => panic!("Expected run error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn many_lines_in_turn() {
+        // the runner keeps count of the lines itself, so each line can be
+        // given to it as soon as it has been made
+        let table = Table::builtin_set();
+        let mut runner = LineRunner::new(&table, Some(16), None, ParseOptions::default());
+        let mut total = 0_usize;
+
+        for index in 0 .. 100_000_u32 {
+            let line = alloc::format!("be32[{}] x4(AB)", index);
+            let (bytes, _) = runner.run_line(&line).expect("Running failed");
+            assert_eq!(&bytes[.. 4], &index.to_be_bytes());
+            total += bytes.len();
+        }
+
+        assert_eq!(total, 100_000 * 8);
+    }
+}
@@ -27,9 +27,53 @@ pub enum Token<'src> {
     /// A quoted string, such as `"vorbis"`.
     Quoted(Placed<&'src str>),
 
+    /// A quoted string with a prefix that says how its contents are encoded,
+    /// such as `b32"MFRGG==="`. This is placed at the start of the prefix,
+    /// but holds only what is inside the quotes.
+    Encoded(StringEncoding, Placed<&'src str>),
+
     /// Any other character, such as `é`. This is an error if encountered
     /// outside of a front comment.
     Stray(Placed<&'src str>),
+
+    /// A comment, such as `# header` or the front comment `Length:`. These
+    /// are only produced when the lexer has been asked to keep comments, and
+    /// produce no bytes.
+    Comment(Placed<&'src str>),
+}
+
+/// The ways that the contents of a prefixed string can be encoded.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum StringEncoding {
+
+    /// Base32, using the RFC 4648 alphabet, with the `b32` prefix.
+    Base32,
+
+    /// Pairs of hex characters with any amount of whitespace between them,
+    /// with the `hex` prefix.
+    Hex,
+}
+
+impl StringEncoding {
+
+    /// Returns the encoding that a string prefix stands for, or `None` if it
+    /// is not one of the known prefixes.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "b32"  => Some(Self::Base32),
+            "hex"  => Some(Self::Hex),
+            _      => None,
+        }
+    }
+
+    /// Returns the prefix that comes before the opening quote of a string
+    /// in this encoding.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Base32  => "b32",
+            Self::Hex     => "hex",
+        }
+    }
 }
 
 impl<'src> Token<'src> {
@@ -46,6 +90,17 @@ impl<'src> Token<'src> {
         false
     }
 
+    /// Returns the contents of a comment token, if this token is one. This
+    /// gets used to pick the comments out of a line for tools to use.
+    pub fn as_comment(&self) -> Option<Placed<&'src str>> {
+        if let Token::Comment(placed) = self {
+            Some(*placed)
+        }
+        else {
+            None
+        }
+    }
+
     /// Returns the contents of a stray token, if this token is one. This gets
     /// used to determine whether to error out with a misplaced character.
     pub fn as_stray(&self) -> Option<Placed<&'src str>> {
@@ -0,0 +1,186 @@
+//! Verifying that the output’s length matches a property before printing it.
+//!
+//! The command-line program does this with its `--verify-length` and
+//! `--verify-boundary` options, but the checks live here so that other
+//! programs that run Hexit code can make the same ones.
+
+use core::fmt;
+
+use crate::eval::LocatedError;
+
+
+/// Hexit can be run with some **verification** that can be run after all the
+/// output has been generated, making sure that its length matches some
+/// property.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Verification {
+
+    /// Hexit should verify that the output’s length _exactly_ matches the
+    /// given number.
+    ExactLength(OutputLength),
+
+    /// Hexit should verify that the output’s length is a multiple of the
+    /// given number.
+    Multiple(OutputLength),
+
+    /// Hexit should not verify anything and just print the output.
+    AnythingGoes,
+}
+
+/// The number of bytes that get produced as a result of running Hexit.
+pub type OutputLength = usize;
+
+impl Verification {
+
+    /// Verifies the computed output length to make sure it conforms to the
+    /// user’s wishes, returning an error describing what the length _should_
+    /// be if validation fails.
+    pub fn verify(self, ol: OutputLength) -> Result<(), VerificationError> {
+        if let Verification::ExactLength(exact) = self {
+            if ol != exact {
+                return Err(VerificationError { expected: exact, actual: ol, kind: VerificationKind::ExactLength });
+            }
+        }
+
+        if let Verification::Multiple(multiple) = self {
+            // zero is the only multiple of zero, and this avoids dividing by it
+            if ol.checked_rem(multiple).unwrap_or(ol) != 0 {
+                return Err(VerificationError { expected: multiple, actual: ol, kind: VerificationKind::Multiple });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+/// The error that occurs when the output’s length does not match.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct VerificationError {
+
+    /// The number that the length was verified against.
+    pub expected: OutputLength,
+
+    /// The length of the output that was actually produced.
+    pub actual: OutputLength,
+
+    /// Which kind of verification failed.
+    pub kind: VerificationKind,
+}
+
+/// The kind of verification that failed, which says how the expected number
+/// should relate to the actual length.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum VerificationKind {
+
+    /// The length should have been exactly the expected number.
+    ExactLength,
+
+    /// The length should have been a multiple of the expected number.
+    Multiple,
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            VerificationKind::ExactLength  => write!(f, "{}", self.expected),
+            VerificationKind::Multiple     => write!(f, "multiple of {}", self.expected),
+        }
+    }
+}
+
+
+/// Something that can go wrong running a program that has to produce an
+/// exact number of bytes, with `Program::run_exact`.
+#[derive(PartialEq, Debug)]
+pub enum ExactRunError<'src> {
+
+    /// The program could not be run.
+    Run(LocatedError<'src>),
+
+    /// The program ran, but produced the wrong number of bytes.
+    Length(VerificationError),
+}
+
+impl<'src> fmt::Display for ExactRunError<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Run(e)     => e.fmt(f),
+            Self::Length(e)  => write!(f, "Output is {} bytes long, but it should be {}", e.actual, e),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn exact_miss_fields() {
+        let error = Verification::ExactLength(13).verify(3).unwrap_err();
+        assert_eq!(error.expected, 13);
+        assert_eq!(error.actual, 3);
+        assert_eq!(error.kind, VerificationKind::ExactLength);
+    }
+
+    #[test]
+    fn multiple_miss_fields() {
+        let error = Verification::Multiple(13).verify(27).unwrap_err();
+        assert_eq!(error.expected, 13);
+        assert_eq!(error.actual, 27);
+        assert_eq!(error.kind, VerificationKind::Multiple);
+    }
+
+    #[test]
+    fn anything_1() {
+        assert_eq!(Ok(()), Verification::AnythingGoes.verify(1));
+    }
+
+    #[test]
+    fn anything_0() {
+        assert_eq!(Ok(()), Verification::AnythingGoes.verify(0));
+    }
+
+    #[test]
+    fn exact_hit() {
+        assert_eq!(Ok(()), Verification::ExactLength(13).verify(13));
+    }
+
+    #[test]
+    fn exact_miss() {
+        assert_eq!(Err("13".to_string()), Verification::ExactLength(13).verify(3).map_err(|e| e.to_string()));
+    }
+
+    #[test]
+    fn multiple_exact() {
+        assert_eq!(Ok(()), Verification::Multiple(13).verify(13));
+    }
+
+    #[test]
+    fn multiple_half() {
+        assert_eq!(Ok(()), Verification::Multiple(13).verify(26));
+    }
+
+    #[test]
+    fn multiple_of_zero_hit() {
+        assert_eq!(Ok(()), Verification::Multiple(0).verify(0));
+    }
+
+    #[test]
+    fn multiple_of_zero_miss() {
+        assert_eq!(Err("multiple of 0".to_string()), Verification::Multiple(0).verify(3).map_err(|e| e.to_string()));
+    }
+
+    #[test]
+    fn exact_run_length_message() {
+        let error = ExactRunError::Length(Verification::ExactLength(4).verify(6).unwrap_err());
+        assert_eq!(error.to_string(), "Output is 6 bytes long, but it should be 4");
+    }
+
+    #[test]
+    fn multiple_miss() {
+        assert_eq!(Err("multiple of 13".to_string()), Verification::Multiple(13).verify(3).map_err(|e| e.to_string()));
+    }
+}
@@ -0,0 +1,16 @@
+// Packed BCD puts one decimal digit in each half of a byte. Odd numbers of
+// digits get a leading zero, and leading zeros in the input are kept.
+
+
+test_eval!(bcd_four_digits:     "bcd[1234]"         => Ok(vec![ 0x12, 0x34 ]));
+test_eval!(bcd_one_digit:       "bcd[5]"            => Ok(vec![ 0x05 ]));
+test_eval!(bcd_odd_digits:      "bcd[12345]"        => Ok(vec![ 0x01, 0x23, 0x45 ]));
+test_eval!(bcd_zero:            "bcd[0]"            => Ok(vec![ 0x00 ]));
+test_eval!(bcd_leading_zeros:   "bcd[0012]"         => Ok(vec![ 0x00, 0x12 ]));
+test_eval!(bcd_nines:           "bcd[99]"           => Ok(vec![ 0x99 ]));
+test_eval!(bcd_in_parens:       "bcd([1234])"       => Ok(vec![ 0x12, 0x34 ]));
+test_eval!(bcd_long:            "bcd[12345678901234567890123]" => Ok(vec![ 0x01, 0x23, 0x45, 0x67, 0x89, 0x01, 0x23, 0x45, 0x67, 0x89, 0x01, 0x23 ]));
+
+test_eval!(bcd_byte:            "bcd(12)"           => Err(String::from("Invalid arguments: Function ‘bcd’ needs a decimal number, not a value of type byte")));
+test_eval!(bcd_hex_bytes:       "bcd(1234)"         => Err(String::from("Invalid arguments: Too many arguments (2) passed to function")));
+test_eval!(bcd_float:           "bcd[f1.5]"         => Err(String::from("Invalid arguments: Function ‘bcd’ needs a decimal number, not a value of type unsized float")));
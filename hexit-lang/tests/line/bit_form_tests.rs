@@ -10,6 +10,17 @@ test_eval!(bits_0s:  "[b00000000]" => Ok(vec![ 0 ]));
 test_eval!(bits_255_u: "[b1111_1111]" => Ok(vec![ 255 ]));
 test_eval!(bits_0s_u:  "[b0000_0000]" => Ok(vec![ 0 ]));
 
+test_eval!(bits_195_s:  "[b 1100 0011]"  => Ok(vec![ 195 ]));
+test_eval!(bits_195_s2: "[b1100  0011]"  => Ok(vec![ 195 ]));
+test_eval!(bits_mixed:  "[b 11_00 0011]" => Ok(vec![ 195 ]));
+
+#[test]
+fn bits_only_spaces() {
+    let lines = vec![ "[b   ]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"b   \"");
+}
+
 // 1-byte bits, extended
 test_eval!(bits_be16s: "be16[b11111110]" => Ok(vec![ 0, 254 ]));
 test_eval!(bits_le16s: "le16[b11111110]" => Ok(vec![ 254, 0 ]));
@@ -22,6 +33,7 @@ test_eval!(bits_le64s: "le64[b11111110]" => Ok(vec![ 254, 0, 0, 0, 0, 0, 0, 0 ])
 test_eval!(bits_be16_0: "be16[b00110000110011]" => Ok(vec![ 0x0C, 0x33 ]));
 test_eval!(bits_be16: "be16[b1100110000110011]" => Ok(vec![ 0xCC, 0x33 ]));
 test_eval!(bits_le16: "le16[b1100110000110011]" => Ok(vec![ 0x33, 0xCC ]));
+test_eval!(bits_be16_s: "be16[b 1100 1100 0011 0011]" => Ok(vec![ 0xCC, 0x33 ]));
 
 // 2-byte bits, extended
 test_eval!(bits_be32m: "be32[b1100110000110011]" => Ok(vec![ 0, 0, 0xCC, 0x33 ]));
@@ -50,3 +62,31 @@ test_eval!(bits_be64: "be64[b110011000011001101010101101010101111000000001111111
 test_eval!(bits_le64: "le64[b1100110000110011010101011010101011110000000011111111000010101010]" => {
     Ok(vec![ 0xAA, 0xF0, 0x0F, 0xF0, 0xAA, 0x55, 0x33, 0xCC ])
 });
+
+// the largest values at each width boundary
+test_eval!(bits_max_8:  "[b1111_1111]"                                      => Ok(vec![ 0xFF ]));
+test_eval!(bits_max_16: "be16[b1111_1111_1111_1111]"                        => Ok(vec![ 0xFF; 2 ]));
+test_eval!(bits_max_32: "be32[b1111_1111_1111_1111_1111_1111_1111_1111]"    => Ok(vec![ 0xFF; 4 ]));
+
+test_eval!(bits_max_64: "le64[b1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111]" => {
+    Ok(vec![ 0xFF; 8 ])
+});
+
+// one bit past each width boundary
+test_eval!(bits_over_8:  "[b1_1111_1111]" => Err(String::from("2-byte number ‘511’ without an endianness")));
+
+test_eval!(bits_over_16: "be16[b1_1111_1111_1111_1111]" => {
+    Err(String::from("4-byte number ‘131071’ cannot be narrowed to 2 bytes (Hexit never narrows values)"))
+});
+
+test_eval!(bits_over_32: "be32[b1_1111_1111_1111_1111_1111_1111_1111_1111]" => {
+    Err(String::from("8-byte number ‘8589934591’ cannot be narrowed to 4 bytes (Hexit never narrows values)"))
+});
+
+test_eval!(bits_over_64: "be64[b1_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111_1111]" => {
+    Err(String::from("Bit set of length 65 without an endianness"))
+});
+
+test_eval!(bits_over_64_zeroes: "[b0_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000_0000]" => {
+    Err(String::from("Bit set of length 65 without an endianness"))
+});
@@ -0,0 +1,46 @@
+// packing bit fields into bytes
+test_eval!(bits_ipv4_first:  "bits(4:4 4:5)"             => Ok(vec![ 0x45 ]));
+test_eval!(bits_ipv6_first:  "bits(4:6 8:0 20:0)"        => Ok(vec![ 0x60, 0x00, 0x00, 0x00 ]));
+test_eval!(bits_flags:       "bits(1:1 1:0 1:1 5:0x1F)"  => Ok(vec![ 0xBF ]));
+test_eval!(bits_with_form:   "bits(4:4 [b0101])"         => Ok(vec![ 0x45 ]));
+test_eval!(bits_with_bytes:  "bits(4:0xF AB 4:0)"        => Ok(vec![ 0xFA, 0xB0 ]));
+test_eval!(bits_wide:        "bits(16:256)"              => Ok(vec![ 0x01, 0x00 ]));
+test_eval!(bits_nothing:     "bits()"                    => Ok(vec![]));
+test_eval!(bits_part_byte:   "bits(4:4)"                 => Err(String::from("Invalid arguments: Bits function given 4 bits, which is not a whole number of bytes")));
+
+// bit fields in other functions
+test_eval!(bits_repeated:    "x2(bits(4:4 4:5))"         => Ok(vec![ 0x45, 0x45 ]));
+test_eval!(bits_in_group:    "(bits(4:0 12:0xABC))"      => Ok(vec![ 0x0A, 0xBC ]));
+test_eval!(bits_of_group:    "bits((4:4 4:5))"           => Ok(vec![ 0x45 ]));
+test_eval!(bits_of_groups:   "bits(1:1 (3:0 (4:0xF)))"   => Ok(vec![ 0x8F ]));
+
+#[test]
+fn bits_field_too_big() {
+    let lines = vec![ "bits(4:16 4:0)" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Invalid bit field \"4:16\"");
+    assert_eq!(errors[0].source_pos().column_number, 5);
+}
+
+#[test]
+fn bits_field_outside_parentheses() {
+    // the colon makes everything before it a front comment
+    let lines = vec![ "AB 4:5" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Unpaired hex character \"5\"");
+}
+
+#[test]
+fn bits_field_in_other_function() {
+    let lines = vec![ "be16(12:0xABC)" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Bit field \"12:0xABC\" outside of bits()");
+    assert_eq!(errors[0].source_pos().column_number, 5);
+}
+
+#[test]
+fn bits_field_in_group() {
+    let lines = vec![ "(4:4 4:5)" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Bit field \"4:4\" outside of bits()");
+}
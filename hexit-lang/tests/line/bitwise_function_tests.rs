@@ -1,14 +1,96 @@
 // ‘and’ function
 test_eval!(and_1byte:  "and(5C)"     => Ok(vec![ 0x5C ]));
 test_eval!(and_2bytes: "and(5C 74)"  => Ok(vec![ 0x54 ]));
+test_eval!(and_empty:  "and()"       => Err(String::from("Invalid arguments: No arguments passed to ‘and’ function")));
 
 // ‘or’ function
 test_eval!(or_1byte:   "or(5C)"      => Ok(vec![ 0x5C ]));
 test_eval!(or_2bytes:  "or(5C 74)"   => Ok(vec![ 0x7C ]));
+test_eval!(or_empty:   "or()"        => Ok(vec![]));
+test_eval!(or_nested:  "AB or() CD"  => Ok(vec![ 0xAB, 0xCD ]));
 
 // ‘xor’ function
 test_eval!(xor_1byte:  "xor(5C)"     => Ok(vec![ 0x5C ]));
 test_eval!(xor_2bytes: "xor(5C 74)"  => Ok(vec![ 0x28 ]));
+test_eval!(xor_empty:  "xor()"       => Ok(vec![]));
+test_eval!(xor_nested: "AB xor() CD" => Ok(vec![ 0xAB, 0xCD ]));
+
+// folding over more than two operands
+test_eval!(and_3bytes:      "and(7F 3E 1C)"                                => Ok(vec![ 0x1C ]));
+test_eval!(xor_3bytes:      "xor(01 02 04)"                                => Ok(vec![ 0x07 ]));
+test_eval!(and_3be16s:      "and(be16[65280] be16[4080] be16[4095])"       => Ok(vec![ 0x0F, 0x00 ]));
+test_eval!(or_3be16s:       "or(be16[65280] be16[4080] be16[255])"         => Ok(vec![ 0xFF, 0xFF ]));
+test_eval!(and_3bits:       "be16(and([b1111_1111_0000_0000] [b0000_1111_1111_0000] [b0000_1111_1111_1111]))"  => Ok(vec![ 0x0F, 0x00 ]));
+
+// mismatched widths
+test_eval!(and_be16_be32:   "and(be16[1] be32[1])"                         => Err(String::from("Invalid arguments: Values of mismatched widths passed to bitwise function (2 and 4 bytes)")));
+test_eval!(or_3_mismatch:   "or(be16[1] be16[2] be64[3])"                  => Err(String::from("Invalid arguments: Values of mismatched widths passed to bitwise function (2 and 8 bytes)")));
+test_eval!(xor_byte_bits:   "xor(01 [b1_0000_0000])"                       => Err(String::from("Invalid arguments: Values of mismatched widths passed to bitwise function (1 and 2 bytes)")));
+test_eval!(and_raw_number:  "and([256] be16[1])"                           => Err(String::from("Invalid arguments: Arguments of different types passed to bitwise function (unsized number and variable-length byte string)")));
+test_eval!(xor_byte_string: "xor(01 \"A\")"                                 => Err(String::from("Invalid arguments: Arguments of different types passed to bitwise function (byte and variable-length byte string)")));
+test_eval!(and_be16_bits:  "and(be16[1] [b0000_0000_0000_0001])"          => Err(String::from("Invalid arguments: Arguments of different types passed to bitwise function (variable-length byte string and sixteen-bit number)")));
 
 // ‘not’ function
 test_eval!(not_1bype:  "not(5C)"     => Ok(vec![ 0xA3 ]));
+
+// ‘nibbleswap’ function
+test_eval!(nibbleswap_byte:     "nibbleswap(5C)"          => Ok(vec![ 0xC5 ]));
+test_eval!(nibbleswap_bytes:    "nibbleswap(12 34)"       => Ok(vec![ 0x21, 0x43 ]));
+test_eval!(nibbleswap_group:    "nibbleswap(AB (CD EF))"  => Ok(vec![ 0xBA, 0xDC, 0xFE ]));
+test_eval!(nibbleswap_be16:     "nibbleswap(be16[4660])"  => Ok(vec![ 0x21, 0x43 ]));
+test_eval!(nibbleswap_twice:    "nibbleswap(nibbleswap(12 34))" => Ok(vec![ 0x12, 0x34 ]));
+test_eval!(nibbleswap_none:     "nibbleswap()"            => Ok(vec![]));
+
+// ‘neg’ function
+test_eval!(neg_byte:     "neg(05)"                                                 => Ok(vec![ 0xFB ]));
+test_eval!(neg_zero:     "neg(00)"                                                 => Ok(vec![ 0x00 ]));
+test_eval!(neg_80:       "neg(80)"                                                 => Ok(vec![ 0x80 ]));
+test_eval!(neg_16:       "be16(neg(DNS_A))"                                        => Ok(vec![ 0xFF, 0xFF ]));
+test_eval!(neg_16_zero:  "be16(neg([b0000_0000_0000_0000]))"                       => Ok(vec![ 0x00, 0x00 ]));
+test_eval!(neg_32:       "be32(neg([b0000_0000_0000_0000_0000_0000_0000_0010]))"   => Ok(vec![ 0xFF, 0xFF, 0xFF, 0xFE ]));
+#[cfg(feature = "std")]
+test_eval!(neg_32_ts:    "le32(neg([1970-01-01T00:00:01]))"                        => Ok(vec![ 0xFF, 0xFF, 0xFF, 0xFF ]));
+test_eval!(neg_64:       "be64(neg([b1_0000_0000_0000_0000_0000_0000_0000_0011]))" => Ok(vec![ 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFD ]));
+test_eval!(neg_variable: "neg(\"AB\")"                                             => Err(String::from("Invalid arguments: Function ‘neg’ needs a value with a fixed width, not a value of type variable-length byte string")));
+test_eval!(neg_raw:      "neg([5])"                                                => Err(String::from("Invalid arguments: Function ‘neg’ needs a value with a fixed width, not a value of type unsized number")));
+
+// ‘popcount’ function
+test_eval!(popcount_0:      "popcount(00)"         => Ok(vec![ 0x00 ]));
+test_eval!(popcount_1byte:  "popcount(5C)"         => Ok(vec![ 0x04 ]));
+test_eval!(popcount_2bytes: "popcount(FF FF)"      => Ok(vec![ 0x10 ]));
+test_eval!(popcount_string: "popcount(\"AB\")"     => Ok(vec![ 0x04 ]));
+test_eval!(popcount_max:    "popcount(x31(FF) FE)" => Ok(vec![ 0xFF ]));
+test_eval!(popcount_over:   "popcount(x32(FF))"    => Err(String::from("Invalid arguments: Population count (256) does not fit in a byte")));
+test_eval!(popcount_over_args: "popcount(x20(FF) x20(FF))" => Err(String::from("Invalid arguments: Population count (320) does not fit in a byte")));
+
+// ‘clz’ and ‘ctz’ functions
+test_eval!(clz_byte:     "clz(1C)"        => Ok(vec![ 3 ]));
+test_eval!(clz_zero:     "clz(00)"        => Ok(vec![ 8 ]));
+test_eval!(clz_16:       "clz(DNS_MX)"    => Ok(vec![ 12 ]));
+test_eval!(ctz_byte:     "ctz(1C)"        => Ok(vec![ 2 ]));
+test_eval!(ctz_16:       "ctz(TCP_URG)"   => Ok(vec![ 5 ]));
+test_eval!(ctz_16_zero:  "ctz([b0000_0000_0000_0000])" => Ok(vec![ 16 ]));
+test_eval!(clz_raw:      "clz([5])"       => Err(String::from("Invalid arguments: Function ‘clz’ needs a value with a fixed width, not a value of type unsized number")));
+
+// ‘gray’ function
+test_eval!(gray_0:       "gray(00)"            => Ok(vec![ 0x00 ]));
+test_eval!(gray_1:       "gray(01)"            => Ok(vec![ 0x01 ]));
+test_eval!(gray_2:       "gray(02)"            => Ok(vec![ 0x03 ]));
+test_eval!(gray_7:       "gray(07)"            => Ok(vec![ 0x04 ]));
+test_eval!(gray_255:     "gray(FF)"            => Ok(vec![ 0x80 ]));
+test_eval!(gray_be16:    "gray(be16[256])"     => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(gray_be16_2:  "gray(be16[43690])"   => Ok(vec![ 0xFF, 0xFF ]));
+test_eval!(gray_16:      "be16(gray(DNS_MX))"  => Ok(vec![ 0x00, 0x08 ]));
+test_eval!(gray_le16:    "gray(le16[1])"       => Ok(vec![ 0x01, 0x00 ]));
+test_eval!(gray_le16_2:  "gray(le16[256])"     => Ok(vec![ 0x80, 0x01 ]));
+test_eval!(gray_grouped: "gray((be16[1000]))"  => Ok(vec![ 0x02, 0x1C ]));
+test_eval!(gray_grouped_le: "gray((le16[1000]))" => Ok(vec![ 0x1C, 0x02 ]));
+test_eval!(gray_group_of_two: "gray((be16[1] 00))" => Err(String::from("Invalid arguments: Function ‘gray’ needs a value with a fixed width, not a value of type variable-length byte string")));
+test_eval!(gray_string:  "gray(\"AB\")"        => Err(String::from("Invalid arguments: Function ‘gray’ needs a value with a fixed width, not a value of type variable-length byte string")));
+test_eval!(gray_raw:     "gray([5])"           => Err(String::from("Invalid arguments: Function ‘gray’ needs a value with a fixed width, not a value of type unsized number")));
+
+// ‘parity’ function
+test_eval!(parity_even:  "parity(03)"          => Ok(vec![ 0x03, 0x00 ]));
+test_eval!(parity_odd:   "parity(07)"          => Ok(vec![ 0x07, 0x01 ]));
+test_eval!(parity_many:  "parity(01 02 04)"    => Ok(vec![ 0x01, 0x02, 0x04, 0x01 ]));
+test_eval!(parity_none:  "parity()"            => Ok(vec![ 0x00 ]));
@@ -14,3 +14,36 @@ test_eval!(pair_spaces: "09  F9" => Ok(vec![ 0x09, 0xF9 ]));
 
 // oh baby, a triple
 test_eval!(triple:      "09F965" => Ok(vec![ 0x09, 0xF9, 0x65 ]));
+
+// files saved on Windows
+#[test]
+fn bom_and_crlf() {
+    let lines = vec![ "\u{FEFF}AB\r", "CD\r" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    assert_eq!(program.run(&constants, None), Ok(vec![ 0xAB, 0xCD ]));
+}
+
+#[test]
+fn bom_on_second_line() {
+    let lines = vec![ "AB", "\u{FEFF}CD" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Stray symbol \"\\u{feff}\"");
+    assert_eq!((errors[0].source_pos().line_number, errors[0].source_pos().column_number), (2, 0));
+}
+
+// underscores separating hex characters
+test_eval!(separated:          "DE_AD_BE_EF"   => Ok(vec![ 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(separated_lower:    "de_ad be_ef"   => Ok(vec![ 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(separated_nibbles:  "D_E_A_D"       => Ok(vec![ 0xDE, 0xAD ]));
+test_eval!(separated_trailing: "DEAD_"         => Ok(vec![ 0xDE, 0xAD ]));
+test_eval!(separated_constant: "DE_AD IP_UDP"  => Ok(vec![ 0xDE, 0xAD, 0x11 ]));
+
+#[test]
+fn separated_unpaired() {
+    let lines = vec![ "00 AB_C" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Unpaired hex character \"C\"");
+    assert_eq!(errors[0].source_pos().column_number, 6);
+}
@@ -0,0 +1,94 @@
+use hexit_lang::constants::Table;
+use hexit_lang::Program;
+
+// the same bytes as running the program
+#[test]
+fn same_as_run() {
+    let lines = vec![ "AB x3(CD) be16[258] \"hi\" ()" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let bytes = program.bytes_iter(&constants, None, None).collect::<Result<Vec<_>, _>>();
+    assert_eq!(bytes, program.run_ref(&constants, None, None));
+}
+
+#[test]
+fn through_adapters() {
+    let lines = vec![ "01 02 03", "x4(10)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let checksum = program.bytes_iter(&constants, None, None)
+                          .map(|byte| byte.expect("Running failed"))
+                          .filter(|byte| byte % 2 == 0)
+                          .map(u32::from)
+                          .sum::<u32>();
+    assert_eq!(checksum, 0x02 + 0x10 * 4);
+
+    let skipped = program.bytes_iter(&constants, None, None)
+                         .skip(2)
+                         .step_by(2)
+                         .collect::<Result<Vec<_>, _>>();
+    assert_eq!(skipped, Ok(vec![ 0x03, 0x10, 0x10 ]));
+}
+
+#[test]
+fn empty_program() {
+    let lines = vec![ "# nothing" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let mut iter = program.bytes_iter(&constants, None, None);
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert_eq!(iter.next(), None);
+}
+
+// expressions only get evaluated once they are reached, so the bytes before
+// an error still come out
+#[test]
+fn lazy_until_error() {
+    let lines = vec![ "AB CD", "NOT_A_CONSTANT", "EF" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let first_two = program.bytes_iter(&constants, None, None).take(2).collect::<Result<Vec<_>, _>>();
+    assert_eq!(first_two, Ok(vec![ 0xAB, 0xCD ]));
+
+    let mut iter = program.bytes_iter(&constants, None, None);
+    assert_eq!(iter.next(), Some(Ok(0xAB)));
+    assert_eq!(iter.next(), Some(Ok(0xCD)));
+
+    let error = iter.next().expect("No error").expect_err("No error");
+    assert_eq!(error.to_string(), "Unknown constant ‘NOT_A_CONSTANT’");
+    assert_eq!(error.source_pos().line_number, 2);
+
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+}
+
+#[test]
+fn limit_per_expression() {
+    let lines = vec![ "01 zero([5000])" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let items = program.bytes_iter(&constants, Some(1000), None)
+                       .map(|item| item.map_err(|e| e.to_string()))
+                       .collect::<Vec<_>>();
+    assert_eq!(items, vec![ Ok(0x01), Err(String::from("Too much output!")) ]);
+}
+
+#[test]
+fn size_hint_counts_buffered_bytes() {
+    let lines = vec![ "x4(AA)", "BB" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let mut iter = program.bytes_iter(&constants, None, None);
+    assert_eq!(iter.size_hint(), (0, None));
+    iter.next();
+    assert_eq!(iter.size_hint(), (3, None));
+    iter.nth(2);
+    iter.next();
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+}
@@ -1,2 +1,70 @@
 test_eval!(bgp_open:  "BGP_OPEN"  => Ok(vec![ 0x01 ]));
 test_eval!(bgp_close: "BGP_CLOSE" => Err(String::from("Unknown constant ‘BGP_CLOSE’")));
+
+// 16-bit constants keep their width, even when their value fits in a byte
+test_eval!(dns_in_bare:     "DNS_IN"          => Err(String::from("16-bit constant ‘DNS_IN’ without an endianness")));
+test_eval!(dns_in_group:    "(01 DNS_IN)"     => Err(String::from("16-bit constant ‘DNS_IN’ without an endianness")));
+test_eval!(dns_in_repeat:   "x2(DNS_IN)"      => Err(String::from("16-bit constant ‘DNS_IN’ without an endianness")));
+test_eval!(dns_in_be16:     "be16(DNS_IN)"    => Ok(vec![ 0x00, 0x01 ]));
+test_eval!(dns_in_le16:     "le16(DNS_IN)"    => Ok(vec![ 0x01, 0x00 ]));
+test_eval!(dns_uri_bare:    "DNS_URI"         => Err(String::from("16-bit constant ‘DNS_URI’ without an endianness")));
+
+// well-known ports are 16 bits wide, like the fields they go in
+test_eval!(port_https_be16:  "be16(PORT_HTTPS)"  => Ok(vec![ 0x01, 0xBB ]));
+test_eval!(port_https_le16:  "le16(PORT_HTTPS)"  => Ok(vec![ 0xBB, 0x01 ]));
+test_eval!(port_dns_be16:    "be16(PORT_DNS)"    => Ok(vec![ 0x00, 0x35 ]));
+test_eval!(port_https_form:  "be16[PORT_HTTPS]"  => Ok(vec![ 0x01, 0xBB ]));
+test_eval!(port_dns_form:    "le16[port.DNS]"    => Ok(vec![ 0x35, 0x00 ]));
+test_eval!(port_http_bare:   "PORT_HTTP"         => Err(String::from("16-bit constant ‘PORT_HTTP’ without an endianness")));
+
+// file signatures are written out in the order they appear in the file
+test_eval!(elf_magic_be32:   "be32(ELF_MAGIC)"                     => Ok(vec![ 0x7F, b'E', b'L', b'F' ]));
+test_eval!(elf_magic_le32:   "le32(ELF_MAGIC)"                     => Ok(vec![ b'F', b'L', b'E', 0x7F ]));
+test_eval!(png_magic:        "be32(PNG_MAGIC) be32(PNG_MAGIC_END)"  => Ok(vec![ 0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A ]));
+test_eval!(gzip_magic:       "GZIP_ID1 GZIP_ID2"                   => Ok(vec![ 0x1F, 0x8B ]));
+test_eval!(mz_magic:         "be16(MZ_MAGIC)"                      => Ok(vec![ b'M', b'Z' ]));
+test_eval!(pe_magic:         "be32(PE_MAGIC)"                      => Ok(vec![ b'P', b'E', 0x00, 0x00 ]));
+test_eval!(elf_magic_bare:   "ELF_MAGIC"                           => Err(String::from("32-bit constant ‘ELF_MAGIC’ without an endianness")));
+test_eval!(elf_magic_be16:   "be16(ELF_MAGIC)"                     => Err(String::from("4-byte number ‘2135247942’ cannot be narrowed to 2 bytes (Hexit never narrows values)")));
+
+// constants can be namespaced with a dot instead of an underscore
+test_eval!(namespaced_be16:      "be16(dns.A) be16(DNS_A)"  => Ok(vec![ 0x00, 0x01, 0x00, 0x01 ]));
+test_eval!(namespaced_byte:      "ip.UDP IP_UDP"            => Ok(vec![ 0x11, 0x11 ]));
+test_eval!(namespaced_digits:    "gzip.ID1 gzip.ID2"        => Ok(vec![ 0x1F, 0x8B ]));
+test_eval!(namespaced_bare:      "dns.A"                    => Err(String::from("16-bit constant ‘dns.A’ without an endianness")));
+test_eval!(namespaced_unknown:   "dns.NOPE"                 => Err(String::from("Unknown constant ‘dns.NOPE’")));
+
+// locating unknown constants
+#[test]
+fn unknown_constant_position() {
+    let lines = vec![ "AB", "CD  BGP_CLOSE" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let error = program.run_located(&constants, None).unwrap_err();
+    assert_eq!(error.source_pos().line_number, 2);
+    assert_eq!(error.source_pos().column_number, 4);
+}
+
+#[test]
+fn unknown_constant_position_in_function() {
+    let lines = vec![ "x2(AB be16(BGP_CLOSE))" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let error = program.run_located(&constants, None).unwrap_err();
+    assert_eq!(error.source_pos().contents, "BGP_CLOSE");
+    assert_eq!(error.source_pos().column_number, 11);
+}
+
+// other errors point at the top-level expression
+#[test]
+fn top_level_error_position() {
+    let lines = vec![ "AB  x2([256])" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let error = program.run_located(&constants, None).unwrap_err();
+    assert_eq!(error.source_pos().contents, "x2");
+    assert_eq!(error.source_pos().column_number, 4);
+}
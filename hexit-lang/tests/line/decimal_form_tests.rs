@@ -2,8 +2,8 @@
 test_eval!(top_0:    "[0]"    => Ok(vec![ 0 ]));
 test_eval!(top_50:   "[50]"   => Ok(vec![ 50 ]));
 test_eval!(top_255:  "[255]"  => Ok(vec![ 255 ]));
-test_eval!(top_256:  "[256]"  => Err(String::from("Decimal number ‘256’ at top level")));
-test_eval!(top_500:  "[500]"  => Err(String::from("Decimal number ‘500’ at top level")));
+test_eval!(top_256:  "[256]"  => Err(String::from("Decimal number ‘256’ without an endianness")));
+test_eval!(top_500:  "[500]"  => Err(String::from("Decimal number ‘500’ without an endianness")));
 
 // be16 function
 test_eval!(be16_0:   "be16[0]"      => Ok(vec![ 0, 0 ]));
@@ -52,3 +52,19 @@ test_eval!(le64_255: "le64[255]"                   => Ok(vec![ 255, 0, 0, 0, 0,
 test_eval!(le64_256: "le64[256]"                   => Ok(vec![ 0, 1, 0, 0, 0, 0, 0, 0 ]));
 test_eval!(le64_all: "le64[18446744073709551615]"  => Ok(vec![ 255, 255, 255, 255, 255, 255, 255, 255 ]));
 test_eval!(le64_err: "le64[18446744073709551616]"  => Err(String::from("Decimal number ‘18446744073709551616’ is too big for target")));
+
+// aliases
+test_eval!(u16be_256: "u16be[256]"  => Ok(vec![ 1, 0 ]));
+test_eval!(u32le_256: "u32le[256]"  => Ok(vec![ 0, 1, 0, 0 ]));
+test_eval!(wordbe_256:  "wordbe[256]"   => Ok(vec![ 1, 0 ]));
+test_eval!(dwordle_256: "dwordle[256]"  => Ok(vec![ 0, 1, 0, 0 ]));
+test_eval!(qwordbe_256: "qwordbe[256]"  => Ok(vec![ 0, 0, 0, 0, 0, 0, 1, 0 ]));
+
+// widening and narrowing
+test_eval!(be32_widen_16: "be32[b0000_0001_0000_0010]"  => Ok(vec![ 0, 0, 1, 2 ]));
+test_eval!(le64_widen_16: "le64[b0000_0001_0000_0010]"  => Ok(vec![ 2, 1, 0, 0, 0, 0, 0, 0 ]));
+test_eval!(le32_widen_32: "le32[b0000_0001_0000_0000_0000_0000_0000_0010]"  => Ok(vec![ 2, 0, 0, 1 ]));
+test_eval!(be16_narrow_32: "be16[b0000_0001_0000_0000_0000_0000_0000_0010]"  => Err(String::from("4-byte number ‘16777218’ cannot be narrowed to 2 bytes (Hexit never narrows values)")));
+test_eval!(le16_varbytes:  "le16(\"ab\")"  => Err(String::from("Invalid arguments: Cannot convert variable bytes into 2 bytes")));
+test_eval!(be32_narrow_64: "be32[b1_0000_0000_0000_0000_0000_0000_0000_0000]"  => Err(String::from("8-byte number ‘4294967296’ cannot be narrowed to 4 bytes (Hexit never narrows values)")));
+test_eval!(le16_narrow_64: "le16[b1_0000_0000_0000_0000_0000_0000_0000_0000]"  => Err(String::from("8-byte number ‘4294967296’ cannot be narrowed to 2 bytes (Hexit never narrows values)")));
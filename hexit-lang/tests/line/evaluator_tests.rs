@@ -0,0 +1,90 @@
+use hexit_lang::{Evaluator, Placed, Program};
+use hexit_lang::constants::Table;
+
+// running the same program more than once
+#[test]
+fn same_program_twice() {
+    let lines = vec![ "AB x2(CD) \"ef\" be16(DNS_A) [b0101]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+    let evaluator = Evaluator::new(&constants, Some(1000), None);
+
+    let expected = vec![ 0xAB, 0xCD, 0xCD, b'e', b'f', 0x00, 0x01, 0x05 ];
+    assert_eq!(evaluator.evaluate(&program), Ok(expected.clone()));
+    assert_eq!(evaluator.evaluate(&program), Ok(expected));
+}
+
+#[test]
+fn same_error_twice() {
+    let lines = vec![ "AB x20(CD)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+    let evaluator = Evaluator::new(&constants, None, Some(10));
+
+    for _ in 0 .. 2 {
+        let error = evaluator.evaluate(&program).unwrap_err();
+        assert_eq!(error.to_string(), "Too much output from one expression (limit is 10 bytes)!");
+        assert_eq!(error.source_pos(), &Placed { contents: "x20", line_number: 1, column_number: 3 });
+    }
+}
+
+#[test]
+fn spans_twice() {
+    let lines = vec![ "be16[1]  \"hi\"" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+    let evaluator = Evaluator::new(&constants, None, None);
+
+    assert_eq!(evaluator.evaluate_with_spans(&program),
+               evaluator.evaluate_with_spans(&program));
+}
+
+// running many programs with one evaluator
+#[test]
+fn many_programs() {
+    let constants = Table::builtin_set();
+    let evaluator = Evaluator::new(&constants, None, None);
+
+    for (line, expected) in &[ ("IP_UDP", vec![ 17 ]), ("x3(00)", vec![ 0; 3 ]), ("\"ok\"", vec![ b'o', b'k' ]) ] {
+        let lines = [ *line ];
+        let program = Program::read(&lines).expect("Parsing failed");
+        assert_eq!(evaluator.evaluate(&program).as_ref(), Ok(expected));
+    }
+}
+
+// running a program by reference
+#[test]
+fn run_ref_twice() {
+    let lines = vec![ "AB x2(CD) IP_UDP" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    let first = program.run_ref(&constants, None, None);
+    assert_eq!(first, Ok(vec![ 0xAB, 0xCD, 0xCD, 17 ]));
+    assert_eq!(program.run_ref(&constants, None, None), first);
+}
+
+#[test]
+fn run_ref_with_different_limits() {
+    let lines = vec![ "x5(AB)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    assert_eq!(program.run_ref(&constants, None, None), Ok(vec![ 0xAB; 5 ]));
+    assert_eq!(program.run_ref(&constants, Some(5), None).map_err(|e| e.to_string()),
+               Err(String::from("Too much output!")));
+}
+
+#[test]
+fn run_ref_with_different_tables() {
+    let lines = vec![ "MY_TYPE" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    let mut first_table = Table::empty();
+    first_table.insert("MY_TYPE", hexit_lang::constants::Constant::Eight(1));
+    let mut second_table = Table::empty();
+    second_table.insert("MY_TYPE", hexit_lang::constants::Constant::Eight(2));
+
+    assert_eq!(program.run_ref(&first_table, None, None), Ok(vec![ 1 ]));
+    assert_eq!(program.run_ref(&second_table, None, None), Ok(vec![ 2 ]));
+}
@@ -0,0 +1,67 @@
+// Q-format numbers are the decimal multiplied by 2 to the power of the
+// number of fractional bits, stored as a signed two’s complement number.
+
+
+// ---- q8_8 ----
+
+test_eval!(q8_8_one_and_a_half:     "q8_8[1.5]"          => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q8_8be_one_and_a_half:   "q8_8be[1.5]"        => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q8_8le_one_and_a_half:   "q8_8le[1.5]"        => Ok(vec![ 0x80, 0x01 ]));
+test_eval!(q8_8_prefixed_float:     "q8_8[f1.5]"         => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q8_8_whole_number:       "q8_8[3]"            => Ok(vec![ 0x03, 0x00 ]));
+test_eval!(q8_8_zero:               "q8_8[0]"            => Ok(vec![ 0x00, 0x00 ]));
+test_eval!(q8_8_negative:           "q8_8[f-1.5]"        => Ok(vec![ 0xFE, 0x80 ]));
+test_eval!(q8_8_largest:            "q8_8[127.99609375]" => Ok(vec![ 0x7F, 0xFF ]));
+test_eval!(q8_8_smallest:           "q8_8[f-128]"        => Ok(vec![ 0x80, 0x00 ]));
+
+// plain decimals work with either spelling of the argument, and can be
+// negative without needing the ‘f’ prefix
+test_eval!(q8_8_parenthesised:      "q8_8([1.5])"        => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q8_8_negative_plain:     "q8_8[-1.5]"         => Ok(vec![ 0xFE, 0x80 ]));
+test_eval!(q8_8_negative_parenthesised: "q8_8([-1.5])"   => Ok(vec![ 0xFE, 0x80 ]));
+test_eval!(q8_8_negative_whole:     "q8_8[-128]"         => Ok(vec![ 0x80, 0x00 ]));
+
+// fractions that cannot be represented get rounded, with halves rounded
+// away from zero
+test_eval!(q8_8_inexact:            "q8_8[0.1]"          => Ok(vec![ 0x00, 0x1A ]));
+test_eval!(q8_8_half_up:            "q8_8[0.001953125]"  => Ok(vec![ 0x00, 0x01 ]));
+test_eval!(q8_8_half_down:          "q8_8[f-0.001953125]" => Ok(vec![ 0xFF, 0xFF ]));
+test_eval!(q8_8_below_half:         "q8_8[0.0019]"       => Ok(vec![ 0x00, 0x00 ]));
+
+test_eval!(q8_8_too_big:            "q8_8[128]"          => Err(String::from("Number ‘128’ does not fit in Q8.8 fixed point")));
+test_eval!(q8_8_too_small:          "q8_8[f-128.01]"     => Err(String::from("Number ‘-128.01’ does not fit in Q8.8 fixed point")));
+test_eval!(q8_8_rounds_too_big:     "q8_8[127.999]"      => Err(String::from("Number ‘127.999’ does not fit in Q8.8 fixed point")));
+test_eval!(q8_8_byte:               "q8_8(AB)"           => Err(String::from("Invalid arguments: Fixed-point functions need a decimal number, not a value of type byte")));
+test_eval!(q8_8_exponent:           "q8_8[f1e2]"         => Err(String::from("Invalid arguments: Fixed-point functions need a decimal number, not a value of type unsized float")));
+
+
+// ---- q16_16 ----
+
+test_eval!(q16_16_one_and_a_half:   "q16_16[1.5]"        => Ok(vec![ 0x00, 0x01, 0x80, 0x00 ]));
+test_eval!(q16_16le_mixed:          "q16_16le[3.25]"     => Ok(vec![ 0x00, 0x40, 0x03, 0x00 ]));
+test_eval!(q16_16_negative_one:     "q16_16[f-1]"        => Ok(vec![ 0xFF, 0xFF, 0x00, 0x00 ]));
+test_eval!(q16_16_pi:               "q16_16[3.14159265]" => Ok(vec![ 0x00, 0x03, 0x24, 0x3F ]));
+test_eval!(q16_16_largest:          "q16_16[32767]"      => Ok(vec![ 0x7F, 0xFF, 0x00, 0x00 ]));
+
+test_eval!(q16_16_too_big:          "q16_16[32768]"      => Err(String::from("Number ‘32768’ does not fit in Q16.16 fixed point")));
+test_eval!(q8_8_trailing_zeros:      "q8_8[1.500000000000000000000000000000000]" => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q8_8_many_digits_up:     "q8_8[0.00195312500000000000000000000000000001]" => Ok(vec![ 0x00, 0x01 ]));
+test_eval!(q8_8_many_digits_down:   "q8_8[0.00195312499999999999999999999999999999]" => Ok(vec![ 0x00, 0x00 ]));
+test_eval!(q8_8_leading_zeros:      "q8_8[000000000000000000000000000000001.5]" => Ok(vec![ 0x01, 0x80 ]));
+test_eval!(q16_16_many_digits:      "q16_16[1234567890123456789012345678901]" => Err(String::from("Number ‘1234567890123456789012345678901’ does not fit in Q16.16 fixed point")));
+
+
+// plain decimals are only forms in the arguments to a fixed-point function
+#[test]
+fn decimal_elsewhere() {
+    let lines = vec![ "be16[1.5]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"1.5\"");
+}
+
+#[test]
+fn decimal_in_other_function() {
+    let lines = vec![ "q8_8(x1([1.5]))" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"1.5\"");
+}
@@ -2,5 +2,107 @@ test_eval!(localhost: "[127.0.0.1]"            => Ok(vec![ 127, 0, 0, 1 ]));
 test_eval!(broadcast: "[255.255.255.255]"      => Ok(vec![ 255, 255, 255, 255 ]));
 test_eval!(ipv6:      "[::1]"                  => Ok(vec![ 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1 ]));
 
+// timestamps need the standard library
+#[cfg(feature = "std")]
 test_eval!(le32_timestamp: "le32[2017-12-31T21:36:45]" => Ok(vec![ 0x6D, 0x58, 0x49, 0x5A ]));
+#[cfg(feature = "std")]
 test_eval!(be32_timestamp: "be32[2017-12-31T21:36:45]" => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+
+// timestamps with timezone offsets, all of which are the same time in UTC
+#[cfg(feature = "std")]
+test_eval!(be32_timestamp_ahead:  "be32[2017-12-31T23:36:45+02:00]" => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+#[cfg(feature = "std")]
+test_eval!(be32_timestamp_behind: "be32[2017-12-31T16:06:45-05:30]" => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+#[cfg(feature = "std")]
+test_eval!(be32_timestamp_zero:   "be32[2017-12-31T21:36:45+00:00]" => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+#[cfg(feature = "std")]
+test_eval!(be32_timestamp_date:   "be32[2018-01-01T01:36:45+04:00]" => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+
+#[test]
+#[cfg(feature = "std")]
+fn timestamp_offset_out_of_range() {
+    let lines = vec![ "be32[2017-12-31T21:36:45+24:00]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"2017-12-31T21:36:45+24:00\"");
+}
+
+// timestamps in milliseconds and nanoseconds
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ms:        "be64[ms:2017-12-31T21:36:45]"        => Ok(vec![ 0x00, 0x00, 0x01, 0x60, 0xAE, 0x81, 0x69, 0xC8 ]));
+#[cfg(feature = "std")]
+test_eval!(le64_timestamp_ms:        "le64[ms:2017-12-31T21:36:45]"        => Ok(vec![ 0xC8, 0x69, 0x81, 0xAE, 0x60, 0x01, 0x00, 0x00 ]));
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ms_ahead:  "be64[ms:2017-12-31T23:36:45+02:00]"  => Ok(vec![ 0x00, 0x00, 0x01, 0x60, 0xAE, 0x81, 0x69, 0xC8 ]));
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ns:        "be64[ns:2017-12-31T21:36:45]"        => Ok(vec![ 0x15, 0x05, 0x7E, 0xBE, 0x30, 0x58, 0x02, 0x00 ]));
+#[cfg(feature = "std")]
+test_eval!(le64_timestamp_ns:        "le64[ns:2017-12-31T21:36:45]"        => Ok(vec![ 0x00, 0x02, 0x58, 0x30, 0xBE, 0x7E, 0x05, 0x15 ]));
+
+// timestamps with fractions of a second
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ms_fraction:  "be64[ms:2017-12-31T21:36:45.250]"        => Ok(vec![ 0x00, 0x00, 0x01, 0x60, 0xAE, 0x81, 0x6A, 0xC2 ]));
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ms_offset:    "be64[ms:2017-12-31T23:36:45.250+02:00]"  => Ok(vec![ 0x00, 0x00, 0x01, 0x60, 0xAE, 0x81, 0x6A, 0xC2 ]));
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_ns_fraction:  "be64[ns:2017-12-31T21:36:45.000000001]"  => Ok(vec![ 0x15, 0x05, 0x7E, 0xBE, 0x30, 0x58, 0x02, 0x01 ]));
+#[cfg(feature = "std")]
+test_eval!(be32_timestamp_no_fraction:  "be32[2017-12-31T21:36:45.000]"           => Ok(vec![ 0x5A, 0x49, 0x58, 0x6D ]));
+
+#[test]
+#[cfg(feature = "std")]
+fn timestamp_fraction_in_seconds() {
+    let lines = vec![ "be32[2017-12-31T21:36:45.250]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"2017-12-31T21:36:45.250\"");
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn timestamp_fraction_in_millis() {
+    let lines = vec![ "be64[ms:2017-12-31T21:36:45.2505]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"ms:2017-12-31T21:36:45.2505\"");
+}
+
+// seconds after 2106 do not fit in 32 bits
+#[cfg(feature = "std")]
+test_eval!(be64_timestamp_far:       "be64[2200-01-01T00:00:00]"           => Ok(vec![ 0x00, 0x00, 0x00, 0x01, 0xB0, 0x9E, 0x19, 0x00 ]));
+
+#[test]
+#[cfg(feature = "std")]
+fn timestamp_ns_overflow() {
+    let lines = vec![ "be64[ns:2600-01-01T00:00:00]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"ns:2600-01-01T00:00:00\"");
+}
+
+test_eval!(typed_u16:       "[u16:256]"       => Ok(vec![ 0x01, 0x00 ]));
+test_eval!(typed_u16be:     "[u16be:256]"     => Ok(vec![ 0x01, 0x00 ]));
+test_eval!(typed_u16le:     "[u16le:256]"     => Ok(vec![ 0x00, 0x01 ]));
+test_eval!(typed_i16:       "[i16:-1]"        => Ok(vec![ 0xFF, 0xFF ]));
+test_eval!(typed_i32le:     "[i32le:-2]"      => Ok(vec![ 0xFE, 0xFF, 0xFF, 0xFF ]));
+test_eval!(typed_u64_hex:   "[u64:0xABCD]"    => Ok(vec![ 0, 0, 0, 0, 0, 0, 0xAB, 0xCD ]));
+test_eval!(typed_i8_repeat: "x2([i8:-128])"   => Ok(vec![ 0x80, 0x80 ]));
+
+// percentages scale 0–255, rounding halves up
+test_eval!(percent_0:       "[0%]"            => Ok(vec![ 0x00 ]));
+test_eval!(percent_1:       "[1%]"            => Ok(vec![ 0x03 ]));
+test_eval!(percent_50:      "[50%]"           => Ok(vec![ 0x80 ]));
+test_eval!(percent_99:      "[99%]"           => Ok(vec![ 0xFC ]));
+test_eval!(percent_100:     "[100%]"          => Ok(vec![ 0xFF ]));
+test_eval!(percent_widened: "be16[50%]"       => Err(String::from("Invalid arguments: Cannot convert variable bytes into 2 bytes")));
+
+#[test]
+fn percent_over_100() {
+    let lines = vec![ "[150%]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Percentage in form \"150%\" is over 100%");
+}
+
+// forms can contain brackets, but only known forms are accepted
+#[test]
+fn nested_brackets_rejected() {
+    let lines = vec![ "[[:alpha:]]" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Could not interpret form \"[:alpha:]\"");
+}
@@ -0,0 +1,28 @@
+// grouping bytes together
+test_eval!(group_bytes:     "(AB CD)"           => Ok(vec![ 0xAB, 0xCD ]));
+test_eval!(group_between:   "01 (AB CD) 02"     => Ok(vec![ 0x01, 0xAB, 0xCD, 0x02 ]));
+test_eval!(group_empty:     "01 () 02"          => Ok(vec![ 0x01, 0x02 ]));
+test_eval!(group_nested:    "((AB) (CD EF))"    => Ok(vec![ 0xAB, 0xCD, 0xEF ]));
+test_eval!(group_mixed:     "(\"hi\" be16[1])"  => Ok(vec![ b'h', b'i', 0x00, 0x01 ]));
+
+// groups as function arguments
+test_eval!(group_repeated:  "x2((AB CD) EF)"    => Ok(vec![ 0xAB, 0xCD, 0xEF, 0xAB, 0xCD, 0xEF ]));
+test_eval!(group_xor:       "xor((F0 0F) (FF FF))"  => Ok(vec![ 0x0F, 0xF0 ]));
+
+// groups produce bytes, so numbers in them need a width
+test_eval!(group_big_dec:   "([256])"           => Err(String::from("Decimal number ‘256’ without an endianness")));
+
+#[test]
+fn group_unclosed() {
+    let lines = vec![ "AB (CD" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Unclosed parenthesis");
+    assert_eq!(errors[0].source_pos().column_number, 3);
+}
+
+#[test]
+fn group_stray_close() {
+    let lines = vec![ "(AB))" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Stray character \")\"");
+}
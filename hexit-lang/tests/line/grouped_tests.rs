@@ -0,0 +1,52 @@
+use std::num::NonZeroUsize;
+
+fn run_grouped(line: &str, group_size: usize) -> Vec<Vec<u8>> {
+    let lines = vec![ line ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let group_size = NonZeroUsize::new(group_size).unwrap();
+    program.to_bytes_grouped(&constants, None, None, group_size).expect("Running failed")
+}
+
+// groups that divide the output evenly
+#[test]
+fn even_groups() {
+    assert_eq!(run_grouped("01 02 03 04 05 06", 2),
+               vec![ vec![ 0x01, 0x02 ], vec![ 0x03, 0x04 ], vec![ 0x05, 0x06 ] ]);
+}
+
+#[test]
+fn one_byte_groups() {
+    assert_eq!(run_grouped("be16[258]", 1),
+               vec![ vec![ 0x01 ], vec![ 0x02 ] ]);
+}
+
+// groups that leave a short one at the end
+#[test]
+fn short_last_group() {
+    assert_eq!(run_grouped("01 02 03 04 05", 4),
+               vec![ vec![ 0x01, 0x02, 0x03, 0x04 ], vec![ 0x05 ] ]);
+}
+
+#[test]
+fn group_larger_than_output() {
+    assert_eq!(run_grouped("\"hi\"", 16),
+               vec![ vec![ b'h', b'i' ] ]);
+}
+
+#[test]
+fn no_output() {
+    assert_eq!(run_grouped("()", 4),
+               Vec::<Vec<u8>>::new());
+}
+
+#[test]
+fn grouped_over_limit() {
+    let lines = vec![ "zero([5000])" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let result = program.to_bytes_grouped(&constants, Some(1000), None, NonZeroUsize::new(8).unwrap()).map_err(|e| e.to_string());
+    assert_eq!(result, Err(String::from("Too much output!")));
+}
@@ -0,0 +1,82 @@
+use hexit_lang::ast::{Exp, FunctionName, MultiByteType};
+use hexit_lang::constants::Table;
+use hexit_lang::{EvalEvent, Program};
+
+// counting the functions run by a nested program
+#[test]
+fn count_functions() {
+    let lines = vec![ "x2(be16[1] x3(not(AB)))", "CD" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    let mut functions = Vec::new();
+    let bytes = program.run_with_hook(&Table::builtin_set(), None, None, &mut |event| {
+        if let EvalEvent::Function { name, .. } = event {
+            functions.push(name);
+        }
+    });
+
+    assert_eq!(bytes.map(|b| b.len()), Ok(11));
+    assert_eq!(functions.len(), 4);
+    assert_eq!(functions[0], FunctionName::Repeat(2));
+    assert_eq!(functions[1], FunctionName::MultiByte(MultiByteType::Be16));
+}
+
+#[test]
+fn every_expression() {
+    let lines = vec![ "AB (CD EF) x2(01)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    let mut count = 0;
+    program.run_with_hook(&Table::builtin_set(), None, None, &mut |event| {
+        if let EvalEvent::Expression(_) = event {
+            count += 1;
+        }
+    }).expect("Running failed");
+
+    // the byte, the group and its two bytes, and the function and its byte
+    assert_eq!(count, 6);
+}
+
+#[test]
+fn output_ranges() {
+    let lines = vec![ "AB", "be32[1] ()", "\"hey\"" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    let mut outputs = Vec::new();
+    program.run_with_hook(&Table::builtin_set(), None, None, &mut |event| {
+        if let EvalEvent::Output { position, range } = event {
+            outputs.push((position.line_number, range));
+        }
+    }).expect("Running failed");
+
+    assert_eq!(outputs, vec![ (1, 0 .. 1), (2, 1 .. 5), (2, 5 .. 5), (3, 5 .. 8) ]);
+}
+
+#[test]
+fn events_in_order() {
+    let lines = vec![ "not(AB)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    let mut events = Vec::new();
+    program.run_with_hook(&Table::builtin_set(), None, None, &mut |event| {
+        events.push(match event {
+            EvalEvent::Expression(Exp::Char(_))  => "byte",
+            EvalEvent::Expression(_)             => "expression",
+            EvalEvent::Function { .. }           => "function",
+            EvalEvent::Output { .. }             => "output",
+        });
+    }).expect("Running failed");
+
+    assert_eq!(events, vec![ "expression", "function", "byte", "output" ]);
+}
+
+// the same bytes come out as without a hook
+#[test]
+fn same_as_run() {
+    let lines = vec![ "x3(AB be16[258])", "zero[4]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    assert_eq!(program.run_with_hook(&constants, None, None, &mut |_| {}),
+               program.run_ref(&constants, None, None));
+}
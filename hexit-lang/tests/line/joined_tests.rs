@@ -0,0 +1,37 @@
+// repeating with a separator
+test_eval!(joined_1:         "joined([1] 00 AB)"         => Ok(vec![ 0xAB ]));
+test_eval!(joined_3:         "joined([3] 00 AB)"         => Ok(vec![ 0xAB, 0x00, 0xAB, 0x00, 0xAB ]));
+test_eval!(joined_hex:       "joined(02 00 AB CD)"       => Ok(vec![ 0xAB, 0xCD, 0x00, 0xAB, 0xCD ]));
+test_eval!(joined_const:     "joined(IP_UDP 00 AB)"      => Ok([ 0xAB, 0x00 ].repeat(17)[.. 33].to_vec()));
+test_eval!(joined_string:    "joined([3] \", \" \"a\")"  => Ok(b"a, a, a".to_vec()));
+test_eval!(joined_group:     "joined([2] (0D 0A) AB)"    => Ok(vec![ 0xAB, 0x0D, 0x0A, 0xAB ]));
+test_eval!(joined_no_bytes:  "joined([3] 00)"            => Ok(vec![ 0x00, 0x00 ]));
+test_eval!(joined_nested:    "joined([2] 00 x2(AB))"     => Ok(vec![ 0xAB, 0xAB, 0x00, 0xAB, 0xAB ]));
+
+// errors
+test_eval!(joined_zero:      "joined([0] 00 AB)"         => Err(String::from("Invalid arguments: Repeat function given an amount of zero")));
+test_eval!(joined_big:       "joined([65536] 00 AB)"     => Err(String::from("Invalid arguments: Repeat amount 65536 is too large")));
+test_eval!(joined_float:     "joined([f1.5] 00 AB)"      => Err(String::from("Invalid arguments: Cannot use a value of type unsized float as an amount")));
+test_eval!(joined_one_arg:   "joined([3])"               => Err(String::from("Invalid arguments: Function ‘joined’ needs an amount and a separator")));
+test_eval!(joined_nothing:   "joined()"                  => Err(String::from("Invalid arguments: Function ‘joined’ needs an amount and a separator")));
+
+// the separators count towards the limit
+#[test]
+fn joined_separators_limited() {
+    let lines = vec![ "joined([100] x20(00) AB)" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    assert_eq!(program.run(&constants, Some(1000)).map_err(|e| e.to_string()),
+               Err(String::from("Too much output!")));
+}
+
+#[test]
+fn joined_0_allowed() {
+    let lines = vec![ "CD joined([0] 00 AB) EF" ];
+    let options = hexit_lang::ParseOptions { allow_zero_repeats: true, ..hexit_lang::ParseOptions::default() };
+    let program = hexit_lang::Program::read_with_options(&lines, options).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    assert_eq!(program.run(&constants, None), Ok(vec![ 0xCD, 0xEF ]));
+}
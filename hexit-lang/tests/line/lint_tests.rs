@@ -0,0 +1,55 @@
+use hexit_lang::constants::Table;
+use hexit_lang::Program;
+
+fn lint(lines: &[&str], limit: Option<usize>) -> Vec<(usize, String)> {
+    let program = Program::read(lines).expect("Parsing failed");
+    program.lint(&Table::builtin_set(), limit, None).iter()
+           .map(|w| (w.source_pos().column_number, w.to_string()))
+           .collect()
+}
+
+#[test]
+fn large_repeat() {
+    assert_eq!(lint(&[ "AB x2000(x1000(00))" ], None),
+               vec![ (3, "Repeat will output at least 2000000 bytes".into()) ]);
+}
+
+#[test]
+fn large_repeat_still_runs() {
+    let lines = [ "x2000(x1000(00))" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let constants = Table::builtin_set();
+
+    assert_eq!(program.lint(&constants, Some(4_000_000), None).len(), 1);
+    assert_eq!(program.run(&constants, Some(4_000_000)).map(|bytes| bytes.len()), Ok(2_000_000));
+}
+
+#[test]
+fn large_repeat_in_a_group() {
+    assert_eq!(lint(&[ "(AB x5000(CD EF x300(00)))" ], None),
+               vec![ (4, "Repeat will output at least 1510000 bytes".into()) ]);
+}
+
+#[test]
+fn large_repeat_in_a_function() {
+    assert_eq!(lint(&[ "AB popcount(x2000(x1000(00)))" ], None),
+               vec![ (12, "Repeat will output at least 2000000 bytes".into()) ]);
+}
+
+#[test]
+fn large_repeat_with_a_constant_amount() {
+    assert_eq!(lint(&[ "repeat(IP_UDP x65535(00))" ], None),
+               vec![ (0, "Repeat will output at least 1114095 bytes".into()) ]);
+}
+
+#[test]
+fn small_repeat() {
+    assert_eq!(lint(&[ "x1000(x1000(00))" ], None),
+               vec![]);
+}
+
+#[test]
+fn repeat_over_the_limit() {
+    assert_eq!(lint(&[ "x2000(x1000(00))" ], Some(1_000_000)),
+               vec![]);
+}
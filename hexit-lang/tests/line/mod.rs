@@ -13,12 +13,26 @@ macro_rules! test_eval {
 }
 
 
+mod bcd_tests;
 mod bit_form_tests;
+mod bits_function_tests;
 mod bitwise_function_tests;
 mod byte_tests;
+mod bytes_iter_tests;
 mod constant_tests;
 mod decimal_form_tests;
+mod evaluator_tests;
+mod fixed_point_tests;
 mod float_form_tests;
 mod form_tests;
+mod group_tests;
+mod grouped_tests;
+mod hook_tests;
+mod joined_tests;
+mod lint_tests;
 mod repeat_tests;
+mod span_tests;
 mod string_tests;
+mod value_tests;
+mod varint_tests;
+mod verify_tests;
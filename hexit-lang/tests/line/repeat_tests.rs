@@ -3,8 +3,137 @@ test_eval!(repeat_1:  "x1(AB)"  => Ok(vec![ 0xAB; 1 ]));
 test_eval!(repeat_3:  "x3(AB)"  => Ok(vec![ 0xAB; 3 ]));
 test_eval!(repeat_11: "x11(AB)" => Ok(vec![ 0xAB; 11 ]));
 
+// repeating with an uppercase X
+test_eval!(repeat_X3:  "X3(AB)"  => Ok(vec![ 0xAB; 3 ]));
+test_eval!(repeat_X11: "X11(AB)" => Ok(vec![ 0xAB; 11 ]));
+
 // repeating two bytes
 test_eval!(repeat_22: "x11(AB AB)" => Ok(vec![ 0xAB; 22 ]));
 
 // repeating three bytes
 test_eval!(repeat_33: "x11(AB AB AB)" => Ok(vec![ 0xAB; 33 ]));
+
+// repeating a mixture of values
+test_eval!(repeat_mixed:  "x3(AB \"cd\" be16[1])"  => Ok(vec![ 0xAB, b'c', b'd', 0x00, 0x01, 0xAB, b'c', b'd', 0x00, 0x01, 0xAB, b'c', b'd', 0x00, 0x01 ]));
+test_eval!(repeat_nested: "x2(01 x2(02 x2(03)))"    => Ok(vec![ 0x01, 0x02, 0x03, 0x03, 0x02, 0x03, 0x03, 0x01, 0x02, 0x03, 0x03, 0x02, 0x03, 0x03 ]));
+test_eval!(repeat_large:  "x60000(AB CD)"           => Ok([ 0xAB, 0xCD ].repeat(60000)));
+
+// filling to a length
+test_eval!(fill_5:        "fill([5] AB CD)"     => Ok(vec![ 0xAB, 0xCD, 0xAB, 0xCD, 0xAB ]));
+test_eval!(fill_hex:      "fill(04 AB CD)"      => Ok(vec![ 0xAB, 0xCD, 0xAB, 0xCD ]));
+test_eval!(fill_short:    "fill([1] AB CD)"     => Ok(vec![ 0xAB ]));
+test_eval!(fill_string:   "fill([3] \"-\")"     => Ok(vec![ b'-'; 3 ]));
+test_eval!(fill_big:      "fill([300] 00)"      => Ok(vec![ 0x00; 300 ]));
+test_eval!(fill_zero:     "fill([0] AB)"        => Err(String::from("Invalid arguments: Fill function given a length of zero")));
+test_eval!(fill_empty:    "fill([5])"           => Err(String::from("Invalid arguments: Fill function given no bytes to fill with")));
+test_eval!(fill_nothing:  "fill()"              => Err(String::from("Invalid arguments: No arguments passed to fill function")));
+test_eval!(fill_float:    "fill([f1.5] AB)"     => Err(String::from("Invalid arguments: Cannot use a value of type unsized float as an amount")));
+
+// runs of zeroes and ones
+test_eval!(zero_4:        "zero([4])"           => Ok(vec![ 0x00; 4 ]));
+test_eval!(zero_hex:      "AB zero(02) CD"      => Ok(vec![ 0xAB, 0x00, 0x00, 0xCD ]));
+test_eval!(zero_none:     "AB zero([0]) CD"     => Ok(vec![ 0xAB, 0xCD ]));
+test_eval!(ones_3:        "ones([3])"           => Ok(vec![ 0xFF; 3 ]));
+test_eval!(ones_big:      "ones([300])"         => Ok(vec![ 0xFF; 300 ]));
+test_eval!(ones_const:    "ones(IP_UDP)"        => Ok(vec![ 0xFF; 17 ]));
+test_eval!(zero_two:      "zero([1] [2])"       => Err(String::from("Invalid arguments: Too many arguments (2) passed to function")));
+test_eval!(ones_float:    "ones([f1.5])"        => Err(String::from("Invalid arguments: Cannot use a value of type unsized float as an amount")));
+
+#[test]
+fn zero_over_limit() {
+    let lines = vec![ "zero([5000])" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let result = program.run(&constants, Some(1000)).map_err(|e| e.to_string());
+    assert_eq!(result, Err(String::from("Too much output!")));
+}
+
+#[test]
+fn nested_repeat_over_limit() {
+    let lines = vec![ "AB x60000(x60000(FF))" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let error = program.run_located(&constants, Some(100_000_000)).unwrap_err();
+    assert_eq!(error.to_string(), "Too much output!");
+    assert_eq!(error.source_pos().column_number, 3);
+}
+
+#[test]
+fn repeat_over_line_limit() {
+    let lines = vec![ "AB", "x300(CD)" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let error = program.run_with_line_limit(&constants, Some(1000), Some(200)).unwrap_err();
+    assert_eq!(error.to_string(), "Too much output from one expression (limit is 200 bytes)!");
+    assert_eq!(error.source_pos().line_number, 2);
+}
+
+#[test]
+fn large_output_unchanged() {
+    let lines = vec![ "x1000(AB CD) zero([4000]) fill([3] 01 02)", "x2(x3(EF) \"hi\")" ];
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let mut expected = Vec::new();
+    for _ in 0 .. 1000 {
+        expected.extend(&[ 0xAB, 0xCD ]);
+    }
+    expected.extend(vec![ 0x00; 4000 ]);
+    expected.extend(&[ 0x01, 0x02, 0x01 ]);
+    expected.extend(&[ 0xEF, 0xEF, 0xEF, b'h', b'i', 0xEF, 0xEF, 0xEF, b'h', b'i' ]);
+
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    assert_eq!(program.run(&constants, None), Ok(expected.clone()));
+
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    assert_eq!(program.run(&constants, Some(5000)), Ok(expected));
+}
+
+#[test]
+fn repeat_inside_popcount_over_limit() {
+    let lines = vec![ "popcount(x60000(x60000(FF)))" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let result = program.run(&constants, Some(100_000_000)).map_err(|e| e.to_string());
+    assert_eq!(result, Err(String::from("Too much output!")));
+}
+
+// repeating a computed number of times
+test_eval!(repeat_dyn_dec:      "repeat([3] AB)"        => Ok(vec![ 0xAB; 3 ]));
+test_eval!(repeat_dyn_hex:      "repeat(02 AB CD)"      => Ok(vec![ 0xAB, 0xCD, 0xAB, 0xCD ]));
+test_eval!(repeat_dyn_const:    "repeat(IP_UDP 00)"     => Ok(vec![ 0x00; 17 ]));
+test_eval!(repeat_dyn_be16:     "repeat(be16[2] AB)"    => Err(String::from("Invalid arguments: Cannot use a value of type variable-length byte string as an amount")));
+test_eval!(repeat_dyn_big:      "repeat([65536] AB)"    => Err(String::from("Invalid arguments: Repeat amount 65536 is too large")));
+test_eval!(repeat_dyn_zero:     "repeat([0] AB)"        => Err(String::from("Invalid arguments: Repeat function given an amount of zero")));
+test_eval!(repeat_dyn_nothing:  "repeat()"              => Err(String::from("Invalid arguments: No arguments passed to repeat function")));
+
+// repeating zero times
+#[test]
+fn repeat_0_rejected() {
+    let lines = vec![ "x0(AB)" ];
+    let errors = hexit_lang::Program::read(&*lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Invalid repeat amount \"x0\"");
+}
+
+#[test]
+fn repeat_0_allowed() {
+    let lines = vec![ "CD x0(AB) EF" ];
+    let options = hexit_lang::ParseOptions { allow_zero_repeats: true, ..hexit_lang::ParseOptions::default() };
+    let program = hexit_lang::Program::read_with_options(&*lines, options).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    assert_eq!(program.run(&constants, None), Ok(vec![ 0xCD, 0xEF ]));
+}
+
+#[test]
+fn repeat_dyn_0_allowed() {
+    let lines = vec![ "CD repeat([0] AB) EF" ];
+    let options = hexit_lang::ParseOptions { allow_zero_repeats: true, ..hexit_lang::ParseOptions::default() };
+    let program = hexit_lang::Program::read_with_options(&*lines, options).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    assert_eq!(program.run(&constants, None), Ok(vec![ 0xCD, 0xEF ]));
+}
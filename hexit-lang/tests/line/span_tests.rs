@@ -0,0 +1,30 @@
+use hexit_lang::Placed;
+
+// mapping expressions to the output they produced
+#[test]
+fn two_expressions() {
+    let lines = vec![ "be16[1]  \"hi\"" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let (bytes, spans) = program.run_with_spans(&constants, None, None).expect("Running failed");
+    assert_eq!(bytes, vec![ 0x00, 0x01, b'h', b'i' ]);
+    assert_eq!(spans, vec![
+        (Placed { contents: "be16", line_number: 1, column_number: 0 }, 0 .. 2),
+        (Placed { contents: "hi",   line_number: 1, column_number: 9 }, 2 .. 4),
+    ]);
+}
+
+#[test]
+fn bytes_across_lines() {
+    let lines = vec![ "ABCD", "IP_UDP" ];
+    let program = hexit_lang::Program::read(&lines).expect("Parsing failed");
+    let constants = hexit_lang::constants::Table::builtin_set();
+
+    let (_, spans) = program.run_with_spans(&constants, None, None).expect("Running failed");
+    assert_eq!(spans, vec![
+        (Placed { contents: "AB",     line_number: 1, column_number: 0 }, 0 .. 1),
+        (Placed { contents: "CD",     line_number: 1, column_number: 2 }, 1 .. 2),
+        (Placed { contents: "IP_UDP", line_number: 2, column_number: 0 }, 2 .. 3),
+    ]);
+}
@@ -6,3 +6,112 @@ test_eval!(surround:  "AB\"JSON\"CD"  => Ok(vec![ 0xAB, b'J', b'S', b'O', b'N',
 test_eval!(newline:   "\"hi\\nyo\""   => Ok(vec![ b'h', b'i', b'\n', b'y', b'o' ]));
 test_eval!(rewline:   "\"hi\\ryo\""   => Ok(vec![ b'h', b'i', b'\r', b'y', b'o' ]));
 test_eval!(tab:       "\"hi\\tyo\""   => Ok(vec![ b'h', b'i', b'\t', b'y', b'o' ]));
+
+// other control characters
+test_eval!(bell:      "\"\\a\""       => Ok(vec![ 0x07 ]));
+test_eval!(backspace: "\"\\b\""       => Ok(vec![ 0x08 ]));
+test_eval!(formfeed:  "\"\\f\""       => Ok(vec![ 0x0C ]));
+test_eval!(vtab:      "\"\\v\""       => Ok(vec![ 0x0B ]));
+test_eval!(escape:    "\"\\e[0m\""    => Ok(vec![ 0x1B, b'[', b'0', b'm' ]));
+
+// octal escapes
+test_eval!(octal_a:     "\"\\101\""     => Ok(vec![ b'A' ]));
+test_eval!(octal_nul:   "\"a\\0b\""     => Ok(vec![ b'a', 0x00, b'b' ]));
+test_eval!(octal_high:  "\"\\377\\200\""  => Ok(vec![ 0xFF, 0x80 ]));
+test_eval!(octal_short: "\"\\12x\""     => Ok(vec![ 0x0A, b'x' ]));
+test_eval!(octal_four:  "\"\\1011\""    => Ok(vec![ b'A', b'1' ]));
+
+#[test]
+fn octal_too_big() {
+    let lines = vec![ "\"\\400\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "String contains escape \"\\400\" that is too big for a byte");
+    assert_eq!(errors[0].source_pos().column_number, 1);
+}
+
+// unknown escapes are always rejected, pointing at the escape
+#[test]
+fn unknown_escape() {
+    let lines = vec![ "AB \"ok\\q\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "String contains invalid escape character \"\\q\"");
+    assert_eq!(errors[0].source_pos().column_number, 6);
+}
+
+
+// base32 strings, with the RFC 4648 test vectors
+test_eval!(base32_empty:      "b32\"\""                 => Ok(vec![]));
+test_eval!(base32_one:        "b32\"MY======\""         => Ok(b"f".to_vec()));
+test_eval!(base32_two:        "b32\"MZXQ====\""         => Ok(b"fo".to_vec()));
+test_eval!(base32_three:      "b32\"MZXW6===\""         => Ok(b"foo".to_vec()));
+test_eval!(base32_four:       "b32\"MZXW6YQ=\""         => Ok(b"foob".to_vec()));
+test_eval!(base32_five:       "b32\"MZXW6YTB\""         => Ok(b"fooba".to_vec()));
+test_eval!(base32_six:        "b32\"MZXW6YTBOI======\"" => Ok(b"foobar".to_vec()));
+test_eval!(base32_unpadded:   "b32\"MZXW6YTBOI\""       => Ok(b"foobar".to_vec()));
+test_eval!(base32_lowercase:  "b32\"mzxw6ytboi\""       => Ok(b"foobar".to_vec()));
+test_eval!(base32_totp:       "b32\"JBSWY3DPEHPK3PXP\"" => Ok(vec![ 0x48, 0x65, 0x6C, 0x6C, 0x6F, 0x21, 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(base32_in_args:    "x2(b32\"MY\")"           => Ok(b"ff".to_vec()));
+
+#[test]
+fn base32_invalid_character() {
+    let lines = vec![ "AB b32\"MZ1W\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Base32 string contains invalid character \"1\"");
+    assert_eq!(errors[0].source_pos().column_number, 9);
+}
+
+#[test]
+fn base32_padding_in_middle() {
+    let lines = vec![ "b32\"MY=AMY==\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Base32 string contains invalid character \"=\"");
+    assert_eq!(errors[0].source_pos().column_number, 6);
+}
+
+#[test]
+fn base32_invalid_length() {
+    let lines = vec![ "b32\"MZX\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Base32 string \"MZX\" has an invalid length");
+    assert_eq!(errors[0].source_pos().column_number, 4);
+}
+
+#[test]
+fn base32_short_padding() {
+    let lines = vec![ "b32\"MY==\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Base32 string \"MY==\" has an invalid length");
+}
+
+
+// hex strings, which ignore whitespace between their characters
+test_eval!(hex_string:            "hex\"DEADBEEF\""       => Ok(vec![ 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(hex_string_spaces:     "hex\"DE AD  BE EF\""   => Ok(vec![ 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(hex_string_misaligned: "hex\"D EAD BEE F\""    => Ok(vec![ 0xDE, 0xAD, 0xBE, 0xEF ]));
+test_eval!(hex_string_tabs:       "hex\"\tde\tad\t\""      => Ok(vec![ 0xDE, 0xAD ]));
+test_eval!(hex_string_empty:      "hex\"  \""             => Ok(vec![]));
+test_eval!(hex_string_in_args:    "x2(hex\"AB CD\")"      => Ok(vec![ 0xAB, 0xCD, 0xAB, 0xCD ]));
+
+#[test]
+fn hex_string_over_two_lines() {
+    // each line is read on its own, so a string cannot carry on to the next
+    let lines = vec![ "hex\"DE AD", "BE EF\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Unclosed string \"hex\\\"DE AD\"");
+}
+
+#[test]
+fn hex_string_odd_digits() {
+    let lines = vec![ "hex\"DE AD B\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Hex string \"DE AD B\" has an odd number of hex characters");
+    assert_eq!(errors[0].source_pos().column_number, 4);
+}
+
+#[test]
+fn hex_string_invalid_character() {
+    let lines = vec![ "hex\"DE,AD\"" ];
+    let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+    assert_eq!(errors[0].to_string(), "Hex string contains invalid character \",\"");
+    assert_eq!(errors[0].source_pos().column_number, 6);
+}
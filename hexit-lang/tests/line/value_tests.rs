@@ -0,0 +1,53 @@
+use hexit_lang::constants::Table;
+use hexit_lang::{Program, Value, MultiByteValue, Width, Endianness};
+
+fn run_values<'src>(lines: &'src [&'src str]) -> Vec<Value<'src>> {
+    let program = Program::read(lines).expect("Parsing failed");
+    program.run_values(&Table::builtin_set(), None, None).expect("Running failed")
+}
+
+#[test]
+fn be16() {
+    let values = run_values(&[ "be16[256]" ]);
+    assert_eq!(values, vec![ Value::Ordered(MultiByteValue::Sixteen(256), Endianness::Big) ]);
+    assert_eq!(values[0].type_name(), "variable-length byte string");
+    assert_eq!(values[0].byte_width(), Some(2));
+}
+
+#[test]
+fn one_value_per_expression() {
+    assert_eq!(run_values(&[ "AB [300] [f1.5] \"hi\"" ]),
+               vec![ Value::Byte(0xAB),
+                     Value::RawNumber("300"),
+                     Value::RawFloat("1.5"),
+                     Value::VariableBytes(b"hi".to_vec()) ]);
+}
+
+// a sixteen-bit constant has no endianness, so it could not be turned into
+// bytes, but it can still be inspected
+#[test]
+fn sixteen_bit_constant() {
+    let values = run_values(&[ "PORT_BGP" ]);
+    assert_eq!(values, vec![ Value::MultiByte(MultiByteValue::Sixteen(179)) ]);
+
+    if let Value::MultiByte(mbv) = values[0] {
+        assert_eq!(mbv.width(), Width::Sixteen);
+        assert_eq!(mbv.widened(), 179);
+    }
+}
+
+#[test]
+fn values_into_bytes() {
+    let bytes = run_values(&[ "AB be32[1]" ]).into_iter()
+                    .map(|value| value.eval_to_bytes().expect("Conversion failed"))
+                    .collect::<Vec<_>>();
+    assert_eq!(bytes, vec![ vec![ 0xAB ], vec![ 0x00, 0x00, 0x00, 0x01 ] ]);
+}
+
+#[test]
+fn values_over_limit() {
+    let lines = vec![ "AB x20(CD)" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let result = program.run_values(&Table::builtin_set(), Some(10), None).map_err(|e| e.to_string());
+    assert_eq!(result, Err(String::from("Too much output!")));
+}
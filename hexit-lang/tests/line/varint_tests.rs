@@ -0,0 +1,35 @@
+// Varints are LEB128: seven bits to a byte, least significant first, with
+// the top bit set on every byte but the last. Signed ones get zigzagged
+// first, so that small negative numbers stay small.
+
+
+// ---- unsigned ----
+
+test_eval!(varint_zero:             "varint[0]"             => Ok(vec![ 0x00 ]));
+test_eval!(varint_one_byte_max:     "varint[127]"           => Ok(vec![ 0x7F ]));
+test_eval!(varint_two_bytes_min:    "varint[128]"           => Ok(vec![ 0x80, 0x01 ]));
+test_eval!(varint_300:              "varint[300]"           => Ok(vec![ 0xAC, 0x02 ]));
+test_eval!(uvarint_300:             "uvarint[300]"          => Ok(vec![ 0xAC, 0x02 ]));
+test_eval!(varint_large:            "varint[624485]"        => Ok(vec![ 0xE5, 0x8E, 0x26 ]));
+test_eval!(varint_u64_max:          "varint[18446744073709551615]" => Ok(vec![ 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01 ]));
+test_eval!(varint_byte:             "varint(96)"            => Ok(vec![ 0x96, 0x01 ]));
+test_eval!(varint_typed:            "varint[u16:300]"       => Ok(vec![ 0xAC, 0x02 ]));
+test_eval!(varint_of_be32:          "varint(be32[300])"     => Ok(vec![ 0xAC, 0x02 ]));
+test_eval!(varint_in_group:         "(varint[1] varint[2])" => Ok(vec![ 0x01, 0x02 ]));
+
+test_eval!(varint_too_big:          "varint[18446744073709551616]" => Err(String::from("Decimal number ‘18446744073709551616’ is too big for target")));
+test_eval!(varint_float:            "varint[f1.5]"          => Err(String::from("Invalid arguments: Varint functions need a number of up to eight bytes, not a value of type unsized float")));
+test_eval!(varint_too_long:         "varint(\"too long!\")" => Err(String::from("Invalid arguments: Varint functions need a number of up to eight bytes, not a value of type variable-length byte string")));
+
+
+// ---- signed ----
+
+test_eval!(svarint_zero:            "svarint[0]"            => Ok(vec![ 0x00 ]));
+test_eval!(svarint_positive:        "svarint[1]"            => Ok(vec![ 0x02 ]));
+test_eval!(svarint_minus_one:       "svarint[i32:-1]"       => Ok(vec![ 0x01 ]));
+test_eval!(svarint_minus_two:       "svarint[i16:-2]"       => Ok(vec![ 0x03 ]));
+test_eval!(svarint_minus_65:        "svarint[i64:-65]"      => Ok(vec![ 0x81, 0x01 ]));
+test_eval!(svarint_positive_150:    "svarint[150]"          => Ok(vec![ 0xAC, 0x02 ]));
+test_eval!(svarint_byte_is_signed:  "svarint(FF)"           => Ok(vec![ 0x01 ]));
+test_eval!(svarint_i64_min:         "svarint[i64:-9223372036854775808]" => Ok(vec![ 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01 ]));
+test_eval!(svarint_raw_too_big:     "svarint[9223372036854775808]" => Err(String::from("Decimal number ‘9223372036854775808’ is too big for target")));
@@ -0,0 +1,61 @@
+use hexit_lang::Program;
+use hexit_lang::constants::Table;
+use hexit_lang::verify::{Verification, VerificationError, VerificationKind, ExactRunError};
+
+
+#[test]
+fn output_has_exact_length() {
+    let lines = vec![ "x4(AB) be16[1]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let output = program.run(&Table::builtin_set(), None).expect("Running failed");
+
+    assert_eq!(Verification::ExactLength(6).verify(output.len()), Ok(()));
+    assert_eq!(Verification::ExactLength(4).verify(output.len()),
+               Err(VerificationError { expected: 4, actual: 6, kind: VerificationKind::ExactLength }));
+}
+
+#[test]
+fn output_is_multiple() {
+    let lines = vec![ "x4(AB) be16[1]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+    let output = program.run(&Table::builtin_set(), None).expect("Running failed");
+
+    assert_eq!(Verification::Multiple(3).verify(output.len()), Ok(()));
+    assert_eq!(Verification::Multiple(4).verify(output.len()),
+               Err(VerificationError { expected: 4, actual: 6, kind: VerificationKind::Multiple }));
+}
+
+#[test]
+fn run_exact_match() {
+    let lines = vec![ "x4(AB) be16[1]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    assert_eq!(program.run_exact::<6>(&Table::builtin_set()),
+               Ok([ 0xAB, 0xAB, 0xAB, 0xAB, 0x00, 0x01 ]));
+}
+
+#[test]
+fn run_exact_too_short() {
+    let lines = vec![ "x4(AB) be16[1]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    assert_eq!(program.run_exact::<8>(&Table::builtin_set()),
+               Err(ExactRunError::Length(VerificationError { expected: 8, actual: 6, kind: VerificationKind::ExactLength })));
+}
+
+#[test]
+fn run_exact_too_long() {
+    let lines = vec![ "x4(AB) be16[1]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    assert_eq!(program.run_exact::<4>(&Table::builtin_set()).map_err(|e| e.to_string()),
+               Err("Output is 6 bytes long, but it should be 4".into()));
+}
+
+#[test]
+fn run_exact_error() {
+    let lines = vec![ "AB be16[99999]" ];
+    let program = Program::read(&lines).expect("Parsing failed");
+
+    assert!(matches!(program.run_exact::<2>(&Table::builtin_set()), Err(ExactRunError::Run(_))));
+}
@@ -0,0 +1,125 @@
+//! Parsing numbers of bytes given on the command-line, which can have a
+//! suffix to make large numbers easier to write.
+
+use std::fmt;
+use std::num::ParseIntError;
+
+
+/// Parses a number of bytes, such as `4096`, `4K`, or `4KiB`. The suffixes
+/// `K`, `M`, and `G` count in powers of 1024, as do `KiB`, `MiB`, and `GiB`;
+/// the suffixes `KB`, `MB`, and `GB` count in powers of 1000 instead. A
+/// trailing `B` on its own, as in `512B`, is allowed and changes nothing.
+pub fn parse_byte_count(input: &str) -> Result<usize, ByteCountError> {
+    let digits_length = input.bytes().take_while(u8::is_ascii_digit).count();
+    let (number, suffix) = input.split_at(digits_length);
+    let number: usize = number.parse().map_err(ByteCountError::InvalidNumber)?;
+
+    let multiplier: usize = match suffix {
+        "" | "B"       => 1,
+        "K" | "KiB"    => 1 << 10,
+        "M" | "MiB"    => 1 << 20,
+        "G" | "GiB"    => 1 << 30,
+        "KB"           => 1_000,
+        "MB"           => 1_000_000,
+        "GB"           => 1_000_000_000,
+        _              => return Err(ByteCountError::UnknownSuffix(suffix.into())),
+    };
+
+    number.checked_mul(multiplier).ok_or(ByteCountError::TooLarge)
+}
+
+
+/// Something that can go wrong parsing a number of bytes.
+#[derive(PartialEq, Debug)]
+pub enum ByteCountError {
+
+    /// The number part could not be parsed.
+    InvalidNumber(ParseIntError),
+
+    /// The number was followed by a suffix that is not a unit of bytes.
+    UnknownSuffix(String),
+
+    /// The number was too large once the suffix was taken into account.
+    TooLarge,
+}
+
+impl fmt::Display for ByteCountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(pie)     => write!(f, "{}", pie),
+            Self::UnknownSuffix(suffix)  => write!(f, "unknown suffix {:?}", suffix),
+            Self::TooLarge               => write!(f, "number too large"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn plain_number() {
+        assert_eq!(parse_byte_count("4096"), Ok(4096));
+    }
+
+    #[test]
+    fn zero() {
+        assert_eq!(parse_byte_count("0"), Ok(0));
+    }
+
+    #[test]
+    fn bytes_suffix() {
+        assert_eq!(parse_byte_count("512B"), Ok(512));
+    }
+
+    #[test]
+    fn binary_suffixes() {
+        assert_eq!(parse_byte_count("4K"),   Ok(4096));
+        assert_eq!(parse_byte_count("1KiB"), Ok(1024));
+        assert_eq!(parse_byte_count("2M"),   Ok(2_097_152));
+        assert_eq!(parse_byte_count("1MiB"), Ok(1_048_576));
+        assert_eq!(parse_byte_count("1G"),   Ok(1_073_741_824));
+        assert_eq!(parse_byte_count("1GiB"), Ok(1_073_741_824));
+    }
+
+    #[test]
+    fn decimal_suffixes() {
+        assert_eq!(parse_byte_count("4KB"), Ok(4000));
+        assert_eq!(parse_byte_count("1MB"), Ok(1_000_000));
+        assert_eq!(parse_byte_count("1GB"), Ok(1_000_000_000));
+    }
+
+    #[test]
+    fn unknown_suffix() {
+        assert_eq!(parse_byte_count("4Q"), Err(ByteCountError::UnknownSuffix("Q".into())));
+        assert_eq!(parse_byte_count("4 K"), Err(ByteCountError::UnknownSuffix(" K".into())));
+        assert_eq!(parse_byte_count("4k"), Err(ByteCountError::UnknownSuffix("k".into())));
+    }
+
+    #[test]
+    fn just_a_suffix() {
+        assert!(matches!(parse_byte_count("K"), Err(ByteCountError::InvalidNumber(_))));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(matches!(parse_byte_count(""), Err(ByteCountError::InvalidNumber(_))));
+    }
+
+    #[test]
+    fn negative() {
+        assert!(matches!(parse_byte_count("-4"), Err(ByteCountError::InvalidNumber(_))));
+    }
+
+    #[test]
+    fn too_large() {
+        assert_eq!(parse_byte_count("18446744073709551615M"), Err(ByteCountError::TooLarge));
+    }
+
+    #[test]
+    fn too_large_without_suffix() {
+        assert!(matches!(parse_byte_count("99999999999999999999999"), Err(ByteCountError::InvalidNumber(_))));
+    }
+}
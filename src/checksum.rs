@@ -0,0 +1,116 @@
+//! Computing a checksum over the output to write after it, for formats that
+//! end with one, such as Modbus frames. A function in the program cannot see
+//! the bytes that came before it, so this is done once all of them exist.
+
+
+/// A checksum that can be written after the output.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Checksum {
+
+    /// The CRC-16 used by Modbus, written low byte first, as Modbus does.
+    Crc16Modbus,
+
+    /// The sum of every byte, wrapping around, as a single byte.
+    Sum8,
+}
+
+impl Checksum {
+
+    /// Returns the checksum with the given name, as given on the command
+    /// line, or `None` if there is no checksum with that name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "crc16"  => Some(Self::Crc16Modbus),
+            "sum8"   => Some(Self::Sum8),
+            _        => None,
+        }
+    }
+
+    /// Computes this checksum over the given bytes, and adds it to the end.
+    pub fn append_to(self, mut bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::Crc16Modbus  => bytes.extend(crc16_modbus(&bytes).to_le_bytes()),
+            Self::Sum8         => bytes.push(sum8(&bytes)),
+        }
+
+        bytes
+    }
+}
+
+
+/// Computes the CRC-16 of the given bytes using the Modbus parameters: the
+/// reflected 0x8005 polynomial, starting from all ones. There’s no table
+/// here, as the output is usually small.
+fn crc16_modbus(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+
+    for byte in bytes {
+        crc ^= u16::from(*byte);
+
+        for _ in 0 .. 8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ 0xA001;
+            }
+            else {
+                crc >>= 1;
+            }
+        }
+    }
+
+    crc
+}
+
+/// Adds up the given bytes into a single byte, ignoring any overflow.
+fn sum8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(Checksum::Crc16Modbus.append_to(b"123456789".to_vec()),
+                   b"123456789\x37\x4B".to_vec());
+    }
+
+    #[test]
+    fn crc16_modbus_frame() {
+        // reading two holding registers from device 1
+        assert_eq!(Checksum::Crc16Modbus.append_to(vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x02 ]),
+                   vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x02, 0xC4, 0x0B ]);
+    }
+
+    #[test]
+    fn crc16_nothing() {
+        assert_eq!(Checksum::Crc16Modbus.append_to(vec![]),
+                   vec![ 0xFF, 0xFF ]);
+    }
+
+    #[test]
+    fn sum8_check_value() {
+        assert_eq!(Checksum::Sum8.append_to(b"123456789".to_vec()),
+                   b"123456789\xDD".to_vec());
+    }
+
+    #[test]
+    fn sum8_wraps() {
+        assert_eq!(Checksum::Sum8.append_to(vec![ 0xFF, 0x02 ]),
+                   vec![ 0xFF, 0x02, 0x01 ]);
+    }
+
+    #[test]
+    fn sum8_nothing() {
+        assert_eq!(Checksum::Sum8.append_to(vec![]),
+                   vec![ 0x00 ]);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Checksum::from_name("crc16"), Some(Checksum::Crc16Modbus));
+        assert_eq!(Checksum::from_name("sum8"), Some(Checksum::Sum8));
+        assert_eq!(Checksum::from_name("crc32"), None);
+    }
+}
@@ -3,12 +3,20 @@
 use ansi_term::Style;
 use ansi_term::Color::*;
 
+use crate::style::ByteCategory;
 
-/// The colours used in Hexit’s terminal UI, for reporting errors in programs.
+
+/// The colours used in Hexit’s terminal UI, for reporting errors in programs
+/// and for highlighting formatted output bytes.
 #[derive(Default, Copy, Clone)]
 pub struct Colours {
     pub error: Style,
     pub warning: Style,
+
+    pub zero_byte: Style,
+    pub printable_byte: Style,
+    pub control_byte: Style,
+    pub other_byte: Style,
 }
 
 impl Colours {
@@ -16,10 +24,25 @@ impl Colours {
         Colours {
             error:   Red.bold(),
             warning: Yellow.bold(),
+
+            zero_byte:       Fixed(244).normal(),
+            printable_byte:  Green.normal(),
+            control_byte:    Purple.normal(),
+            other_byte:      Style::default(),
         }
     }
 
     pub fn plain() -> Self {
         Self::default()
     }
+
+    /// The style to paint a formatted output byte with.
+    pub fn for_byte(&self, byte: u8) -> Style {
+        match ByteCategory::of(byte) {
+            ByteCategory::Zero       => self.zero_byte,
+            ByteCategory::Printable  => self.printable_byte,
+            ByteCategory::Control    => self.control_byte,
+            ByteCategory::Other      => self.other_byte,
+        }
+    }
 }
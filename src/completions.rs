@@ -0,0 +1,76 @@
+//! Generating shell completion scripts, so that the names of the built-in
+//! constants can be completed without running Hexit every time.
+
+use std::fmt::Write;
+
+use hexit_lang::constants::Table;
+
+use crate::options::Shell;
+
+
+/// Returns a completion script for the given shell. The script is the one
+/// from the `completions` directory, with the names of every constant in the
+/// given table written in ahead of it, so it does not need to ask Hexit for
+/// them when it gets used.
+pub fn generate(shell: Shell, constants: &Table) -> String {
+    let mut out = String::new();
+
+    match shell {
+        Shell::Bash => {
+            let script = include_str!("../completions/hexit.bash");
+
+            writeln!(out, "# Generated by ‘hexit --generate-completions bash’").unwrap();
+            writeln!(out, "_hexit_constants='").unwrap();
+            for (name, _) in constants.all() {
+                writeln!(out, "{}", name).unwrap();
+            }
+            writeln!(out, "'").unwrap();
+            writeln!(out).unwrap();
+            out.push_str(script);
+        }
+
+        Shell::Zsh => {
+            let script = include_str!("../completions/hexit.zsh");
+
+            // the ‘#compdef’ line has to stay at the top of the file
+            let (compdef, rest) = script.split_at(script.find('\n').map_or(0, |i| i + 1));
+            out.push_str(compdef);
+
+            writeln!(out, "# Generated by ‘hexit --generate-completions zsh’").unwrap();
+            writeln!(out, "__hexit_constants() {{").unwrap();
+            writeln!(out, "    local -a names").unwrap();
+            writeln!(out, "    names=(").unwrap();
+            for (name, _) in constants.all() {
+                writeln!(out, "        {}", name).unwrap();
+            }
+            writeln!(out, "    )").unwrap();
+            writeln!(out, "    compadd -a names").unwrap();
+            writeln!(out, "}}").unwrap();
+            out.push_str(rest);
+        }
+    }
+
+    out
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bash_options_and_constants() {
+        let script = generate(Shell::Bash, &Table::builtin_set());
+        assert!(script.contains("--generate-completions"));
+        assert!(script.contains("\nIP_UDP\n"));
+        assert!(script.contains("\nBGP_OPEN\n"));
+    }
+
+    #[test]
+    fn zsh_options_and_constants() {
+        let script = generate(Shell::Zsh, &Table::builtin_set());
+        assert!(script.starts_with("#compdef hexit\n"));
+        assert!(script.contains("--list-constants"));
+        assert!(script.contains("        IP_UDP\n"));
+    }
+}
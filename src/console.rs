@@ -31,10 +31,10 @@ impl UseColours {
     /// output is to a terminal.
     pub fn palette(self) -> Colours {
         if self.should_use_colours() {
-            Colours::plain()
+            Colours::pretty()
         }
         else {
-            Colours::pretty()
+            Colours::plain()
         }
     }
 }
@@ -0,0 +1,423 @@
+//! Loading extra constants from a file, on top of the built-in set.
+//!
+//! Each line of the file defines one constant, such as `MY_TYPE = 2A`. The
+//! number of hex characters in the value decides the constant’s width: two
+//! for an 8-bit constant, four for a 16-bit one, or eight for a 32-bit one.
+//! Blank lines, and any text after a `#`, are ignored. If a name is defined
+//! more than once, the last definition wins.
+//!
+//! Files ending in `.toml` are read as a small subset of TOML instead, where
+//! each `[section]` is the namespace for the integer keys that follow it, so
+//! `A = 1` under `[dns]` defines `DNS_A`. There, values that shadow a
+//! built-in constant keep its width. Otherwise, the width is decided by the
+//! number of hex digits for values such as `0x002A`, or by the smallest width
+//! that fits for decimal values. A name can only be defined once in a TOML
+//! file, as it can in TOML itself.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use hexit_lang::constants::{Table, Constant};
+
+
+/// Reads the constants file at the given path, and inserts every constant in
+/// it into the table. Returns the names of the constants that replaced ones
+/// already in the table, so the user can be warned about them.
+pub fn load(path: &Path, table: &mut Table) -> Result<Vec<String>, Error> {
+    let contents = std::fs::read_to_string(path).map_err(Error::Io)?;
+
+    if path.extension() == Some(OsStr::new("toml")) {
+        insert_all_toml(&contents, table)
+    }
+    else {
+        insert_all(&contents, table)
+    }
+}
+
+/// Inserts every constant defined in the given file contents into the table,
+/// returning the names of the constants that replaced existing ones.
+fn insert_all(contents: &str, table: &mut Table) -> Result<Vec<String>, Error> {
+    let mut shadowed = Vec::new();
+
+    for (line_index, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let line_number = line_index + 1;
+        let (name, value) = parse_line(line).ok_or(Error::InvalidLine(line_number))?;
+
+        if table.insert(name.to_owned(), value).is_some() {
+            shadowed.push(name.to_owned());
+        }
+    }
+
+    Ok(shadowed)
+}
+
+/// Parses one non-empty line of a constants file into a name and value.
+fn parse_line(line: &str) -> Option<(&str, Constant)> {
+    let equals_index = line.find('=')?;
+    let name = line[.. equals_index].trim();
+    let value = line[equals_index + 1 ..].trim();
+
+    if ! is_constant_name(name) {
+        return None;
+    }
+
+    if ! value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match value.len() {
+        2 => Some((name, Constant::Eight(u8::from_str_radix(value, 16).ok()?))),
+        4 => Some((name, Constant::Sixteen(u16::from_str_radix(value, 16).ok()?))),
+        8 => Some((name, Constant::ThirtyTwo(u32::from_str_radix(value, 16).ok()?))),
+        _ => None,
+    }
+}
+
+
+/// Inserts every constant defined in the given TOML file contents into the
+/// table, returning the names of the constants that replaced existing ones.
+fn insert_all_toml(contents: &str, table: &mut Table) -> Result<Vec<String>, Error> {
+    let mut shadowed = Vec::new();
+    let mut defined = HashSet::new();
+    let mut section = None;
+
+    for (line_index, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let line_number = line_index + 1;
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let header = header.trim();
+            if header.is_empty() || ! header.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return Err(Error::InvalidLine(line_number));
+            }
+
+            section = Some(header.to_ascii_uppercase());
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or(Error::InvalidLine(line_number))?;
+        let key = key.trim();
+        let name = match &section {
+            Some(section)  => format!("{}_{}", section, key),
+            None           => key.to_owned(),
+        };
+
+        if key.is_empty() || ! is_constant_name(&name) {
+            return Err(Error::InvalidLine(line_number));
+        }
+
+        if defined.contains(&name) {
+            return Err(Error::Duplicate(line_number));
+        }
+
+        let shadowed_width = table.lookup(&name).map(Constant::bit_width);
+        let value = parse_toml_integer(value.trim(), shadowed_width, line_number)?;
+        defined.insert(name.clone());
+
+        if table.insert(name.clone(), value).is_some() {
+            shadowed.push(name);
+        }
+    }
+
+    Ok(shadowed)
+}
+
+/// Parses a TOML integer into a constant. Values get the width of the
+/// constant they shadow, if there is one. Otherwise, hex values such as
+/// `0x002A` get the width of their digits, which must be two, four, or eight
+/// of them, and decimal values get the smallest width they fit in.
+fn parse_toml_integer(value: &str, shadowed_width: Option<u8>, line_number: usize) -> Result<Constant, Error> {
+    let invalid = || Error::InvalidLine(line_number);
+
+    // TOML allows underscores between digits, such as in `0xDEAD_BEEF`
+    let digits = value.replace('_', "");
+
+    if let Some(hex) = digits.strip_prefix("0x") {
+        if hex.is_empty() || ! hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let width = match (shadowed_width, hex.len()) {
+            (Some(width), _)  => width,
+            (None, 2)         => 8,
+            (None, 4)         => 16,
+            (None, 8)         => 32,
+            (None, _)         => return Err(invalid()),
+        };
+
+        let number = u64::from_str_radix(hex, 16).map_err(|_| Error::TooLarge(line_number))?;
+        return constant_of_width(number, width).ok_or(Error::TooLarge(line_number));
+    }
+
+    if digits.is_empty() || ! digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let number: u64 = digits.parse().map_err(|_| Error::TooLarge(line_number))?;
+
+    let constant = match shadowed_width {
+        Some(width)  => constant_of_width(number, width),
+        None         => smallest_constant(number),
+    };
+
+    constant.ok_or(Error::TooLarge(line_number))
+}
+
+/// Returns a constant holding the given number in the given bit width, if
+/// it fits.
+fn constant_of_width(number: u64, width: u8) -> Option<Constant> {
+    match width {
+        8   => u8::try_from(number).ok().map(Constant::Eight),
+        16  => u16::try_from(number).ok().map(Constant::Sixteen),
+        _   => u32::try_from(number).ok().map(Constant::ThirtyTwo),
+    }
+}
+
+/// Returns a constant holding the given number in the smallest width that
+/// it fits in, if it fits in any of them.
+fn smallest_constant(number: u64) -> Option<Constant> {
+    if let Ok(byte) = u8::try_from(number) {
+        Some(Constant::Eight(byte))
+    }
+    else if let Ok(word) = u16::try_from(number) {
+        Some(Constant::Sixteen(word))
+    }
+    else if let Ok(long) = u32::try_from(number) {
+        Some(Constant::ThirtyTwo(long))
+    }
+    else {
+        None
+    }
+}
+
+/// Determines whether the given name is one that Hexit would read as a
+/// constant, rather than as hex bytes separated by underscores.
+fn is_constant_name(name: &str) -> bool {
+    name.len() >= 3
+        && name.contains('_')
+        && name.as_bytes()[0].is_ascii_uppercase()
+        && name.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+        && ! name.bytes().all(|b| b.is_ascii_hexdigit() || b == b'_')
+}
+
+
+/// Something that can go wrong loading a constants file.
+#[derive(Debug)]
+pub enum Error {
+
+    /// The file could not be read.
+    Io(io::Error),
+
+    /// The line with the given number did not define a constant.
+    InvalidLine(usize),
+
+    /// The line with the given number defined a constant whose value is too
+    /// large to fit in its width.
+    TooLarge(usize),
+
+    /// The line with the given number defined a constant that an earlier
+    /// line in the same file had already defined.
+    Duplicate(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e)                 => write!(f, "{}", e),
+            Self::InvalidLine(number)   => write!(f, "Invalid constant definition on line {}", number),
+            Self::TooLarge(number)      => write!(f, "Constant value on line {} is too large", number),
+            Self::Duplicate(number)     => write!(f, "Constant on line {} was already defined earlier in the file", number),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn widths() {
+        let mut table = Table::empty();
+        assert_eq!(insert_all("MY_BYTE = 2A\nMY_WORD = 012C\nMY_LONG = 7F454C46\n", &mut table).unwrap(),
+                   Vec::<String>::new());
+        assert_eq!(table.lookup("MY_BYTE"), Some(Constant::Eight(0x2A)));
+        assert_eq!(table.lookup("MY_WORD"), Some(Constant::Sixteen(0x012C)));
+        assert_eq!(table.lookup("MY_LONG"), Some(Constant::ThirtyTwo(0x7F45_4C46)));
+    }
+
+    #[test]
+    fn comments_and_blanks() {
+        let mut table = Table::empty();
+        insert_all("# my constants\n\nMY_BYTE = 2A  # the answer\n", &mut table).unwrap();
+        assert_eq!(table.lookup("MY_BYTE"), Some(Constant::Eight(0x2A)));
+    }
+
+    #[test]
+    fn shadowing_a_builtin() {
+        let mut table = Table::builtin_set();
+        assert_eq!(insert_all("DNS_A = 0063\nMY_BYTE = 2A\n", &mut table).unwrap(),
+                   vec![ String::from("DNS_A") ]);
+        assert_eq!(table.lookup("DNS_A"), Some(Constant::Sixteen(0x63)));
+    }
+
+    #[test]
+    fn invalid_width() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all("MY_BYTE = 2A\nMY_OTHER = 123\n", &mut table),
+                         Err(Error::InvalidLine(2))));
+    }
+
+    #[test]
+    fn name_without_underscore() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all("MINE = 2A\n", &mut table),
+                         Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn name_that_reads_as_hex() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all("BE_EF = 2A\n", &mut table),
+                         Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn invalid_name() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all("my byte = 2A\n", &mut table),
+                         Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn toml_sections() {
+        let mut table = Table::empty();
+        insert_all_toml("[dns]\nMY_TYPE = 0x002A\n\n[gps]\nQUERY = 7  # comment\n", &mut table).unwrap();
+        assert_eq!(table.lookup("DNS_MY_TYPE"), Some(Constant::Sixteen(0x2A)));
+        assert_eq!(table.lookup("gps.QUERY"), Some(Constant::Eight(7)));
+    }
+
+    #[test]
+    fn toml_without_section() {
+        let mut table = Table::empty();
+        insert_all_toml("MY_TYPE = 0x2A\n", &mut table).unwrap();
+        assert_eq!(table.lookup("MY_TYPE"), Some(Constant::Eight(0x2A)));
+    }
+
+    #[test]
+    fn toml_decimal_widths() {
+        let mut table = Table::empty();
+        insert_all_toml("[my]\nBYTE = 255\nWORD = 256\nLONG = 4_294_967_295\n", &mut table).unwrap();
+        assert_eq!(table.lookup("MY_BYTE"), Some(Constant::Eight(255)));
+        assert_eq!(table.lookup("MY_WORD"), Some(Constant::Sixteen(256)));
+        assert_eq!(table.lookup("MY_LONG"), Some(Constant::ThirtyTwo(0xFFFF_FFFF)));
+    }
+
+    #[test]
+    fn toml_hex_width() {
+        let mut table = Table::empty();
+        insert_all_toml("[elf]\nMAGIC = 0x7F45_4C46\n", &mut table).unwrap();
+        assert_eq!(table.lookup("ELF_MAGIC"), Some(Constant::ThirtyTwo(0x7F45_4C46)));
+    }
+
+    #[test]
+    fn toml_shadowing_a_builtin() {
+        let mut table = Table::builtin_set();
+        assert_eq!(insert_all_toml("[dns]\nA = 0x0063\n", &mut table).unwrap(),
+                   vec![ String::from("DNS_A") ]);
+        assert_eq!(table.lookup("dns.A"), Some(Constant::Sixteen(0x63)));
+    }
+
+    #[test]
+    fn toml_decimal_keeps_shadowed_width() {
+        let mut table = Table::builtin_set();
+        assert_eq!(insert_all_toml("[dns]\nA = 1\n", &mut table).unwrap(),
+                   vec![ String::from("DNS_A") ]);
+        assert_eq!(table.lookup("dns.A"), Some(Constant::Sixteen(1)));
+    }
+
+    #[test]
+    fn toml_hex_keeps_shadowed_width() {
+        let mut table = Table::builtin_set();
+        assert_eq!(insert_all_toml("[dns]\nA = 0x01\n", &mut table).unwrap(),
+                   vec![ String::from("DNS_A") ]);
+        assert_eq!(table.lookup("dns.A"), Some(Constant::Sixteen(1)));
+    }
+
+    #[test]
+    fn toml_hex_too_large_for_shadowed_width() {
+        let mut table = Table::builtin_set();
+        assert!(matches!(insert_all_toml("[dns]\nA = 0x0001_0000\n", &mut table),
+                         Err(Error::TooLarge(2))));
+    }
+
+    #[test]
+    fn toml_decimal_too_large_for_shadowed_width() {
+        let mut table = Table::builtin_set();
+        assert!(matches!(insert_all_toml("[dns]\nA = 65536\n", &mut table),
+                         Err(Error::TooLarge(2))));
+    }
+
+    #[test]
+    fn last_definition_wins() {
+        let mut table = Table::empty();
+        insert_all("MY_BYTE = 2A\nMY_BYTE = 2B\n", &mut table).unwrap();
+        assert_eq!(table.lookup("MY_BYTE"), Some(Constant::Eight(0x2B)));
+    }
+
+    #[test]
+    fn toml_duplicate() {
+        let mut table = Table::builtin_set();
+        assert!(matches!(insert_all_toml("[dns]\nA = 1\n\n[dns]\nA = 2\n", &mut table),
+                         Err(Error::Duplicate(5))));
+    }
+
+    #[test]
+    fn toml_too_large() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all_toml("[my]\nA = 1\nHUGE = 4294967296\n", &mut table),
+                         Err(Error::TooLarge(3))));
+    }
+
+    #[test]
+    fn toml_odd_hex_width() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all_toml("[my]\nTYPE = 0x12C\n", &mut table),
+                         Err(Error::InvalidLine(2))));
+    }
+
+    #[test]
+    fn toml_not_an_integer() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all_toml("[my]\nTYPE = \"hello\"\n", &mut table),
+                         Err(Error::InvalidLine(2))));
+    }
+
+    #[test]
+    fn toml_invalid_section() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all_toml("[my.types]\nTYPE = 1\n", &mut table),
+                         Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn toml_lowercase_key() {
+        let mut table = Table::empty();
+        assert!(matches!(insert_all_toml("[my]\ntype = 1\n", &mut table),
+                         Err(Error::InvalidLine(2))));
+    }
+}
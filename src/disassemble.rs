@@ -0,0 +1,106 @@
+//! Turning raw bytes back into a Hexit program that would produce them.
+
+use hexit_lang::constants::{Table, Constant};
+
+
+/// The shortest run of printable ASCII characters that gets written as a
+/// string literal, rather than as individual bytes. Shorter runs are more
+/// likely to be coincidences in binary data than actual text.
+const MIN_STRING_LENGTH: usize = 4;
+
+/// Writes out a plausible Hexit program that produces the given bytes.
+/// Runs of printable ASCII characters become string literals, and bytes
+/// with the value of exactly one 8-bit constant in the table become that
+/// constant’s name; everything else is written as a pair of hex characters.
+/// Bytes shared by more than one constant are left as hex, as there’s no
+/// way to know which (if any) was meant.
+pub fn disassemble(bytes: &[u8], constants: &Table) -> String {
+    let mut parts = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let run_length = bytes[index ..].iter().take_while(|b| is_printable(**b)).count();
+
+        if run_length >= MIN_STRING_LENGTH {
+            parts.push(string_literal(&bytes[index .. index + run_length]));
+            index += run_length;
+            continue;
+        }
+
+        let byte = bytes[index];
+        let mut names = constants.names_for_value(Constant::Eight(byte));
+        match (names.next(), names.next()) {
+            (Some(name), None)  => parts.push(name.to_owned()),
+            _                   => parts.push(format!("{:02X}", byte)),
+        }
+
+        index += 1;
+    }
+
+    parts.join(" ")
+}
+
+/// Whether the byte is a printable ASCII character, including the space.
+fn is_printable(byte: u8) -> bool {
+    (0x20 ..= 0x7E).contains(&byte)
+}
+
+/// Writes a run of printable bytes as a quoted string, escaping the
+/// characters that need it.
+fn string_literal(bytes: &[u8]) -> String {
+    let mut literal = String::from("\"");
+
+    for &byte in bytes {
+        match byte {
+            b'"'   => literal.push_str("\\\""),
+            b'\\'  => literal.push_str("\\\\"),
+            _      => literal.push(char::from(byte)),
+        }
+    }
+
+    literal.push('"');
+    literal
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hexit_lang::Program;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn nothing() {
+        assert_eq!(disassemble(&[], &Table::builtin_set()),
+                   "");
+    }
+
+    #[test]
+    fn plain_bytes() {
+        assert_eq!(disassemble(&[ 0x00, 0xAB, 0xFF ], &Table::builtin_set()),
+                   "00 AB FF");
+    }
+
+    #[test]
+    fn short_runs_stay_as_bytes() {
+        assert_eq!(disassemble(b"hi\x00", &Table::builtin_set()),
+                   "68 69 00");
+    }
+
+    #[test]
+    fn ascii_run_and_constant() {
+        assert_eq!(disassemble(b"\x84GET \"/\"\x00", &Table::builtin_set()),
+                   "IP_SCTP \"GET \\\"/\\\"\" 00");
+    }
+
+    #[test]
+    fn round_trip() {
+        let constants = Table::builtin_set();
+        let bytes = b"\x84\x01\x02say \"hello\\world\"\xFF\x0C";
+
+        let program_text = disassemble(bytes, &constants);
+        let lines = [ program_text.as_str() ];
+        let program = Program::read(&lines).expect("Parsing failed");
+        assert_eq!(program.run(&constants, None), Ok(bytes.to_vec()));
+    }
+}
@@ -4,6 +4,7 @@ use std::fs::File;
 use std::fmt;
 use std::io::{self, Read, BufRead, BufReader};
 use std::path::PathBuf;
+use std::str;
 
 use log::*;
 
@@ -15,19 +16,31 @@ pub enum Input {
     /// The program has been read from a command-line argument.
     Expression(String),
 
+    /// The program has been read from the `HEXIT_PROGRAM` environment
+    /// variable.
+    Env(String),
+
     /// The program should be read from standard input.
     Stdin,
 
     /// The program should be read from the file at the given path.
     File(PathBuf),
+
+    /// The program should be read from another input, with an expression
+    /// from a command-line argument added on after its last line. The lines
+    /// of the expression are numbered on from there, but get reported as
+    /// lines of the expression.
+    Appended(Box<Input>, String),
 }
 
 impl fmt::Display for Input {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Expression(_)  => write!(f, "<expression>"),
+            Self::Env(_)         => write!(f, "<$HEXIT_PROGRAM>"),
             Self::Stdin          => write!(f, "<stdin>"),
             Self::File(path)     => write!(f, "{}", path.display()),
+            Self::Appended(base, _)  => write!(f, "{}+<expression>", base),
         }
     }
 }
@@ -38,7 +51,7 @@ impl Input {
     /// as a series of strings, or an I/O error if something goes wrong.
     pub fn read(&self) -> io::Result<Vec<String>> {
         match self {
-            Self::Expression(input_string) => {
+            Self::Expression(input_string) | Self::Env(input_string) => {
                 info!("Reading from string");
 
                 let lines = input_string.lines().map(|line| line.to_owned()).collect();
@@ -63,7 +76,139 @@ impl Input {
                 debug!("Successfully read file contents");
                 Ok(lines)
             }
+
+            Self::Appended(base, input_string) => {
+                let mut lines = base.read()?;
+                info!("Appending lines from string");
+
+                lines.extend(input_string.lines().map(|line| line.to_owned()));
+                Ok(lines)
+            }
+        }
+    }
+
+    /// Returns an iterator over the lines of the Hexit program, reading
+    /// each line from the input source only once it is asked for, or an I/O
+    /// error if the input source cannot be opened.
+    pub fn lines(&self) -> io::Result<Lines<'_>> {
+        match self {
+            Self::Expression(input_string) | Self::Env(input_string) => {
+                info!("Streaming from string");
+                Ok(Lines::new(Box::new(input_string.lines().map(|line| Ok(line.to_owned()))), None))
+            }
+
+            Self::Stdin => {
+                info!("Streaming from standard input");
+                Ok(Lines::new(Box::new(BufReader::new(io::stdin().lock()).lines()), None))
+            }
+
+            Self::File(path) => {
+                info!("Streaming from file → {:?}", path);
+                let handle = File::open(path)?;
+                Ok(Lines::new(Box::new(BufReader::new(handle).lines()), None))
+            }
+
+            Self::Appended(base, input_string) => {
+                Ok(Lines::new(Box::new(base.lines()?), Some(input_string.lines())))
+            }
+        }
+    }
+
+    /// Reads the complete input source as raw bytes, rather than as lines of
+    /// a program, returning an I/O error if something goes wrong.
+    pub fn read_bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Expression(input_string) | Self::Env(input_string) => {
+                info!("Reading bytes from string");
+                Ok(input_string.as_bytes().to_vec())
+            }
+
+            Self::Stdin => {
+                info!("Reading bytes from standard input");
+                let mut bytes = Vec::new();
+                io::stdin().lock().read_to_end(&mut bytes)?;
+                Ok(bytes)
+            }
+
+            Self::File(path) => {
+                info!("Reading bytes from file → {:?}", path);
+                std::fs::read(path)
+            }
+
+            Self::Appended(..) => {
+                // the options reject this, as the expression’s text would
+                // get mixed in with the file’s bytes
+                Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot append an expression to bytes"))
+            }
+        }
+    }
+
+    /// Returns the number of lines of the program that came from the base
+    /// of an appended input, given every line that was read from it. For
+    /// any other input, this is all of them.
+    pub fn base_line_count(&self, lines: &[String]) -> usize {
+        match self {
+            Self::Appended(_, input_string)  => lines.len() - input_string.lines().count(),
+            _                                => lines.len(),
+        }
+    }
+
+    /// Returns the name of the input that the line with the given number
+    /// came from, along with the number of that line within it. For an
+    /// appended input, lines after the given number of base lines are from
+    /// the expression, and get numbered from one again.
+    pub fn locate(&self, line_number: usize, base_line_count: usize) -> (String, usize) {
+        match self {
+            Self::Appended(_, _) if line_number > base_line_count  => (String::from("<expression>"), line_number - base_line_count),
+            Self::Appended(base, _)                                => (base.to_string(), line_number),
+            _                                                      => (self.to_string(), line_number),
+        }
+    }
+}
+
+
+/// The lines of a Hexit program, read from its input source one at a time.
+/// For an appended input, the base’s lines come before the expression’s.
+pub struct Lines<'a> {
+
+    /// The lines read from the input source, or from the base of an
+    /// appended input.
+    base: Box<dyn Iterator<Item=io::Result<String>> + 'a>,
+
+    /// The lines of the expression appended to the base, if there is one.
+    expression: Option<str::Lines<'a>>,
+
+    /// The number of lines that have been read from the base so far.
+    base_line_count: usize,
+}
+
+impl<'a> Lines<'a> {
+
+    /// Creates lines that read from the given base, followed by the lines
+    /// of the given expression if there is one.
+    fn new(base: Box<dyn Iterator<Item=io::Result<String>> + 'a>, expression: Option<str::Lines<'a>>) -> Self {
+        Self { base, expression, base_line_count: 0 }
+    }
+
+    /// Returns the number of lines read so far that came from the base of
+    /// an appended input, so they can be told apart from the expression’s
+    /// lines while the program is still being read. For any other input,
+    /// this is all of them.
+    pub fn base_line_count(&self) -> usize {
+        self.base_line_count
+    }
+}
+
+impl Iterator for Lines<'_> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(line) = self.base.next() {
+            self.base_line_count += 1;
+            return Some(line);
         }
+
+        self.expression.as_mut()?.next().map(|line| Ok(line.to_owned()))
     }
 }
 
@@ -75,3 +220,68 @@ fn read_all_lines(handle: impl Read) -> io::Result<Vec<String>> {
     let lines = reader.lines().collect::<io::Result<_>>()?;
     Ok(lines)
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn crlf_lines() {
+        let source: &[u8] = b"\xEF\xBB\xBFAB\r\nCD # comment\r\n";
+        assert_eq!(read_all_lines(source).unwrap(),
+                   vec![ String::from("\u{FEFF}AB"), String::from("CD # comment") ]);
+    }
+
+    #[test]
+    fn expression_line_numbers() {
+        let input = Input::Expression(String::from("AB CD\nEF GZ"));
+        let lines = input.read().unwrap();
+        let errors = hexit_lang::Program::read(&lines).err().expect("Parsing succeeded");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source_pos().line_number, 2);
+        assert_eq!(errors[0].source_pos().column_number, 3);
+    }
+
+    #[test]
+    fn appended_lines() {
+        let input = Input::Appended(Box::new(Input::Env(String::from("AB\nCD"))), String::from("EF\n01"));
+        assert_eq!(input.read().unwrap(),
+                   vec![ String::from("AB"), String::from("CD"), String::from("EF"), String::from("01") ]);
+    }
+
+    #[test]
+    fn appended_lines_streamed() {
+        let input = Input::Appended(Box::new(Input::Env(String::from("AB\nCD"))), String::from("EF"));
+        let mut lines = input.lines().unwrap();
+        assert_eq!(lines.by_ref().collect::<io::Result<Vec<_>>>().unwrap(),
+                   input.read().unwrap());
+        assert_eq!(lines.base_line_count(), 2);
+    }
+
+    #[test]
+    fn appended_locations() {
+        let input = Input::Appended(Box::new(Input::File(PathBuf::from("base.hexit"))), String::from("EF\n01"));
+        let lines = vec![ String::from("AB"), String::from("CD"), String::from("EF"), String::from("01") ];
+        let base_line_count = input.base_line_count(&lines);
+
+        assert_eq!(base_line_count, 2);
+        assert_eq!(input.locate(2, base_line_count), (String::from("base.hexit"), 2));
+        assert_eq!(input.locate(3, base_line_count), (String::from("<expression>"), 1));
+        assert_eq!(input.locate(4, base_line_count), (String::from("<expression>"), 2));
+    }
+
+    #[test]
+    fn file_location() {
+        let input = Input::File(PathBuf::from("base.hexit"));
+        assert_eq!(input.locate(3, 3), (String::from("base.hexit"), 3));
+    }
+
+    #[test]
+    fn appended_name() {
+        let input = Input::Appended(Box::new(Input::File(PathBuf::from("base.hexit"))), String::from("EF"));
+        assert_eq!(input.to_string(), "base.hexit+<expression>");
+    }
+}
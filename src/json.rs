@@ -0,0 +1,84 @@
+//! Writing errors as JSON objects, one per line, for tools such as editors
+//! that want to read them rather than show them to a person.
+
+use std::fmt::Write;
+
+
+/// Writes a syntax or runtime error, or a warning, as a JSON object,
+/// including the position in the source where it occurred.
+pub fn error(input: &str, line: usize, column: usize, kind: &str, message: &str) -> String {
+    format!("{{\"input\":{},\"line\":{},\"column\":{},\"kind\":{},\"message\":{}}}",
+            string(input), line, column, string(kind), string(message))
+}
+
+/// Writes a warning about the input as a whole, rather than one place in
+/// it, as a JSON object. It has the same fields as an error, but with a
+/// null line and column.
+pub fn input_warning(input: &str, kind: &str, message: &str) -> String {
+    format!("{{\"input\":{},\"line\":null,\"column\":null,\"kind\":{},\"message\":{}}}",
+            string(input), string(kind), string(message))
+}
+
+/// Writes a string as a quoted JSON string, escaping the characters that
+/// need it.
+fn string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"'   => out.push_str("\\\""),
+            '\\'  => out.push_str("\\\\"),
+            '\n'  => out.push_str("\\n"),
+            '\r'  => out.push_str("\\r"),
+            '\t'  => out.push_str("\\t"),
+            c if c < ' ' => {
+                // writing to a String cannot fail
+                write!(out, "\\u{:04x}", u32::from(c)).unwrap();
+            }
+            _     => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hexit_lang::Program;
+    use hexit_lang::constants::Table;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn single_hex() {
+        let lines = [ "AB C" ];
+        let errors = Program::read(&lines).err().expect("Parsing succeeded");
+        let e = &errors[0];
+
+        assert_eq!(error("<expression>", e.source_pos().line_number, e.source_pos().column_number, e.kind(), &e.to_string()),
+                   r#"{"input":"<expression>","line":1,"column":3,"kind":"single_hex","message":"Unpaired hex character \"C\""}"#);
+    }
+
+    #[test]
+    fn unknown_constant() {
+        let lines = [ "AB be16(NOT_HERE)" ];
+        let e = Program::read(&lines).unwrap().run_located(&Table::builtin_set(), None).unwrap_err();
+
+        assert_eq!(error("star.hexit", e.source_pos().line_number, e.source_pos().column_number, e.error.kind(), &e.to_string()),
+                   r#"{"input":"star.hexit","line":1,"column":8,"kind":"unknown_constant","message":"Unknown constant ‘NOT_HERE’"}"#);
+    }
+
+    #[test]
+    fn no_output() {
+        assert_eq!(input_warning("empty.hexit", "empty_output", "Program produced no output"),
+                   r#"{"input":"empty.hexit","line":null,"column":null,"kind":"empty_output","message":"Program produced no output"}"#);
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(string("a\"b\\c\n\u{1}"),
+                   r#""a\"b\\c\n\u0001""#);
+    }
+}
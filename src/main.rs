@@ -17,21 +17,38 @@
 
 
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
 use log::*;
 
-use hexit_lang::Program;
-use hexit_lang::constants::{Table, Constant};
+use hexit_lang::{Program, LineRunner};
+use hexit_lang::constants::Table;
+use hexit_lang::verify::Verification;
 
+mod byte_count;
+mod checksum;
 mod colours;
+mod completions;
 mod console;
+mod constants_file;
+mod disassemble;
 mod logger;
 mod input;
+mod json;
+mod normalise;
 mod options;
+mod stats;
+mod snippet;
+mod split;
+mod stream;
 mod style;
-mod verify;
-use crate::options::{RunningMode, Options, Output, Format, OptionsResult, HelpReason};
+use crate::colours::Colours;
+use crate::console::UseColours;
+use crate::input::Input;
+use crate::options::{RunningMode, Options, Output, Format, ErrorFormat, OptionsResult, HelpReason};
+use crate::stats::Stats;
+use crate::stream::{StreamedBytes, Failure, Problem};
 
 
 fn main() {
@@ -40,7 +57,7 @@ fn main() {
 
     logger::configure(env::var_os("HEXIT_DEBUG"));
 
-    match RunningMode::getopts(env::args_os().skip(1)) {
+    match RunningMode::getopts_with_env_program(env::args_os().skip(1), env::var_os("HEXIT_PROGRAM")) {
         OptionsResult::Ok(run_mode) => {
             exit(run(run_mode));
         }
@@ -90,9 +107,16 @@ pub fn run(mode: RunningMode) -> i32 {
     info!("Running with mode → {:#?}", mode);
 
     match mode {
+        RunningMode::Run(opts) if opts.stream => {
+            return run_streaming(*opts);
+        }
+
         RunningMode::Run(opts) => {
-            let Options { input, output, format, verification, limit } = opts;
-            let source_lines = match input.read() {
+            return run_program(*opts);
+        }
+
+        RunningMode::SyntaxCheck { input, parse_options } => {
+            let source = match input.read() {
                 Ok(p) => p,
                 Err(e) => {
                     eprintln!("{}: {}", input, e);
@@ -100,91 +124,82 @@ pub fn run(mode: RunningMode) -> i32 {
                 }
             };
 
-            let program = match Program::read(&source_lines) {
-                Ok(p) => p,
+            match Program::read_with_options(&source, parse_options) {
+                Ok(_) => {
+                    println!("{}: Syntax OK", input);
+                },
                 Err(es) => {
+                    let base_line_count = input.base_line_count(&source);
                     for e in es {
-                        eprintln!("{}:{}:{}: syntax error: {}", input, e.source_pos().line_number, e.source_pos().column_number, e);
+                        let (name, line_number) = input.locate(e.source_pos().line_number, base_line_count);
+                        println!("{}:{}: syntax error: {}", name, line_number, e);
                     }
                     return exits::PROGRAM_ERROR;
                 }
             };
+        }
 
-            let constants = Table::builtin_set();
-            let bytes = match program.run(&constants, limit) {
-                Ok(bs) => bs,
+        RunningMode::Normalise { input, expand_constants, parse_options, constants_file } => {
+            let source = match input.read() {
+                Ok(p) => p,
                 Err(e) => {
-                    eprintln!("{}: runtime error: {}", input, e);
-
-                    if let Some(note) = e.note() {
-                        eprintln!("{}: note: {}", input, note);
-                    }
-
-                    return exits::PROGRAM_ERROR;
+                    eprintln!("{}: {}", input, e);
+                    return exits::IO_ERROR;
                 }
             };
 
-            let bytes_written_attempt = match output {
-                Output::Stdout => {
-                    let stdout = io::stdout();
-                    let mut stdout = stdout.lock();
+            let program = match Program::read_with_options(&source, parse_options) {
+                Ok(p) => p,
+                Err(es) => {
+                    let base_line_count = input.base_line_count(&source);
+                    for e in es {
+                        let (name, line_number) = input.locate(e.source_pos().line_number, base_line_count);
+                        eprintln!("{}:{}:{}: syntax error: {}", name, line_number, e.source_pos().column_number, e);
 
-                    match format {
-                        Format::Raw               => stdout.write(&bytes),
-                        Format::Formatted(style)  => style.format(bytes.into_iter(), &mut stdout),
-                    }
-                },
-                Output::File(path) => {
-                    let mut file = match File::create(&path) {
-                        Ok(f) => f,
-                        Err(e) => {
-                            eprintln!("{}: error creating file: {}", path.display(), e);
-                            return exits::IO_ERROR;
+                        if let Some(source_line) = source.get(e.source_pos().line_number - 1) {
+                            eprintln!("{}", snippet::render(source_line, e.source_pos().column_number, e.span_length(), parse_options.tab_width, ansi_term::Style::default()));
                         }
-                    };
-
-                    match format {
-                        Format::Raw               => file.write(&bytes),
-                        Format::Formatted(style)  => style.format(bytes.into_iter(), &mut file),
                     }
-                },
+                    return exits::PROGRAM_ERROR;
+                }
             };
 
-            if let Err(e) = bytes_written_attempt {
-                eprintln!("{}: error writing output: {}", input, e);
-                return exits::IO_ERROR;
+            let constants = if expand_constants {
+                match load_constants(constants_file.as_deref()) {
+                    Some(c) => Some(c),
+                    None    => return exits::IO_ERROR,
+                }
             }
+            else {
+                None
+            };
 
-            if let Err(e) = verification.verify(bytes_written_attempt.unwrap()) {
-                eprintln!("{}: validation failed: {}", input, e);
-                return exits::LENGTH_VERIFICATION_ERROR;
-            }
+            println!("{}", normalise::normalise(program.exps(), constants.as_ref()));
         }
 
-        RunningMode::SyntaxCheck(input) => {
-            let source = match input.read() {
-                Ok(p) => p,
+        RunningMode::FromBytes { input, constants_file } => {
+            let bytes = match input.read_bytes() {
+                Ok(bs) => bs,
                 Err(e) => {
                     eprintln!("{}: {}", input, e);
                     return exits::IO_ERROR;
                 }
             };
 
-            match Program::read(&source) {
-                Ok(_) => {
-                    println!("{}: Syntax OK", input);
-                },
-                Err(es) => {
-                    for e in es {
-                        println!("{}:{}: syntax error: {}", input, e.source_pos().line_number, e);
-                    }
-                    return exits::PROGRAM_ERROR;
-                }
+            let constants = match load_constants(constants_file.as_deref()) {
+                Some(c) => c,
+                None    => return exits::IO_ERROR,
             };
+
+            println!("{}", disassemble::disassemble(&bytes, &constants));
         }
 
-        RunningMode::ListConstants { filter } => {
-            let constants = Table::builtin_set();
+        RunningMode::ListConstants { filter, names_only, constants_file } => {
+            let constants = match load_constants(constants_file.as_deref()) {
+                Some(c) => c,
+                None    => return exits::IO_ERROR,
+            };
+
             let stdout = io::stdout();
             let mut out_handle = stdout.lock();
             let mut found_any = false;
@@ -196,14 +211,12 @@ pub fn run(mode: RunningMode) -> i32 {
                     }
                 }
 
-                match value {
-                    Constant::Eight(v) => {
-                        writeln!(out_handle, "{} => {} (8-bit)", name, v)
-                    }
-                    Constant::Sixteen(v) => {
-                        writeln!(out_handle, "{} => {} (16-bit)", name, v)
-                    }
-                }.unwrap();
+                if names_only {
+                    writeln!(out_handle, "{}", name).unwrap();
+                }
+                else {
+                    writeln!(out_handle, "{} => {} ({}-bit)", name, value.value_u64(), value.bit_width()).unwrap();
+                }
 
                 found_any = true;
             }
@@ -213,12 +226,362 @@ pub fn run(mode: RunningMode) -> i32 {
                 return exits::NO_CONSTANTS_FOUND;
             }
         }
+
+        RunningMode::GenerateCompletions(shell) => {
+            let constants = Table::builtin_set();
+            print!("{}", completions::generate(shell, &constants));
+        }
     }
 
     exits::SUCCESS
 }
 
 
+/// Reads the whole program, runs it, and writes its output.
+fn run_program(opts: Options) -> i32 {
+    let Options { input, output, format, prepend, append, append_checksum, colour_bytes, error_colours, verification, limit, line_limit, stats, constants_file, errors, parsing: parse_options, stream: _ } = opts;
+    let colours = error_colours.palette();
+    let source_lines = match input.read() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}: {}", input, e);
+            return exits::IO_ERROR;
+        }
+    };
+
+    let base_line_count = input.base_line_count(&source_lines);
+    let program = match Program::read_with_options(&source_lines, parse_options) {
+        Ok(p) => p,
+        Err(es) => {
+            for e in es {
+                let (name, line_number) = input.locate(e.source_pos().line_number, base_line_count);
+                match errors {
+                    ErrorFormat::Human => {
+                        eprintln!("{}:{}:{}: {}: {}", name, line_number, e.source_pos().column_number, colours.error.paint("syntax error"), e);
+
+                        if let Some(source_line) = source_lines.get(e.source_pos().line_number - 1) {
+                            eprintln!("{}", snippet::render(source_line, e.source_pos().column_number, e.span_length(), parse_options.tab_width, colours.error));
+                        }
+                    }
+                    ErrorFormat::Json => {
+                        eprintln!("{}", json::error(&name, line_number, e.source_pos().column_number, e.kind(), &e.to_string()));
+                    }
+                }
+            }
+            return exits::PROGRAM_ERROR;
+        }
+    };
+
+    let constants = match load_constants(constants_file.as_deref()) {
+        Some(c) => c,
+        None    => return exits::IO_ERROR,
+    };
+
+    // warnings and lints only get produced when they have been asked
+    // for, so there is nothing to filter out here
+    let lints = if parse_options.lint { program.lint(&constants, limit, line_limit) } else { Vec::new() };
+    for w in program.warnings().iter().chain(&lints) {
+        let pos = w.source_pos();
+        let problem = Problem { line_number: pos.line_number, column_number: pos.column_number, length: pos.contents.chars().count(), kind: w.kind(), message: w.to_string(), note: None, source_line: source_lines.get(pos.line_number - 1).cloned() };
+        print_problem(&input, base_line_count, errors, parse_options.tab_width, colours.warning, "warning", &problem);
+    }
+
+    let bytes = match program.run_with_line_limit(&constants, limit, line_limit) {
+        Ok(bs) => bs,
+        Err(e) => {
+            let (name, line_number) = input.locate(e.source_pos().line_number, base_line_count);
+            match errors {
+                ErrorFormat::Human => {
+                    eprintln!("{}:{}:{}: {}: {}", name, line_number, e.source_pos().column_number, colours.error.paint("runtime error"), e);
+
+                    if let Some(source_line) = source_lines.get(e.source_pos().line_number - 1) {
+                        eprintln!("{}", snippet::render(source_line, e.source_pos().column_number, e.source_pos().contents.chars().count(), parse_options.tab_width, colours.error));
+                    }
+
+                    if let Some(note) = e.error.note() {
+                        eprintln!("{}: note: {}", name, note);
+                    }
+                }
+                ErrorFormat::Json => {
+                    eprintln!("{}", json::error(&name, line_number, e.source_pos().column_number, e.error.kind(), &e.to_string()));
+                }
+            }
+
+            return exits::PROGRAM_ERROR;
+        }
+    };
+
+    let output_is_empty = bytes.is_empty();
+
+    // the extra bytes count towards the output’s length when verifying
+    let bytes = if prepend.is_empty() && append.is_empty() { bytes }
+                                                         else { [ prepend, bytes, append ].concat() };
+
+    // so does the checksum, which covers every byte before it
+    let bytes = match append_checksum {
+        Some(checksum)  => checksum.append_to(bytes),
+        None            => bytes,
+    };
+
+    let stats = if stats { Some(Stats::of(&bytes)) } else { None };
+    let dry_run = matches!(output, Output::DryRun);
+
+    let write_attempt = match write_output(&output, &format, colour_bytes, bytes.into_iter()) {
+        Ok(attempt) => attempt,
+        Err(code)   => return code,
+    };
+
+    let count = match check_written(&input, write_attempt, dry_run) {
+        Ok(count) => count,
+        Err(code) => return code,
+    };
+
+    if let Some(stats) = stats {
+        eprintln!("{}: {}", input, stats);
+    }
+
+    check_output(&input, count, output_is_empty, verification, errors, colours.warning)
+}
+
+/// Returns the built-in constants table, with the constants from the given
+/// file added to it if there is one. Returns `None` if the file could not be
+/// loaded, after printing why.
+fn load_constants(constants_file: Option<&Path>) -> Option<Table> {
+    let mut constants = Table::builtin_set();
+
+    if let Some(path) = constants_file {
+        match constants_file::load(path, &mut constants) {
+            Ok(shadowed) => {
+                for name in shadowed {
+                    eprintln!("{}: warning: constant ‘{}’ shadows an existing constant", path.display(), name);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                return None;
+            }
+        }
+    }
+
+    Some(constants)
+}
+
+/// Runs the program one line at a time, writing the output as it goes,
+/// rather than reading the whole program and then running it. Errors can
+/// only be reported once the output before them has been written.
+fn run_streaming(opts: Options) -> i32 {
+    let Options { input, output, format, prepend, append, colour_bytes, error_colours, verification, limit, line_limit, constants_file, errors, parsing: parse_options, .. } = opts;
+    let colours = error_colours.palette();
+
+    // the options reject this, as the files can only be split up once every
+    // byte is known
+    if let Output::SplitFiles { .. } = output {
+        eprintln!("{}: cannot split the output when streaming", input);
+        return exits::OPTIONS_ERROR;
+    }
+
+    let constants = match load_constants(constants_file.as_deref()) {
+        Some(c) => c,
+        None    => return exits::IO_ERROR,
+    };
+
+    let lines = match input.lines() {
+        Ok(ls) => ls,
+        Err(e) => {
+            eprintln!("{}: {}", input, e);
+            return exits::IO_ERROR;
+        }
+    };
+
+    let runner = LineRunner::new(&constants, limit, line_limit, parse_options);
+    let mut streamed = StreamedBytes::new(lines, runner, prepend, append);
+
+    let dry_run = matches!(output, Output::DryRun);
+    let write_attempt = match write_output(&output, &format, colour_bytes, streamed.by_ref()) {
+        Ok(attempt) => attempt,
+        Err(code)   => return code,
+    };
+
+    let base_line_count = streamed.lines().base_line_count();
+    for w in &streamed.warnings {
+        print_problem(&input, base_line_count, errors, parse_options.tab_width, colours.warning, "warning", w);
+    }
+
+    match &streamed.failure {
+        Some(Failure::Io(e)) => {
+            eprintln!("{}: {}", input, e);
+            return exits::IO_ERROR;
+        }
+        Some(Failure::Syntax(problem)) => {
+            print_problem(&input, base_line_count, errors, parse_options.tab_width, colours.error, "syntax error", problem);
+            return exits::PROGRAM_ERROR;
+        }
+        Some(Failure::Runtime(problem)) => {
+            print_problem(&input, base_line_count, errors, parse_options.tab_width, colours.error, "runtime error", problem);
+            return exits::PROGRAM_ERROR;
+        }
+        None => {}
+    }
+
+    let count = match check_written(&input, write_attempt, dry_run) {
+        Ok(count) => count,
+        Err(code) => return code,
+    };
+
+    check_output(&input, count, streamed.program_count == 0, verification, errors, colours.warning)
+}
+
+/// Writes bytes to the given output in the given format as they are
+/// produced, returning the attempt to write them, which holds the number of
+/// bytes on success. A dry run only counts them. Returns the exit code to
+/// stop with instead if an output file could not be created.
+fn write_output(output: &Output, format: &Format, colour_bytes: Option<UseColours>, bytes: impl Iterator<Item=u8>) -> Result<io::Result<usize>, i32> {
+    match output {
+        Output::Stdout => {
+            // bytes only get coloured when they are going to a terminal,
+            // never when they are being piped or written to a file
+            let byte_colours = colour_bytes.filter(|c| c.should_use_colours()).map(|_| Colours::pretty());
+            Ok(write_bytes(format, byte_colours.as_ref(), bytes, io::stdout().lock()))
+        }
+        Output::File(path) => {
+            let file = create_file(path)?;
+            Ok(write_bytes(format, None, bytes, file))
+        }
+        Output::DryRun => {
+            Ok(Ok(bytes.count()))
+        }
+        Output::SplitFiles { path, chunk_size } => {
+            // the number of files depends on the number of bytes, so every
+            // one of them has to be produced before any get written
+            let bytes = bytes.collect::<Vec<_>>();
+            let mut total = 0;
+
+            for (chunk_path, chunk) in split::chunks(path, &bytes, *chunk_size) {
+                let file = create_file(&chunk_path)?;
+                match write_bytes(format, None, chunk.iter().copied(), file) {
+                    Ok(count)  => total += count,
+                    Err(e)     => return Ok(Err(e)),
+                }
+            }
+
+            Ok(Ok(total))
+        }
+    }
+}
+
+/// Creates the file at the given path for writing, returning the exit code
+/// to stop with if it could not be created, after printing why.
+fn create_file(path: &Path) -> Result<File, i32> {
+    File::create(path).map_err(|e| {
+        eprintln!("{}: error creating file: {}", path.display(), e);
+        exits::IO_ERROR
+    })
+}
+
+/// Writes bytes to the given sink as they are produced, in the given format,
+/// buffering them so that each byte does not need its own write. Returns the
+/// number of bytes written.
+fn write_bytes(format: &Format, colours: Option<&Colours>, bytes: impl Iterator<Item=u8>, sink: impl Write) -> io::Result<usize> {
+    let mut sink = BufWriter::new(sink);
+
+    let count = match (format, colours) {
+        (Format::Raw, _) => {
+            let mut count = 0;
+            for byte in bytes {
+                sink.write_all(&[ byte ])?;
+                count += 1;
+            }
+            count
+        }
+        (Format::Formatted(style), None) => {
+            style.format(bytes, &mut sink)?
+        }
+        (Format::Formatted(style), Some(cols)) => {
+            style.format_coloured(bytes, &mut sink, cols)?
+        }
+    };
+
+    sink.flush()?;
+    Ok(count)
+}
+
+/// Checks the attempt to write the output, printing how many bytes would
+/// have been written if it was a dry run. Returns the number of bytes, or
+/// the exit code to stop with if they could not be written.
+fn check_written(input: &Input, write_attempt: io::Result<usize>, dry_run: bool) -> Result<usize, i32> {
+    let count = match write_attempt {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("{}: error writing output: {}", input, e);
+            return Err(exits::IO_ERROR);
+        }
+    };
+
+    if dry_run {
+        match count {
+            1 => eprintln!("{}: 1 byte would be written", input),
+            n => eprintln!("{}: {} bytes would be written", input, n),
+        }
+    }
+
+    Ok(count)
+}
+
+/// Warns if the program itself produced no output, and then verifies the
+/// number of bytes that were written, returning the exit code to stop with.
+fn check_output(input: &Input, count: usize, program_was_empty: bool, verification: Verification, errors: ErrorFormat, warning_style: ansi_term::Style) -> i32 {
+    // a program that produces nothing, such as a file that is all
+    // comments, is usually a mistake, even if it passes verification
+    if program_was_empty {
+        print_empty_output_warning(input, errors, warning_style);
+    }
+
+    if let Err(e) = verification.verify(count) {
+        eprintln!("{}: validation failed: {}", input, e);
+        return exits::LENGTH_VERIFICATION_ERROR;
+    }
+
+    exits::SUCCESS
+}
+
+/// Prints an error or warning found in the program, along with the line of
+/// source it is on if there is one, whether the program was read all at once
+/// or streamed. The label and any carets are painted with the given style.
+fn print_problem(input: &Input, base_line_count: usize, errors: ErrorFormat, tab_width: Option<usize>, style: ansi_term::Style, label: &str, problem: &Problem) {
+    let (name, line_number) = input.locate(problem.line_number, base_line_count);
+
+    match errors {
+        ErrorFormat::Human => {
+            eprintln!("{}:{}:{}: {}: {}", name, line_number, problem.column_number, style.paint(label), problem.message);
+
+            if let Some(source_line) = &problem.source_line {
+                eprintln!("{}", snippet::render(source_line, problem.column_number, problem.length, tab_width, style));
+            }
+
+            if let Some(note) = problem.note {
+                eprintln!("{}: note: {}", name, note);
+            }
+        }
+        ErrorFormat::Json => {
+            eprintln!("{}", json::error(&name, line_number, problem.column_number, problem.kind, &problem.message));
+        }
+    }
+}
+
+/// Prints the warning for a program that produced no output at all, such as
+/// a file that is all comments. This has no position in the source, so it
+/// gets printed against the input as a whole.
+fn print_empty_output_warning(input: &Input, errors: ErrorFormat, style: ansi_term::Style) {
+    match errors {
+        ErrorFormat::Human => {
+            eprintln!("{}: {}: program produced no output", input, style.paint("warning"));
+        }
+        ErrorFormat::Json => {
+            eprintln!("{}", json::input_warning(&input.to_string(), "empty_output", "Program produced no output"));
+        }
+    }
+}
+
 mod exits {
 
     /// Exit code for when everything turns out OK.
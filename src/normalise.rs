@@ -0,0 +1,327 @@
+//! Re-emitting a program as canonical Hexit source, so that fixtures written
+//! in slightly different styles can be diffed against one another.
+
+use std::fmt::Write;
+use std::net::Ipv6Addr;
+use std::time::{Duration, UNIX_EPOCH};
+
+use hexit_lang::ast::{Exp, FunctionName, MultiByteType, FixedPointType, BitwiseFold, TimeUnit};
+use hexit_lang::constants::{Table, Constant};
+
+
+/// Writes the given expressions out as one line of canonical Hexit source:
+/// bytes are in uppercase, expressions are separated by exactly one space,
+/// and function calls are written out in full, so `be16[1]` becomes
+/// `be16([1])`. Comments are not part of the program, so they are lost.
+///
+/// If a table of constants is given, each constant that can be found in it
+/// gets replaced by its value; otherwise, constants are kept by name.
+pub fn normalise(exps: &[Exp<'_>], constants: Option<&Table>) -> String {
+    let mut out = String::new();
+    write_exps(&mut out, exps, constants);
+    out
+}
+
+/// Writes each of the given expressions, with a single space between them.
+fn write_exps(out: &mut String, exps: &[Exp<'_>], constants: Option<&Table>) {
+    for (index, exp) in exps.iter().enumerate() {
+        if index > 0 {
+            out.push(' ');
+        }
+
+        write_exp(out, exp, constants);
+    }
+}
+
+/// Writes a single expression in its canonical form, replacing constants
+/// with their values if a table of them is given.
+fn write_exp(out: &mut String, exp: &Exp<'_>, constants: Option<&Table>) {
+    // writing to a String cannot fail
+    match exp {
+        Exp::Char(byte) => {
+            write!(out, "{:02X}", byte).unwrap();
+        }
+        Exp::Dec(number) => {
+            write!(out, "[{}]", number).unwrap();
+        }
+        Exp::Constant { name } => {
+            match constants.and_then(|c| c.lookup(name.contents)) {
+                Some(Constant::Eight(value)) => {
+                    write!(out, "{:02X}", value).unwrap();
+                }
+                Some(constant) => {
+                    // a bit form keeps its width, just like the constant
+                    let value = constant.value_u64();
+                    let bits = (0 .. constant.bit_width()).rev().map(|i| value >> i & 1 == 1);
+                    write_bits(out, &bits.collect::<Vec<_>>());
+                }
+                None => {
+                    out.push_str(name.contents);
+                }
+            }
+        }
+        Exp::Function { name, args, .. } => {
+            write_function_name(out, *name);
+            out.push('(');
+            write_exps(out, args, constants);
+            out.push(')');
+        }
+        Exp::StringLiteral { bytes } => {
+            out.push('"');
+            write_string_contents(out, bytes);
+            out.push('"');
+        }
+        Exp::IPv4 { bytes } => {
+            write!(out, "[{}.{}.{}.{}]", bytes[0], bytes[1], bytes[2], bytes[3]).unwrap();
+        }
+        Exp::IPv6 { bytes } => {
+            write!(out, "[{}]", Ipv6Addr::from(*bytes)).unwrap();
+        }
+        Exp::Timestamp { time, unit: TimeUnit::Seconds } => {
+            let time = UNIX_EPOCH + Duration::from_secs(*time);
+            write!(out, "[{}]", humantime::format_rfc3339_seconds(time)).unwrap();
+        }
+        Exp::Timestamp { time, unit: TimeUnit::Millis } => {
+            let time = UNIX_EPOCH + Duration::from_millis(*time);
+            write!(out, "[ms:{}]", humantime::format_rfc3339_millis(time)).unwrap();
+        }
+        Exp::Timestamp { time, unit: TimeUnit::Nanos } => {
+            let time = UNIX_EPOCH + Duration::from_nanos(*time);
+            write!(out, "[ns:{}]", humantime::format_rfc3339_nanos(time)).unwrap();
+        }
+        Exp::Float(number) => {
+            write!(out, "[f{}]", number).unwrap();
+        }
+        Exp::Bits(bits) => {
+            write_bits(out, bits);
+        }
+        Exp::Typed { bytes } => {
+            // the bytes are already in order, so write them big-endian
+            write!(out, "[u{}:0x", bytes.len() * 8).unwrap();
+            for byte in bytes {
+                write!(out, "{:02X}", byte).unwrap();
+            }
+            out.push(']');
+        }
+        Exp::Group(exps) => {
+            out.push('(');
+            write_exps(out, exps, constants);
+            out.push(')');
+        }
+    }
+}
+
+/// Writes the bytes of a string literal, escaping the ones that need it.
+/// Text stays as text, but any bytes that are not valid UTF-8, or that are
+/// control characters without a named escape, are written in octal.
+fn write_string_contents(out: &mut String, bytes: &[u8]) {
+    let mut rest = bytes;
+
+    while ! rest.is_empty() {
+        let (text, invalid) = match std::str::from_utf8(rest) {
+            Ok(text) => (text, &[][..]),
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                let invalid_length = e.error_len().unwrap_or(after.len());
+                (std::str::from_utf8(valid).unwrap(), &after[.. invalid_length])
+            }
+        };
+
+        for c in text.chars() {
+            match c {
+                '\n'    => out.push_str("\\n"),
+                '\r'    => out.push_str("\\r"),
+                '\t'    => out.push_str("\\t"),
+                '\x07'  => out.push_str("\\a"),
+                '\x08'  => out.push_str("\\b"),
+                '\x0C'  => out.push_str("\\f"),
+                '\x0B'  => out.push_str("\\v"),
+                '\x1B'  => out.push_str("\\e"),
+                '"'     => out.push_str("\\\""),
+                '\\'    => out.push_str("\\\\"),
+                c if c.is_ascii_control() => {
+                    write!(out, "\\{:03o}", u32::from(c)).unwrap();
+                }
+                _       => out.push(c),
+            }
+        }
+
+        for byte in invalid {
+            write!(out, "\\{:03o}", byte).unwrap();
+        }
+
+        rest = &rest[text.len() + invalid.len() ..];
+    }
+}
+
+/// Writes a bit form, with the bits in groups of four counting from the
+/// right, such as `[b1_0000_0001]`.
+fn write_bits(out: &mut String, bits: &[bool]) {
+    out.push_str("[b");
+
+    for (index, bit) in bits.iter().enumerate() {
+        if index > 0 && (bits.len() - index) % 4 == 0 {
+            out.push('_');
+        }
+
+        out.push(if *bit { '1' } else { '0' });
+    }
+
+    out.push(']');
+}
+
+/// Writes the canonical name of a function, which is the name it is written
+/// with in full, rather than any of its aliases.
+fn write_function_name(out: &mut String, name: FunctionName) {
+    let name = match name {
+        FunctionName::MultiByte(MultiByteType::Be16)        => "be16",
+        FunctionName::MultiByte(MultiByteType::Be32)        => "be32",
+        FunctionName::MultiByte(MultiByteType::Be64)        => "be64",
+        FunctionName::MultiByte(MultiByteType::Le16)        => "le16",
+        FunctionName::MultiByte(MultiByteType::Le32)        => "le32",
+        FunctionName::MultiByte(MultiByteType::Le64)        => "le64",
+        FunctionName::FixedPoint(FixedPointType::Q8_8Be)    => "q8_8be",
+        FunctionName::FixedPoint(FixedPointType::Q8_8Le)    => "q8_8le",
+        FunctionName::FixedPoint(FixedPointType::Q16_16Be)  => "q16_16be",
+        FunctionName::FixedPoint(FixedPointType::Q16_16Le)  => "q16_16le",
+        FunctionName::Bitwise(BitwiseFold::And)             => "and",
+        FunctionName::Bitwise(BitwiseFold::Or)              => "or",
+        FunctionName::Bitwise(BitwiseFold::Xor)             => "xor",
+        FunctionName::BitwiseNot                            => "not",
+        FunctionName::NibbleSwap                            => "nibbleswap",
+        FunctionName::Neg                                   => "neg",
+        FunctionName::PopCount                              => "popcount",
+        FunctionName::LeadingZeros                          => "clz",
+        FunctionName::TrailingZeros                         => "ctz",
+        FunctionName::Fill                                  => "fill",
+        FunctionName::Bits                                  => "bits",
+        FunctionName::Zero                                  => "zero",
+        FunctionName::Ones                                  => "ones",
+        FunctionName::Gray                                  => "gray",
+        FunctionName::Parity                                => "parity",
+        FunctionName::Varint                                => "varint",
+        FunctionName::SignedVarint                          => "svarint",
+        FunctionName::Bcd                                   => "bcd",
+        FunctionName::RepeatDynamic { .. }                  => "repeat",
+        FunctionName::Joined { .. }                         => "joined",
+        FunctionName::Repeat(amount) => {
+            write!(out, "x{}", amount).unwrap();
+            return;
+        }
+    };
+
+    out.push_str(name);
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hexit_lang::{Program, ParseOptions, FrontComments};
+    use pretty_assertions::assert_eq;
+
+    fn normalise_line(line: &str, constants: Option<&Table>) -> String {
+        normalise_line_with_options(line, constants, ParseOptions::default())
+    }
+
+    fn normalise_line_with_options(line: &str, constants: Option<&Table>, options: ParseOptions) -> String {
+        let lines = vec![ line ];
+        let program = Program::read_with_options(&lines, options).expect("Parsing failed");
+        normalise(program.exps(), constants)
+    }
+
+    #[test]
+    fn bytes_and_spacing() {
+        assert_eq!(normalise_line("  ab cd\tEF01  # comment", None),
+                   "AB CD EF 01");
+    }
+
+    #[test]
+    fn functions_in_full() {
+        assert_eq!(normalise_line("X3(be16[1234])  u32le[7]  or(01   02)", None),
+                   "x3(be16([1234])) le32([7]) or(01 02)");
+    }
+
+    #[test]
+    fn forms() {
+        assert_eq!(normalise_line("[127.0.0.1] [::1] [b101_0101] [f1.5] [i16:-1] [2017-12-31T21:36:45]", None),
+                   "[127.0.0.1] [::1] [b101_0101] [f1.5] [u16:0xFFFF] [2017-12-31T21:36:45Z]");
+    }
+
+    #[test]
+    fn timestamp_units() {
+        assert_eq!(normalise_line("[ms:2017-12-31T21:36:45] [ns:2017-12-31T21:36:45]", None),
+                   "[ms:2017-12-31T21:36:45.000Z] [ns:2017-12-31T21:36:45.000000000Z]");
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(normalise_line(r#""a\"b\\c\n""#, None),
+                   r#""a\"b\\c\n""#);
+        assert_eq!(normalise_line(r#""\a\b\f\v\e""#, None),
+                   r#""\a\b\f\v\e""#);
+        assert_eq!(normalise_line(r#""\101\0\377é""#, None),
+                   r#""A\000\377é""#);
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(normalise_line("( ab  (cd) )x2((01 02))", None),
+                   "(AB (CD)) x2((01 02))");
+    }
+
+    #[test]
+    fn constants_kept() {
+        assert_eq!(normalise_line("IP_UDP be16(DNS_A)", None),
+                   "IP_UDP be16(DNS_A)");
+    }
+
+    #[test]
+    fn constants_expanded() {
+        assert_eq!(normalise_line("IP_UDP be16(DNS_A)", Some(&Table::builtin_set())),
+                   "11 be16([b0000_0000_0000_0001])");
+    }
+
+    #[test]
+    fn semicolon_comments_dropped() {
+        let options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(normalise_line_with_options("ab cd ; comment", None, options),
+                   "AB CD");
+    }
+
+    #[test]
+    fn colons_kept_without_front_comments() {
+        let options = ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() };
+        assert_eq!(normalise_line_with_options("[ms:2017-12-31T21:36:45]", None, options),
+                   "[ms:2017-12-31T21:36:45.000Z]");
+    }
+
+    #[test]
+    fn constants_from_a_table() {
+        let mut constants = Table::builtin_set();
+        constants.insert("MY_MAGIC", Constant::Eight(0x42));
+        assert_eq!(normalise_line("MY_MAGIC IP_UDP", Some(&constants)),
+                   "42 11");
+    }
+
+    #[test]
+    fn round_trip() {
+        let constants = Table::builtin_set();
+
+        for line in &[ "ab x2(CD \"e\") be16[256] be32[2017-12-31T21:36:45]",
+                       "be16(and([b1111_0000_1111_0000] [b0000_1111_1111_0000])) repeat(IP_UDP 00)",
+                       "le32[f1.5] [u32le:1] IP_TCP be16(DNS_A)",
+                       "be64[ms:2017-12-31T21:36:45] le64[ns:2017-12-31T21:36:45]",
+                       "be32(ELF_MAGIC) le32(PNG_MAGIC) GZIP_ID1 GZIP_ID2",
+                       r#""\303\251\303 \1\177""# ] {
+            let canonical = normalise_line(line, Some(&constants));
+            assert_eq!(normalise_line(&canonical, Some(&constants)), canonical);
+
+            let original_lines = [ *line ];
+            let rewritten_lines = [ canonical.as_str() ];
+            let original = Program::read(&original_lines).unwrap().run(&constants, None);
+            let rewritten = Program::read(&rewritten_lines).unwrap().run(&constants, None);
+            assert_eq!(original, rewritten);
+        }
+    }
+}
@@ -1,16 +1,20 @@
 //! Command-line option parsing and the representation of the options.
 
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
-use std::num::ParseIntError;
+use std::num::{NonZeroUsize, ParseIntError};
 use std::path::PathBuf;
 
 use log::*;
 
+use hexit_lang::{FrontComments, ParseOptions};
+use hexit_lang::verify::Verification;
+
+use crate::byte_count::{parse_byte_count, ByteCountError};
+use crate::checksum::Checksum;
 use crate::console::UseColours;
 use crate::input::Input;
 use crate::style::{Style, LetterCase};
-use crate::verify::Verification;
 
 
 /// What Hexit should do after it’s been successfully invoked.
@@ -19,17 +23,62 @@ pub enum RunningMode {
 
     /// Hexit should execute (hexecute) using the given options, interpreting
     /// and running a program and writing its output somewhere.
-    Run(Options),
+    Run(Box<Options>),
 
     /// Hexit should check whether the given input is syntactically correct.
-    SyntaxCheck(Input),
+    SyntaxCheck {
+
+        /// Where the input program comes from.
+        input: Input,
+
+        /// How the input program should be read.
+        parse_options: ParseOptions,
+    },
+
+    /// Hexit should print the given input as canonical Hexit source.
+    Normalise {
+
+        /// Where the input program comes from.
+        input: Input,
+
+        /// Whether constants should be replaced by their values.
+        expand_constants: bool,
+
+        /// How the input program should be read.
+        parse_options: ParseOptions,
+
+        /// A file of extra constants to expand, on top of the built-in ones.
+        constants_file: Option<PathBuf>,
+    },
+
+    /// Hexit should read raw bytes from the given input and print a Hexit
+    /// program that would produce them.
+    FromBytes {
+
+        /// Where the raw bytes come from.
+        input: Input,
+
+        /// A file of extra constants to name values with, on top of the
+        /// built-in ones.
+        constants_file: Option<PathBuf>,
+    },
 
     /// Hexit should list the available constants.
     ListConstants {
 
         /// If given, only list constants that contain this substring.
         filter: Option<String>,
+
+        /// Whether to print only the names of the constants, for use by
+        /// shell completion scripts.
+        names_only: bool,
+
+        /// A file of extra constants to list, on top of the built-in ones.
+        constants_file: Option<PathBuf>,
     },
+
+    /// Hexit should print a completion script for the given shell.
+    GenerateCompletions(Shell),
 }
 
 /// The options necessary to run Hexit.
@@ -45,11 +94,71 @@ pub struct Options {
     /// How the output bytes should be formatted.
     pub format: Format,
 
+    /// Bytes to write before the program’s output, such as a magic number.
+    pub prepend: Vec<u8>,
+
+    /// Bytes to write after the program’s output.
+    pub append: Vec<u8>,
+
+    /// A checksum to compute over the whole output, including the bytes
+    /// prepended and appended to it, and write at the very end.
+    pub append_checksum: Option<Checksum>,
+
+    /// Whether formatted output bytes should be coloured by category, and if
+    /// so, when to use colours.
+    pub colour_bytes: Option<UseColours>,
+
+    /// When to use colours to highlight errors and warnings.
+    pub error_colours: UseColours,
+
     /// How the length of the output should be verified, if at all.
     pub verification: Verification,
 
     /// Whether to limit the maximum possible amount of output.
     pub limit: Option<usize>,
+
+    /// Whether to limit the amount of output from each top-level expression.
+    pub line_limit: Option<usize>,
+
+    /// Whether to print a summary of the output to stderr after writing it.
+    pub stats: bool,
+
+    /// A file of extra constants to load on top of the built-in ones.
+    pub constants_file: Option<PathBuf>,
+
+    /// How syntax and runtime errors should be printed.
+    pub errors: ErrorFormat,
+
+    /// How the program should be read, including the tab width to use
+    /// when working out column numbers for errors, and which warnings to
+    /// look for.
+    pub parsing: ParseOptions,
+
+    /// Whether to read and run the program one line at a time, writing the
+    /// output as it goes, instead of reading the whole program first.
+    pub stream: bool,
+}
+
+/// How errors in the program should be printed.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ErrorFormat {
+
+    /// Print each error as a line of text for a person to read.
+    Human,
+
+    /// Print each error as a JSON object on its own line, for tools to read.
+    Json,
+}
+
+/// A shell that Hexit can generate a completion script for.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Shell {
+
+    /// The Bourne Again Shell.
+    Bash,
+
+    /// The Z shell.
+    Zsh,
 }
 
 /// Where the output gets written to.
@@ -61,6 +170,17 @@ pub enum Output {
 
     /// Output should be written to a new file at the given path.
     File(PathBuf),
+
+    /// Output should not be written anywhere, with only its length being
+    /// reported, for checking that a program runs without producing files.
+    DryRun,
+
+    /// Output should be split into chunks of the given size, each written
+    /// to a new file named after the given path with a number on the end.
+    SplitFiles {
+        path: PathBuf,
+        chunk_size: NonZeroUsize,
+    },
 }
 
 /// How the output bytes should be formatted.
@@ -83,8 +203,19 @@ impl RunningMode {
     /// normally, a `Help` or `Version` variant if one of those options is
     /// specified, or an error variant if there’s an invalid option or
     /// inconsistency within the options after they were parsed.
-    #[allow(unused_results)]
     pub fn getopts<C>(args: C) -> OptionsResult
+    where C: IntoIterator,
+          C::Item: AsRef<OsStr>,
+    {
+        Self::getopts_with_env_program(args, None)
+    }
+
+    /// Parses and interprets a set of options in the same way as `getopts`,
+    /// also given the program in the `HEXIT_PROGRAM` environment variable,
+    /// if there is one. It only gets used when neither an input file nor an
+    /// expression is given.
+    #[allow(unused_results)]
+    pub fn getopts_with_env_program<C>(args: C, env_program: Option<OsString>) -> OptionsResult
     where C: IntoIterator,
           C::Item: AsRef<OsStr>,
     {
@@ -94,17 +225,40 @@ impl RunningMode {
         opts.optopt ("",  "color",           "when to use terminal colors",                                "WHEN");
         opts.optopt ("",  "colour",          "when to use terminal colours",                               "WHEN");
         opts.optflag("",  "list-constants",  "print the list of available constants");
-
+        opts.optflag("",  "names-only",      "print only the names of constants when listing them");
+        opts.optopt ("",  "generate-completions", "print a completion script for a shell (bash, zsh)",  "SHELL");
+        opts.optopt ("",  "constants",       "load extra constants from this file",                         "PATH");
+
+        opts.optflag("",  "semicolon-comments", "treat ‘;’ as the start of a comment, like ‘#’");
+        opts.optflag("",  "no-front-comments", "do not treat text before a colon as a comment");
+        opts.optflag("",  "warn-front-comments", "warn about front comments that contain only values");
+        opts.optflag("",  "lint",            "warn about valid input that looks like a mistake");
         opts.optflag("c", "check-syntax",    "instead of running, check that syntax is valid");
-        opts.optopt ("e", "expression",      "evaluate this expression instead of reading from a file",    "EXPR");
+        opts.optflag("",  "dry-run",         "run and verify the program, but do not write any output");
+        opts.optflag("",  "normalise",       "instead of running, print the program as canonical hexit");
+        opts.optflag("",  "expand-constants", "replace constants with their values when normalising");
+        opts.optflag("",  "from-bytes",      "instead of running, turn raw input bytes into a program");
+        opts.optmulti("e", "expression",     "evaluate this expression instead of reading from a file",    "EXPR");
         opts.optopt ("o", "output",          "output to this file instead of printing the results",        "PATH");
+        opts.optopt ("",  "prepend",         "write these hex bytes before the output",                    "HEX");
+        opts.optopt ("",  "append-bytes",    "write these hex bytes after the output",                     "HEX");
+        opts.optopt ("",  "append-checksum", "write a checksum of the output after it (crc16, sum8)",     "TYPE");
+        opts.optopt ("",  "split",           "split the output file into numbered files of this many bytes", "NUM");
 
         opts.optflag("r", "raw",             "print raw bytes without formatting");
         opts.optopt ("P", "prefix",          "string to print before each pair of hex characters",         "STR");
         opts.optopt ("S", "suffix",          "string to print after each pair of hex characters",          "STR");
         opts.optopt ("s", "separator",       "string to print between successive pairs of hex characters", "STR");
         opts.optflag("l", "lowercase",       "print hex characters in lowercase");
+        opts.optflag("",  "color-bytes",     "color hex characters by the kind of byte they are");
+        opts.optflag("",  "colour-bytes",    "colour hex characters by the kind of byte they are");
+        opts.optflag("",  "no-newline",      "do not print a newline after the output");
         opts.optopt ("",  "limit",           "limit the output from getting too large",                    "NUM");
+        opts.optopt ("",  "line-limit",      "limit the output of each expression from getting too large", "NUM");
+        opts.optflag("",  "stats",           "print the output’s length and checksum to stderr");
+        opts.optflag("",  "stream",          "run the program one line at a time, writing output as it goes");
+        opts.optopt ("",  "errors",          "how to print errors in the program (human, json)",          "FORMAT");
+        opts.optopt ("",  "tab-width",       "count tabs up to this width in error columns",               "NUM");
 
         opts.optopt ("",  "verify-length",   "ensure that the output has this exact length",               "NUM");
         opts.optopt ("",  "verify-boundary", "ensure that the output has a length with a given multiple",  "NUM");
@@ -117,11 +271,11 @@ impl RunningMode {
         if matches.opt_present("version") {
             OptionsResult::Version(UseColours::deduce(&matches))
         }
-        else if let Some(reason) = Self::check_help(&matches) {
+        else if let Some(reason) = Self::check_help(&matches, env_program.is_some()) {
             OptionsResult::Help(reason, UseColours::deduce(&matches))
         }
         else {
-            match Self::deduce(&matches) {
+            match Self::deduce(&matches, env_program) {
                 Ok(opts) => OptionsResult::Ok(opts),
                 Err(e)   => OptionsResult::InvalidOptions(e),
             }
@@ -130,11 +284,11 @@ impl RunningMode {
 
     /// Check whether the given set of matches require the help text to be
     /// printed; if so, returns the reason, and if not, returns nothing.
-    fn check_help(matches: &getopts::Matches) -> Option<HelpReason> {
+    fn check_help(matches: &getopts::Matches, has_env_program: bool) -> Option<HelpReason> {
         if matches.opt_present("help") {
             Some(HelpReason::Flag)
         }
-        else if ! matches.opt_present("expression") && ! matches.opt_present("list-constants") && matches.free.is_empty() {
+        else if ! matches.opt_present("expression") && ! matches.opt_present("list-constants") && ! matches.opt_present("generate-completions") && ! matches.opt_present("from-bytes") && matches.free.is_empty() && ! has_env_program {
             Some(HelpReason::NoArguments)
         }
         else {
@@ -142,63 +296,166 @@ impl RunningMode {
         }
     }
 
-    fn deduce(matches: &getopts::Matches) -> Result<Self, OptionsError> {
-        if matches.opt_present("list-constants") {
+    fn deduce(matches: &getopts::Matches, env_program: Option<OsString>) -> Result<Self, OptionsError> {
+        if let Some(shell) = matches.opt_str("generate-completions") {
+            Ok(Self::GenerateCompletions(Shell::deduce(shell)?))
+        }
+        else if matches.opt_present("list-constants") {
             let filter = match matches.free.len() {
                 0 => None,
                 1 => Some(matches.free[0].clone()),
                 _ => return Err(OptionsError::TooManyConstantSearches),
             };
-            Ok(Self::ListConstants { filter })
+            let names_only = matches.opt_present("names-only");
+            let constants_file = matches.opt_str("constants").map(PathBuf::from);
+            Ok(Self::ListConstants { filter, names_only, constants_file })
         }
         else if matches.opt_present("check-syntax") {
-            let input = Input::deduce(matches)?;
-            Ok(Self::SyntaxCheck(input))
+            let input = Input::deduce(matches, env_program)?;
+            let parse_options = deduce_parse_options(matches)?;
+            Ok(Self::SyntaxCheck { input, parse_options })
+        }
+        else if matches.opt_present("from-bytes") {
+            // the bytes come from standard input unless told otherwise
+            let input = if matches.free.is_empty() && ! matches.opt_present("expression") { Input::Stdin }
+                                                                                       else { Input::deduce(matches, None)? };
+
+            // an expression is its own input here, rather than more lines
+            if let Input::Appended(..) = input {
+                return Err(OptionsError::CannotAppendToBytes);
+            }
+
+            let constants_file = matches.opt_str("constants").map(PathBuf::from);
+            Ok(Self::FromBytes { input, constants_file })
+        }
+        else if matches.opt_present("normalise") {
+            let input = Input::deduce(matches, env_program)?;
+            let expand_constants = matches.opt_present("expand-constants");
+            let parse_options = deduce_parse_options(matches)?;
+            let constants_file = matches.opt_str("constants").map(PathBuf::from);
+            Ok(Self::Normalise { input, expand_constants, parse_options, constants_file })
         }
         else {
-            let input = Input::deduce(matches)?;
-            let output = Output::deduce(matches);
+            let input = Input::deduce(matches, env_program)?;
+            let output = Output::deduce(matches)?;
             let format = Format::deduce(matches);
-            let verification = Verification::deduce(matches)?;
+
+            let prepend = match matches.opt_str("prepend") {
+                Some(hex)  => parse_hex_bytes(&hex).ok_or(OptionsError::InvalidHexBytes(hex))?,
+                None       => Vec::new(),
+            };
+
+            let append = match matches.opt_str("append-bytes") {
+                Some(hex)  => parse_hex_bytes(&hex).ok_or(OptionsError::InvalidHexBytes(hex))?,
+                None       => Vec::new(),
+            };
+
+            let append_checksum = match matches.opt_str("append-checksum") {
+                Some(name)  => Some(Checksum::from_name(&name).ok_or(OptionsError::InvalidChecksum(name))?),
+                None        => None,
+            };
+
+            let colour_bytes = if matches.opt_present("color-bytes") || matches.opt_present("colour-bytes") { Some(UseColours::deduce(matches)) }
+                                                                                                           else { None };
+            let error_colours = UseColours::deduce(matches);
+            let verification = deduce_verification(matches)?;
 
             let limit = match matches.opt_str("limit") {
-                Some(l)  => Some(l.parse().map_err(OptionsError::InvalidLimit)?),
+                Some(l)  => Some(parse_byte_count(&l).map_err(OptionsError::InvalidLimit)?),
+                None     => None,
+            };
+
+            let line_limit = match matches.opt_str("line-limit") {
+                Some(l)  => Some(parse_byte_count(&l).map_err(OptionsError::InvalidLimit)?),
                 None     => None,
             };
 
-            Ok(Self::Run(Options { input, output, format, verification, limit }))
+            let stats = matches.opt_present("stats");
+            let constants_file = matches.opt_str("constants").map(PathBuf::from);
+            let errors = ErrorFormat::deduce(matches)?;
+
+            let parsing = deduce_parse_options(matches)?;
+
+            // streaming never holds the whole output, so it cannot be used
+            // with the options that need all of it at once
+            let stream = matches.opt_present("stream");
+            if stream && stats {
+                return Err(OptionsError::CannotStream("stats"));
+            }
+            else if stream && matches!(output, Output::SplitFiles { .. }) {
+                return Err(OptionsError::CannotStream("split"));
+            }
+            else if stream && append_checksum.is_some() {
+                return Err(OptionsError::CannotStream("append-checksum"));
+            }
+
+            Ok(Self::Run(Box::new(Options { input, output, format, prepend, append, append_checksum, colour_bytes, error_colours, verification, limit, line_limit, stats, constants_file, errors, parsing, stream })))
         }
     }
 }
 
 
 impl Input {
-    fn deduce(matches: &getopts::Matches) -> Result<Self, OptionsError> {
-        if let Some(expr_string) = matches.opt_str("expression") {
-            return Ok(Input::Expression(expr_string));
-        }
 
-        match matches.free.len() {
-            1 => {
+    /// Works out where the program comes from. An expression given with `-e`
+    /// comes first, then an input file, and then the `HEXIT_PROGRAM`
+    /// environment variable if neither of those were given. If `-e` is given
+    /// more than once, each expression becomes its own line of the program;
+    /// if an input file is given as well, the expression lines come after the
+    /// file’s lines.
+    fn deduce(matches: &getopts::Matches, env_program: Option<OsString>) -> Result<Self, OptionsError> {
+        let expr_strings = matches.opt_strs("expression");
+        let expression = if expr_strings.is_empty() { None }
+                                                else { Some(expr_strings.join("\n")) };
+
+        match (matches.free.len(), expression) {
+            (0, Some(expr_string))  => Ok(Input::Expression(expr_string)),
+            (0, None)               => {
+                let env_program = env_program.ok_or(OptionsError::NoInputFiles)?;
+                env_program.into_string().map(Input::Env).map_err(|_| OptionsError::InvalidEnvProgram)
+            }
+            (1, expression)         => {
                 let path = &matches.free[0];
 
-                if path == "-" { Ok(Input::Stdin) }
-                          else { Ok(Input::File(PathBuf::from(path))) }
-            },
+                let base = if path == "-" { Input::Stdin }
+                                     else { Input::File(PathBuf::from(path)) };
 
-            0 => Err(OptionsError::NoInputFiles),
-            _ => Err(OptionsError::TooManyInputFiles),
+                match expression {
+                    Some(expr_string)  => Ok(Input::Appended(Box::new(base), expr_string)),
+                    None               => Ok(base),
+                }
+            }
+            _                       => Err(OptionsError::TooManyInputFiles),
         }
     }
 }
 
 
 impl Output {
-    fn deduce(matches: &getopts::Matches) -> Self {
-       match matches.opt_str("output") {
-           Some(path)  => Output::File(PathBuf::from(path)),
-           None        => Output::Stdout,
-       }
+    fn deduce(matches: &getopts::Matches) -> Result<Self, OptionsError> {
+        let split = match matches.opt_str("split") {
+            Some(s)  => Some(NonZeroUsize::new(parse_byte_count(&s).map_err(OptionsError::InvalidSplitSize)?).ok_or(OptionsError::ZeroSplitSize)?),
+            None     => None,
+        };
+
+        // a dry run writes nothing, so asking for files as well is a mistake
+        if matches.opt_present("dry-run") {
+            if matches.opt_present("output") {
+                return Err(OptionsError::CannotDryRun("output"));
+            }
+            else if split.is_some() {
+                return Err(OptionsError::CannotDryRun("split"));
+            }
+
+            return Ok(Output::DryRun);
+        }
+
+        match (matches.opt_str("output"), split) {
+            (Some(path), None)              => Ok(Output::File(PathBuf::from(path))),
+            (Some(path), Some(chunk_size))  => Ok(Output::SplitFiles { path: PathBuf::from(path), chunk_size }),
+            (None,       None)              => Ok(Output::Stdout),
+            (None,       Some(_))           => Err(OptionsError::SplitWithoutOutput),
+        }
     }
 }
 
@@ -213,6 +470,28 @@ impl Format {
 }
 
 
+impl Shell {
+    fn deduce(name: String) -> Result<Self, OptionsError> {
+        match name.as_str() {
+            "bash"  => Ok(Self::Bash),
+            "zsh"   => Ok(Self::Zsh),
+            _       => Err(OptionsError::InvalidShell(name)),
+        }
+    }
+}
+
+
+impl ErrorFormat {
+    fn deduce(matches: &getopts::Matches) -> Result<Self, OptionsError> {
+        match matches.opt_str("errors").as_deref() {
+            None | Some("human")  => Ok(Self::Human),
+            Some("json")          => Ok(Self::Json),
+            Some(otherwise)       => Err(OptionsError::InvalidErrorFormat(otherwise.into())),
+        }
+    }
+}
+
+
 impl Style {
     fn deduce(matches: &getopts::Matches) -> Self {
         let prefix    = matches.opt_str("prefix");
@@ -220,7 +499,8 @@ impl Style {
         let separator = matches.opt_str("separator");
 
         let case = LetterCase::deduce(matches);
-        Style { prefix, suffix, separator, case }
+        let newline = ! matches.opt_present("no-newline");
+        Style { prefix, suffix, separator, case, newline }
     }
 }
 
@@ -235,18 +515,53 @@ impl LetterCase {
 }
 
 
-impl Verification {
-    fn deduce(matches: &getopts::Matches) -> Result<Self, OptionsError> {
-        let length   = matches.opt_str("verify-length");
-        let boundary = matches.opt_str("verify-boundary");
-
-        match (length, boundary) {
-            (None,    None   )  => Ok(Verification::AnythingGoes),
-            (Some(l), None   )  => Ok(Verification::ExactLength(l.parse()?)),
-            (None,    Some(b))  => Ok(Verification::Multiple(b.parse()?)),
-            (Some(_), Some(_))  => Err(OptionsError::TooMuchVerification),
-        }
+/// Parses a string of hex bytes, such as `89504E47` or `89 50 4E 47`, for
+/// the options that take literal bytes. Each byte needs both its characters.
+fn parse_hex_bytes(input: &str) -> Option<Vec<u8>> {
+    let digits = input.chars().filter(|c| ! c.is_ascii_whitespace()).collect::<String>();
+    if digits.len() % 2 != 0 || ! digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
     }
+
+    (0 .. digits.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i .. i + 2], 16).ok())
+        .collect()
+}
+
+// `Verification` lives in the hexit-lang crate, so this can’t be a method
+fn deduce_verification(matches: &getopts::Matches) -> Result<Verification, OptionsError> {
+    let length   = matches.opt_str("verify-length");
+    let boundary = matches.opt_str("verify-boundary");
+
+    match (length, boundary) {
+        (None,    None   )  => Ok(Verification::AnythingGoes),
+        (Some(l), None   )  => Ok(Verification::ExactLength(parse_byte_count(&l)?)),
+        (None,    Some(b))  => match parse_byte_count(&b)? {
+            0         => Err(OptionsError::ZeroBoundary),
+            multiple  => Ok(Verification::Multiple(multiple)),
+        },
+        (Some(_), Some(_))  => Err(OptionsError::TooMuchVerification),
+    }
+}
+
+// `ParseOptions` lives in the hexit-lang crate, so this can’t be a method
+fn deduce_parse_options(matches: &getopts::Matches) -> Result<ParseOptions, OptionsError> {
+    let tab_width = match matches.opt_str("tab-width") {
+        Some(w)  => Some(w.parse().map_err(OptionsError::InvalidTabWidth)?),
+        None     => None,
+    };
+
+    let semicolon_comments = matches.opt_present("semicolon-comments");
+    let lint = matches.opt_present("lint");
+
+    let front_comments = match (matches.opt_present("no-front-comments"), matches.opt_present("warn-front-comments")) {
+        (true,  true)   => return Err(OptionsError::CannotWarnWithoutFrontComments),
+        (true,  false)  => FrontComments::Off,
+        (false, true)   => FrontComments::Checked,
+        (false, false)  => FrontComments::Ignored,
+    };
+
+    Ok(ParseOptions { tab_width, semicolon_comments, front_comments, lint, ..ParseOptions::default() })
 }
 
 
@@ -313,15 +628,62 @@ pub enum OptionsError {
     /// The user provided too many constant substrings to search for.
     TooManyConstantSearches,
 
+    /// The user provided a boundary of zero to verify against.
+    ZeroBoundary,
+
     /// The user provided a verification option with an unparseable number.
-    InvalidVerificationNumber(ParseIntError),
+    InvalidVerificationNumber(ByteCountError),
 
     /// The user provided a limit option with an unparseable number.
-    InvalidLimit(ParseIntError),
+    InvalidLimit(ByteCountError),
+
+    /// The user provided a tab width option with an unparseable number.
+    InvalidTabWidth(ParseIntError),
+
+    /// The user asked for errors in a format that doesn’t exist.
+    InvalidErrorFormat(String),
+
+    /// The user asked for completions for a shell that isn’t supported.
+    InvalidShell(String),
+
+    /// The user provided bytes to prepend or append that were not hex.
+    InvalidHexBytes(String),
+
+    /// The user asked for a checksum that doesn’t exist.
+    InvalidChecksum(String),
+
+    /// The user provided a split option with an unparseable number.
+    InvalidSplitSize(ByteCountError),
+
+    /// The user asked for the output to be split into chunks of zero bytes.
+    ZeroSplitSize,
+
+    /// The user asked for the output to be split without giving a file.
+    SplitWithoutOutput,
+
+    /// The user asked for the program to be streamed along with an option
+    /// that needs the whole output at once.
+    CannotStream(&'static str),
+
+    /// The user asked for a dry run along with an option that says where
+    /// the output should be written.
+    CannotDryRun(&'static str),
+
+    /// The user asked for an input file to be read as bytes, and also gave
+    /// an expression to add on after it.
+    CannotAppendToBytes,
+
+    /// The user asked for warnings about front comments while also turning
+    /// front comments off.
+    CannotWarnWithoutFrontComments,
+
+    /// The program in the `HEXIT_PROGRAM` environment variable was going to
+    /// be run, but it was not valid UTF-8.
+    InvalidEnvProgram,
 }
 
-impl From<ParseIntError> for OptionsError {
-    fn from(error: ParseIntError) -> Self {
+impl From<ByteCountError> for OptionsError {
+    fn from(error: ByteCountError) -> Self {
         Self::InvalidVerificationNumber(error)
     }
 }
@@ -333,8 +695,22 @@ impl fmt::Display for OptionsError {
             Self::TooManyInputFiles               => write!(f, "Too many input files"),
             Self::TooMuchVerification             => write!(f, "Too much verification"),
             Self::TooManyConstantSearches         => write!(f, "Too many constant searches"),
-            Self::InvalidVerificationNumber(pie)  => write!(f, "Invalid verification: {}", pie),
-            Self::InvalidLimit(pie)               => write!(f, "Invalid limit: {}", pie),
+            Self::ZeroBoundary                    => write!(f, "Verification boundary cannot be zero"),
+            Self::InvalidVerificationNumber(bce)  => write!(f, "Invalid verification: {}", bce),
+            Self::InvalidLimit(bce)               => write!(f, "Invalid limit: {}", bce),
+            Self::InvalidTabWidth(pie)            => write!(f, "Invalid tab width: {}", pie),
+            Self::InvalidErrorFormat(format)      => write!(f, "Invalid error format {:?}", format),
+            Self::InvalidShell(shell)             => write!(f, "Cannot generate completions for shell {:?}", shell),
+            Self::InvalidHexBytes(hex)            => write!(f, "Invalid hex bytes {:?}", hex),
+            Self::InvalidChecksum(name)           => write!(f, "Invalid checksum {:?}", name),
+            Self::InvalidSplitSize(bce)           => write!(f, "Invalid split size: {}", bce),
+            Self::ZeroSplitSize                   => write!(f, "Split size cannot be zero"),
+            Self::SplitWithoutOutput              => write!(f, "Cannot split output without an output file"),
+            Self::CannotStream(option)            => write!(f, "Cannot use --{} when streaming", option),
+            Self::CannotDryRun(option)            => write!(f, "Cannot use --{} with --dry-run", option),
+            Self::CannotAppendToBytes             => write!(f, "Cannot use --expression with an input file and --from-bytes"),
+            Self::CannotWarnWithoutFrontComments  => write!(f, "Cannot use --warn-front-comments with --no-front-comments"),
+            Self::InvalidEnvProgram               => write!(f, "Program in HEXIT_PROGRAM is not valid UTF-8"),
         }
     }
 }
@@ -384,7 +760,45 @@ mod test {
     #[test]
     fn list_constants() {
         assert_eq!(RunningMode::getopts(&[ "--list-constants" ]),
-                   OptionsResult::Ok(RunningMode::ListConstants { filter: None }));
+                   OptionsResult::Ok(RunningMode::ListConstants { filter: None, names_only: false, constants_file: None }));
+    }
+
+    #[test]
+    fn list_constants_names_only() {
+        assert_eq!(RunningMode::getopts(&[ "--list-constants", "--names-only" ]),
+                   OptionsResult::Ok(RunningMode::ListConstants { filter: None, names_only: true, constants_file: None }));
+    }
+
+    #[test]
+    fn list_constants_names_only_filtered() {
+        assert_eq!(RunningMode::getopts(&[ "--names-only", "--list-constants", "TCP" ]),
+                   OptionsResult::Ok(RunningMode::ListConstants { filter: Some(String::from("TCP")), names_only: true, constants_file: None }));
+    }
+
+    #[test]
+    fn list_constants_with_file() {
+        assert_eq!(RunningMode::getopts(&[ "--list-constants", "--constants", "mine.toml" ]),
+                   OptionsResult::Ok(RunningMode::ListConstants { filter: None, names_only: false, constants_file: Some(PathBuf::from("mine.toml")) }));
+    }
+
+    // completion tests
+
+    #[test]
+    fn generate_bash_completions() {
+        assert_eq!(RunningMode::getopts(&[ "--generate-completions", "bash" ]),
+                   OptionsResult::Ok(RunningMode::GenerateCompletions(Shell::Bash)));
+    }
+
+    #[test]
+    fn generate_zsh_completions() {
+        assert_eq!(RunningMode::getopts(&[ "--generate-completions=zsh" ]),
+                   OptionsResult::Ok(RunningMode::GenerateCompletions(Shell::Zsh)));
+    }
+
+    #[test]
+    fn generate_unknown_completions() {
+        assert_eq!(RunningMode::getopts(&[ "--generate-completions", "tcsh" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidShell(String::from("tcsh"))));
     }
 
     // check syntax tests
@@ -392,19 +806,73 @@ mod test {
     #[test]
     fn check_syntax_input_file() {
         assert_eq!(RunningMode::getopts(&[ "--check-syntax", "star.hexit" ]),
-                   OptionsResult::Ok(RunningMode::SyntaxCheck(Input::File(PathBuf::from("star.hexit")))));
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::File(PathBuf::from("star.hexit")), parse_options: ParseOptions::default() }));
     }
 
     #[test]
     fn check_syntax_expression() {
         assert_eq!(RunningMode::getopts(&[ "--check-syntax", "-e", "101" ]),
-                   OptionsResult::Ok(RunningMode::SyntaxCheck(Input::Expression(String::from("101")))));
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::Expression(String::from("101")), parse_options: ParseOptions::default() }));
     }
 
     #[test]
     fn check_syntax_stdin() {
         assert_eq!(RunningMode::getopts(&[ "--check-syntax", "-" ]),
-                   OptionsResult::Ok(RunningMode::SyntaxCheck(Input::Stdin)));
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::Stdin, parse_options: ParseOptions::default() }));
+    }
+
+    #[test]
+    fn check_syntax_with_parse_options() {
+        let parse_options = ParseOptions { front_comments: FrontComments::Off, tab_width: Some(4), ..ParseOptions::default() };
+        assert_eq!(RunningMode::getopts(&[ "--check-syntax", "--no-front-comments", "--tab-width", "4", "-" ]),
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::Stdin, parse_options }));
+    }
+
+    // normalise tests
+
+    #[test]
+    fn normalise_input_file() {
+        assert_eq!(RunningMode::getopts(&[ "--normalise", "star.hexit" ]),
+                   OptionsResult::Ok(RunningMode::Normalise { input: Input::File(PathBuf::from("star.hexit")), expand_constants: false, parse_options: ParseOptions::default(), constants_file: None }));
+    }
+
+    #[test]
+    fn normalise_expanding_constants() {
+        assert_eq!(RunningMode::getopts(&[ "--normalise", "--expand-constants", "-e", "IP_UDP" ]),
+                   OptionsResult::Ok(RunningMode::Normalise { input: Input::Expression(String::from("IP_UDP")), expand_constants: true, parse_options: ParseOptions::default(), constants_file: None }));
+    }
+
+    #[test]
+    fn normalise_with_constants_file() {
+        assert_eq!(RunningMode::getopts(&[ "--normalise", "--expand-constants", "--constants", "mine.toml", "-e", "MY_A" ]),
+                   OptionsResult::Ok(RunningMode::Normalise { input: Input::Expression(String::from("MY_A")), expand_constants: true, parse_options: ParseOptions::default(), constants_file: Some(PathBuf::from("mine.toml")) }));
+    }
+
+    #[test]
+    fn normalise_with_semicolon_comments() {
+        let parse_options = ParseOptions { semicolon_comments: true, ..ParseOptions::default() };
+        assert_eq!(RunningMode::getopts(&[ "--normalise", "--semicolon-comments", "-e", "AB ; CD" ]),
+                   OptionsResult::Ok(RunningMode::Normalise { input: Input::Expression(String::from("AB ; CD")), expand_constants: false, parse_options, constants_file: None }));
+    }
+
+    // from bytes tests
+
+    #[test]
+    fn from_bytes_stdin() {
+        assert_eq!(RunningMode::getopts(&[ "--from-bytes" ]),
+                   OptionsResult::Ok(RunningMode::FromBytes { input: Input::Stdin, constants_file: None }));
+    }
+
+    #[test]
+    fn from_bytes_file() {
+        assert_eq!(RunningMode::getopts(&[ "--from-bytes", "packet.bin" ]),
+                   OptionsResult::Ok(RunningMode::FromBytes { input: Input::File(PathBuf::from("packet.bin")), constants_file: None }));
+    }
+
+    #[test]
+    fn from_bytes_file_and_expression() {
+        assert_eq!(RunningMode::getopts(&[ "--from-bytes", "packet.bin", "-e", "AB" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotAppendToBytes));
     }
 
     // running tests
@@ -412,44 +880,250 @@ mod test {
     #[test]
     fn run_input_file() {
         assert_eq!(RunningMode::getopts(&[ "starchild_numerology.hexit" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("starchild_numerology.hexit")),
                        ..default_args()
-                   })));
+                   }))));
     }
 
     #[test]
     fn run_expression() {
         assert_eq!(RunningMode::getopts(&[ "-e", "be32" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::Expression(String::from("be32")),
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_two_expressions() {
+        assert_eq!(RunningMode::getopts(&[ "-e", "AB CD", "--expression", "be16[1]" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Expression(String::from("AB CD\nbe16[1]")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn check_syntax_two_expressions() {
+        assert_eq!(RunningMode::getopts(&[ "--check-syntax", "-e", "101", "-e", "EF" ]),
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::Expression(String::from("101\nEF")), parse_options: ParseOptions::default() }));
+    }
+
+    #[test]
+    fn run_file_and_expression() {
+        assert_eq!(RunningMode::getopts(&[ "base.hexit", "-e", "AB", "-e", "CD" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Appended(Box::new(Input::File(PathBuf::from("base.hexit"))), String::from("AB\nCD")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_stdin_and_expression() {
+        assert_eq!(RunningMode::getopts(&[ "-e", "AB", "-" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Appended(Box::new(Input::Stdin), String::from("AB")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn two_files_and_expression() {
+        assert_eq!(RunningMode::getopts(&[ "one.hexit", "two.hexit", "-e", "AB" ]),
+                   OptionsResult::InvalidOptions(OptionsError::TooManyInputFiles));
+    }
+
+    #[test]
+    fn run_streaming() {
+        assert_eq!(RunningMode::getopts(&[ "--stream", "huge.hexit", "--verify-length", "4K" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("huge.hexit")),
+                       verification: Verification::ExactLength(4096),
+                       stream: true,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn stream_with_stats() {
+        assert_eq!(RunningMode::getopts(&[ "--stream", "huge.hexit", "--stats" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotStream("stats")));
+    }
+
+    #[test]
+    fn stream_with_checksum() {
+        assert_eq!(RunningMode::getopts(&[ "--stream", "huge.hexit", "--append-checksum", "sum8" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotStream("append-checksum")));
+    }
+
+    #[test]
+    fn stream_with_split() {
+        assert_eq!(RunningMode::getopts(&[ "--stream", "huge.hexit", "-o", "out", "--split", "1K" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotStream("split")));
     }
 
     #[test]
     fn run_stdin() {
         assert_eq!(RunningMode::getopts(&[ "-" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::Stdin,
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_env_program() {
+        let nothing: &[&OsStr] = &[];
+        assert_eq!(RunningMode::getopts_with_env_program(nothing, Some(OsString::from("AB CD"))),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Env(String::from("AB CD")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_env_program_with_options() {
+        assert_eq!(RunningMode::getopts_with_env_program(&[ "--stats" ], Some(OsString::from("AB CD"))),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Env(String::from("AB CD")),
+                       stats: true,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn expression_beats_env_program() {
+        assert_eq!(RunningMode::getopts_with_env_program(&[ "-e", "EF" ], Some(OsString::from("AB CD"))),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::Expression(String::from("EF")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn input_file_beats_env_program() {
+        assert_eq!(RunningMode::getopts_with_env_program(&[ "star.hexit" ], Some(OsString::from("AB CD"))),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn check_syntax_env_program() {
+        assert_eq!(RunningMode::getopts_with_env_program(&[ "--check-syntax" ], Some(OsString::from("AB CD"))),
+                   OptionsResult::Ok(RunningMode::SyntaxCheck { input: Input::Env(String::from("AB CD")), parse_options: ParseOptions::default() }));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn env_program_not_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let nothing: &[&OsStr] = &[];
+        assert_eq!(RunningMode::getopts_with_env_program(nothing, Some(OsString::from_vec(vec![ 0xAB, 0xFF ]))),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidEnvProgram));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn input_file_beats_env_program_not_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        assert_eq!(RunningMode::getopts_with_env_program(&[ "star.hexit" ], Some(OsString::from_vec(vec![ 0xAB, 0xFF ]))),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       ..default_args()
+                   }))));
     }
 
     #[test]
     fn run_with_output() {
         assert_eq!(RunningMode::getopts(&[ "star.hexit", "-o", "wibble" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("star.hexit")),
                        output: Output::File(PathBuf::from("wibble")),
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_with_split_output() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "-o", "wibble", "--split", "16" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       output: Output::SplitFiles { path: PathBuf::from("wibble"), chunk_size: NonZeroUsize::new(16).unwrap() },
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_dry() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--dry-run" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       output: Output::DryRun,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_dry_with_output() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--dry-run", "-o", "wibble", "--verify-length=4" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotDryRun("output")));
+    }
+
+    #[test]
+    fn run_dry_with_split() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--dry-run", "--split", "16" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotDryRun("split")));
+    }
+
+    #[test]
+    fn run_with_prepended_bytes() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--prepend", "89504E47" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       prepend: vec![ 0x89, 0x50, 0x4E, 0x47 ],
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_appended_bytes() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-bytes=0d 0a" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       append: vec![ 0x0D, 0x0A ],
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_crc16_checksum() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-checksum", "crc16" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       append_checksum: Some(Checksum::Crc16Modbus),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_sum8_checksum() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-checksum=sum8" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       append_checksum: Some(Checksum::Sum8),
+                       ..default_args()
+                   }))));
     }
 
     #[test]
     fn run_with_formatting_1() {
         assert_eq!(RunningMode::getopts(&[ "-e", "star.hexit", "--prefix=0x", "--separator= " ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::Expression(String::from("star.hexit")),
                        format: Format::Formatted(Style {
                            prefix: Some("0x".into()),
@@ -457,13 +1131,13 @@ mod test {
                            ..Style::default()
                        }),
                        ..default_args()
-                   })));
+                   }))));
     }
 
     #[test]
     fn run_with_formatting_2() {
         assert_eq!(RunningMode::getopts(&[ "star.hexit", "--suffix=;", "--lowercase" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("star.hexit")),
                        format: Format::Formatted(Style {
                            suffix: Some(";".into()),
@@ -471,37 +1145,238 @@ mod test {
                            ..Style::default()
                        }),
                        ..default_args()
-                   })));
+                   }))));
     }
 
     #[test]
     fn run_with_formatting_3() {
         assert_eq!(RunningMode::getopts(&[ "star.hexit", "--raw" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("star.hexit")),
                        format: Format::Raw,
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_with_formatting_4() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--no-newline" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       format: Format::Formatted(Style {
+                           newline: false,
+                           ..Style::default()
+                       }),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_colour_bytes() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--colour-bytes" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       colour_bytes: Some(UseColours::Automatic),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_color_bytes_always() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--color-bytes", "--color=always" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       colour_bytes: Some(UseColours::Always),
+                       error_colours: UseColours::Always,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_error_colours_never() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--colour=never" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       error_colours: UseColours::Never,
+                       ..default_args()
+                   }))));
     }
 
     #[test]
     fn run_with_verification_length() {
         assert_eq!(RunningMode::getopts(&[ "starchild_numerology.hexit", "--verify-length", "32" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("starchild_numerology.hexit")),
                        verification: Verification::ExactLength(32),
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_with_verification_length_in_kibibytes() {
+        assert_eq!(RunningMode::getopts(&[ "starchild_numerology.hexit", "--verify-length", "1KiB" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("starchild_numerology.hexit")),
+                       verification: Verification::ExactLength(1024),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_verification_multiple_in_kilobytes() {
+        assert_eq!(RunningMode::getopts(&[ "starchild_numerology.hexit", "--verify-boundary", "4KB" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("starchild_numerology.hexit")),
+                       verification: Verification::Multiple(4000),
+                       ..default_args()
+                   }))));
     }
 
     #[test]
     fn run_with_verification_multiple() {
         assert_eq!(RunningMode::getopts(&[ "starchild_numerology.hexit", "--verify-boundary", "8" ]),
-                   OptionsResult::Ok(RunningMode::Run(Options {
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
                        input: Input::File(PathBuf::from("starchild_numerology.hexit")),
                        verification: Verification::Multiple(8),
                        ..default_args()
-                   })));
+                   }))));
+    }
+
+    #[test]
+    fn run_with_stats() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--stats" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       stats: true,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_without_limit() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       limit: None,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_limit() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--limit", "1000" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       limit: Some(1000),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_limit_in_kibibytes() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--limit=4K" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       limit: Some(4096),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_limit_in_mebibytes() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--limit=1M", "--line-limit=2K" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       limit: Some(1_048_576),
+                       line_limit: Some(2048),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_line_limit() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--line-limit", "64" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       line_limit: Some(64),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_constants_file() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--constants", "mine.txt" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       constants_file: Some(PathBuf::from("mine.txt")),
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_json_errors() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--errors", "json" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       errors: ErrorFormat::Json,
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_tab_width() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--tab-width=4" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       parsing: ParseOptions { tab_width: Some(4), ..ParseOptions::default() },
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_semicolon_comments() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--semicolon-comments" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       parsing: ParseOptions { semicolon_comments: true, ..ParseOptions::default() },
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_without_front_comments() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--no-front-comments" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       parsing: ParseOptions { front_comments: FrontComments::Off, ..ParseOptions::default() },
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_with_front_comment_warnings() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--warn-front-comments" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       parsing: ParseOptions { front_comments: FrontComments::Checked, ..ParseOptions::default() },
+                       ..default_args()
+                   }))));
+    }
+
+    #[test]
+    fn run_without_front_comments_to_warn_about() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--no-front-comments", "--warn-front-comments" ]),
+                   OptionsResult::InvalidOptions(OptionsError::CannotWarnWithoutFrontComments));
+    }
+
+    #[test]
+    fn run_with_lint() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--lint" ]),
+                   OptionsResult::Ok(RunningMode::Run(Box::new(Options {
+                       input: Input::File(PathBuf::from("star.hexit")),
+                       parsing: ParseOptions { lint: true, ..ParseOptions::default() },
+                       ..default_args()
+                   }))));
     }
 
     // errors tests
@@ -518,6 +1393,12 @@ mod test {
                    OptionsResult::InvalidOptions(OptionsError::TooMuchVerification));
     }
 
+    #[test]
+    fn zero_boundary() {
+        assert_eq!(RunningMode::getopts(&[ "--verify-boundary=0", "star.hexit" ]),
+                   OptionsResult::InvalidOptions(OptionsError::ZeroBoundary));
+    }
+
     #[test]
     fn double_constance() {
         assert_eq!(RunningMode::getopts(&[ "--list-constants", "A", "B" ]),
@@ -530,13 +1411,90 @@ mod test {
                    OptionsResult::InvalidOptions(OptionsError::TooManyInputFiles));
     }
 
+    #[test]
+    fn split_without_output() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--split=16" ]),
+                   OptionsResult::InvalidOptions(OptionsError::SplitWithoutOutput));
+    }
+
+    #[test]
+    fn split_into_nothing() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "-o", "wibble", "--split=0" ]),
+                   OptionsResult::InvalidOptions(OptionsError::ZeroSplitSize));
+    }
+
+    #[test]
+    fn split_into_nonsense() {
+        assert!(matches!(RunningMode::getopts(&[ "star.hexit", "-o", "wibble", "--split=lots" ]),
+                         OptionsResult::InvalidOptions(OptionsError::InvalidSplitSize(_))));
+    }
+
+    #[test]
+    fn limit_with_unknown_suffix() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--limit=4Q" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidLimit(ByteCountError::UnknownSuffix("Q".into()))));
+    }
+
+    #[test]
+    fn limit_just_a_suffix() {
+        assert!(matches!(RunningMode::getopts(&[ "star.hexit", "--limit=K" ]),
+                         OptionsResult::InvalidOptions(OptionsError::InvalidLimit(_))));
+    }
+
+    #[test]
+    fn limit_too_large() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--limit=18446744073709551615M" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidLimit(ByteCountError::TooLarge)));
+    }
+
+    #[test]
+    fn prepend_odd_length() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--prepend=ABC" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidHexBytes("ABC".into())));
+    }
+
+    #[test]
+    fn append_not_hex() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-bytes=GG" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidHexBytes("GG".into())));
+    }
+
+    #[test]
+    fn unknown_checksum() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-checksum=crc64" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidChecksum("crc64".into())));
+    }
+
+    #[test]
+    fn append_non_ascii() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--append-bytes=éA" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidHexBytes("éA".into())));
+    }
+
+    #[test]
+    fn invalid_error_format() {
+        assert_eq!(RunningMode::getopts(&[ "star.hexit", "--errors=xml" ]),
+                   OptionsResult::InvalidOptions(OptionsError::InvalidErrorFormat("xml".into())));
+    }
+
     fn default_args() -> Options {
         Options {
             input: Input::Stdin,
             output: Output::Stdout,
             format: Format::Formatted(Style::default()),
+            prepend: Vec::new(),
+            append: Vec::new(),
+            append_checksum: None,
+            colour_bytes: None,
+            error_colours: UseColours::Automatic,
             verification: Verification::AnythingGoes,
             limit: None,
+            line_limit: None,
+            stats: false,
+            constants_file: None,
+            errors: ErrorFormat::Human,
+            parsing: ParseOptions::default(),
+            stream: false,
         }
     }
 }
@@ -0,0 +1,148 @@
+//! Showing the line of source that an error is on, with the part of it that
+//! the error is about underlined, for errors that are shown to a person.
+
+use std::iter;
+
+use ansi_term::Style;
+
+/// Renders the given line of source with an underline beneath the part of
+/// it that starts at the given column and is the given number of characters
+/// long, with a caret under its first character and tildes under the rest.
+/// The column is counted in the same way as when reading, so tabs count up
+/// to the tab width if one is given.
+///
+/// Both lines are indented. Tabs get expanded to spaces, up to the tab width
+/// or to eight columns if none is given, so the caret lines up even when the
+/// part starts with a tab. The underline stops at the end of the line, and is
+/// painted with the given style.
+pub fn render(source_line: &str, column_number: usize, length: usize, tab_width: Option<usize>, underline_style: Style) -> String {
+    // the reader skips over a byte order mark, so columns start after it
+    let source_line = source_line.strip_prefix('\u{FEFF}').unwrap_or(source_line);
+    let display_tab_width = tab_width.unwrap_or(8).max(1);
+
+    let mut expanded = String::new();
+    let mut padding = String::new();
+    let mut underline = String::new();
+    let mut column = 0;
+    let mut display_column = 0;
+    let mut underlined_count = 0;
+
+    for c in source_line.chars() {
+        let display_width = if c == '\t' { display_tab_width - display_column % display_tab_width } else { 1 };
+
+        if c == '\t' {
+            expanded.extend(iter::repeat(' ').take(display_width));
+        }
+        else {
+            expanded.push(c);
+        }
+
+        if column < column_number {
+            padding.extend(iter::repeat(' ').take(display_width));
+        }
+        else if underlined_count < length {
+            let first = if underline.is_empty() { '^' } else { '~' };
+            underline.push(first);
+            underline.extend(iter::repeat('~').take(display_width - 1));
+            underlined_count += 1;
+        }
+
+        display_column += display_width;
+        column = match (c, tab_width) {
+            ('\t', Some(width)) if width > 1  => (column / width + 1) * width,
+            _                                 => column + 1,
+        };
+    }
+
+    if underline.is_empty() {
+        underline.push('^');
+    }
+
+    format!("    {}\n    {}{}", expanded, padding, underline_style.paint(underline))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ansi_term::Colour::Red;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn start_of_line() {
+        assert_eq!(render("DNS_A 01", 0, 5, None, Style::default()),
+                   "    DNS_A 01\n    ^~~~~");
+    }
+
+    #[test]
+    fn middle_of_line() {
+        assert_eq!(render("CD  BGP_CLOSE EF", 4, 9, None, Style::default()),
+                   "    CD  BGP_CLOSE EF\n        ^~~~~~~~~");
+    }
+
+    #[test]
+    fn empty_span() {
+        assert_eq!(render("AB", 2, 0, None, Style::default()),
+                   "    AB\n      ^");
+    }
+
+    #[test]
+    fn tabs_expanded() {
+        assert_eq!(render("\tAB\tDNS_A", 4, 5, None, Style::default()),
+                   "            AB      DNS_A\n                    ^~~~~");
+    }
+
+    #[test]
+    fn tabs_with_width() {
+        assert_eq!(render("\tAB\tDNS_A", 16, 5, Some(8), Style::default()),
+                   "            AB      DNS_A\n                    ^~~~~");
+    }
+
+    #[test]
+    fn multi_byte_characters() {
+        assert_eq!(render("\"é\" DNS_A", 4, 5, None, Style::default()),
+                   "    \"é\" DNS_A\n        ^~~~~");
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        assert_eq!(render("\u{FEFF}AB DNS_A", 3, 5, None, Style::default()),
+                   "    AB DNS_A\n       ^~~~~");
+    }
+
+    #[test]
+    fn coloured_carets() {
+        assert_eq!(render("AB DNS_A", 3, 5, None, Red.bold()),
+                   "    AB DNS_A\n       \x1B[1;31m^~~~~\x1B[0m");
+    }
+
+    #[test]
+    fn multi_byte_span() {
+        assert_eq!(render("AB \"é🥑\" C", 3, 4, None, Style::default()),
+                   "    AB \"é🥑\" C\n       ^~~~");
+    }
+
+    #[test]
+    fn tab_inside_span() {
+        assert_eq!(render("AB \"a\tb\"", 3, 5, Some(4), Style::default()),
+                   "    AB \"a   b\"\n       ^~~~~~~");
+    }
+
+    #[test]
+    fn span_starting_with_tab() {
+        assert_eq!(render("AB\t\"x\"", 2, 4, None, Style::default()),
+                   "    AB      \"x\"\n      ^~~~~~~~~");
+    }
+
+    #[test]
+    fn span_starting_with_tab_with_width() {
+        assert_eq!(render("AB\t\"x\"", 2, 4, Some(4), Style::default()),
+                   "    AB  \"x\"\n      ^~~~~");
+    }
+
+    #[test]
+    fn span_past_end_of_line() {
+        assert_eq!(render("AB \"unclosed", 3, 20, None, Style::default()),
+                   "    AB \"unclosed\n       ^~~~~~~~~");
+    }
+}
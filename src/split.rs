@@ -0,0 +1,77 @@
+//! Splitting the output into fixed-size chunks, each of which gets written
+//! to its own numbered file.
+
+use std::ffi::OsString;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+
+/// Splits the given bytes into chunks of the given size, pairing each one
+/// with the path of the file it should be written to. The files are named
+/// after the base path with a number on the end, so `out` becomes `out.000`,
+/// `out.001`, and so on. The last chunk is shorter if the bytes do not divide
+/// evenly, and there are no chunks at all if there are no bytes.
+pub fn chunks<'bytes>(base_path: &Path, bytes: &'bytes [u8], chunk_size: NonZeroUsize) -> Vec<(PathBuf, &'bytes [u8])> {
+    bytes.chunks(chunk_size.get())
+         .enumerate()
+         .map(|(index, chunk)| (numbered_path(base_path, index), chunk))
+         .collect()
+}
+
+/// Returns the path of the file with the given number, which is the base
+/// path followed by at least three digits.
+fn numbered_path(base_path: &Path, index: usize) -> PathBuf {
+    let mut path = OsString::from(base_path);
+    path.push(format!(".{:03}", index));
+    PathBuf::from(path)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn size(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn even_chunks() {
+        let bytes = [ 0x01_u8, 0x02, 0x03, 0x04 ];
+        assert_eq!(chunks(Path::new("out"), &bytes, size(2)),
+                   vec![ (PathBuf::from("out.000"), &bytes[0 .. 2]),
+                         (PathBuf::from("out.001"), &bytes[2 .. 4]) ]);
+    }
+
+    #[test]
+    fn short_last_chunk() {
+        let bytes = [ 0xAB_u8; 10 ];
+        let sizes = chunks(Path::new("out"), &bytes, size(4)).into_iter()
+                        .map(|(_, chunk)| chunk.len())
+                        .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![ 4, 4, 2 ]);
+    }
+
+    #[test]
+    fn one_big_chunk() {
+        let bytes = [ 0xAB_u8; 10 ];
+        assert_eq!(chunks(Path::new("vectors/out.bin"), &bytes, size(100)),
+                   vec![ (PathBuf::from("vectors/out.bin.000"), &bytes[..]) ]);
+    }
+
+    #[test]
+    fn no_bytes() {
+        assert_eq!(chunks(Path::new("out"), &[], size(4)),
+                   Vec::new());
+    }
+
+    #[test]
+    fn many_chunks() {
+        let bytes = [ 0xAB_u8; 1001 ];
+        let paths = chunks(Path::new("out"), &bytes, size(1));
+        assert_eq!(paths.len(), 1001);
+        assert_eq!(paths[999].0, PathBuf::from("out.999"));
+        assert_eq!(paths[1000].0, PathBuf::from("out.1000"));
+    }
+}
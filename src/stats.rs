@@ -0,0 +1,79 @@
+//! Summarising the output that was written, for printing to stderr.
+
+use std::fmt;
+
+
+/// A summary of the bytes that Hexit has produced, which gets printed to
+/// stderr after the output has been written when the user asks for it.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Stats {
+
+    /// The number of bytes that were produced.
+    pub count: usize,
+
+    /// The CRC-32 checksum of the bytes that were produced.
+    pub crc32: u32,
+}
+
+impl Stats {
+
+    /// Computes the summary of the given bytes.
+    pub fn of(bytes: &[u8]) -> Self {
+        Self { count: bytes.len(), crc32: crc32(bytes) }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.count {
+            1 => write!(f, "1 byte, CRC-32 {:08X}", self.crc32),
+            n => write!(f, "{} bytes, CRC-32 {:08X}", n, self.crc32),
+        }
+    }
+}
+
+
+/// Computes the CRC-32 (the IEEE polynomial, as used by gzip and PNG) of the
+/// given bytes. There’s no table here, as the output is usually small.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+
+        for _ in 0 .. 8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ 0xEDB8_8320;
+            }
+            else {
+                crc >>= 1;
+            }
+        }
+    }
+
+    ! crc
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nothing() {
+        assert_eq!(Stats::of(&[]).to_string(),
+                   "0 bytes, CRC-32 00000000");
+    }
+
+    #[test]
+    fn one_byte() {
+        assert_eq!(Stats::of(&[ 0x00 ]).to_string(),
+                   "1 byte, CRC-32 D202EF8D");
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(Stats::of(b"123456789").to_string(),
+                   "9 bytes, CRC-32 CBF43926");
+    }
+}
@@ -0,0 +1,270 @@
+//! Running a program one line at a time as its output gets written, so that
+//! inputs too large to hold in memory can still be run.
+
+use std::io;
+use std::vec;
+
+use hexit_lang::{LineRunner, LineError};
+
+
+/// An iterator over the bytes of a program that gets read and run one line
+/// at a time, as the bytes are asked for. The bytes to prepend are produced
+/// before the first line is run, and the bytes to append after the last one.
+///
+/// The iterator stops early if something goes wrong, and holds on to what
+/// went wrong so that it can be reported after the output has been written.
+pub struct StreamedBytes<'consts, L> {
+
+    /// The lines of the program that have yet to be run.
+    lines: L,
+
+    /// The runner that reads and runs each line.
+    runner: LineRunner<'consts>,
+
+    /// The bytes produced so far that have yet to be iterated over.
+    current: vec::IntoIter<u8>,
+
+    /// The bytes to produce after the last line, if it has not been reached.
+    append: Option<Vec<u8>>,
+
+    /// The number of bytes produced by the program itself, not counting the
+    /// bytes to prepend or append.
+    pub program_count: usize,
+
+    /// Anything suspicious found while reading the program’s lines, if the
+    /// runner’s parse options asked for warnings or lints.
+    pub warnings: Vec<Problem>,
+
+    /// What went wrong, if anything did.
+    pub failure: Option<Failure>,
+}
+
+impl<'consts, L> StreamedBytes<'consts, L>
+where L: Iterator<Item=io::Result<String>>
+{
+
+    /// Creates a new iterator that runs the given lines with the given
+    /// runner, surrounded by the given bytes.
+    pub fn new(lines: L, runner: LineRunner<'consts>, prepend: Vec<u8>, append: Vec<u8>) -> Self {
+        Self {
+            lines, runner,
+            current: prepend.into_iter(),
+            append: Some(append),
+            program_count: 0,
+            warnings: Vec::new(),
+            failure: None,
+        }
+    }
+
+    /// Returns the iterator that the program’s lines are read from, so it
+    /// can be asked how far it got.
+    pub fn lines(&self) -> &L {
+        &self.lines
+    }
+
+    /// Reads and runs the next line, returning its bytes, or returns `None`
+    /// if there are no more lines or something went wrong.
+    fn next_line_bytes(&mut self) -> Option<Vec<u8>> {
+        let line = match self.lines.next()? {
+            Ok(line) => line,
+            Err(e) => {
+                self.failure = Some(Failure::Io(e));
+                return None;
+            }
+        };
+
+        match self.runner.run_line(&line) {
+            Ok((bytes, warnings)) => {
+                for w in warnings {
+                    let pos = w.source_pos();
+                    self.warnings.push(Problem { line_number: pos.line_number, column_number: pos.column_number, length: pos.contents.chars().count(), kind: w.kind(), message: w.to_string(), note: None, source_line: Some(line.clone()) });
+                }
+
+                self.program_count += bytes.len();
+                Some(bytes)
+            }
+            Err(LineError::Read(e)) => {
+                let pos = e.source_pos();
+                self.failure = Some(Failure::Syntax(Problem { line_number: pos.line_number, column_number: pos.column_number, length: e.span_length(), kind: e.kind(), message: e.to_string(), note: None, source_line: Some(line.clone()) }));
+                None
+            }
+            Err(LineError::Run(e)) => {
+                let pos = e.source_pos();
+                self.failure = Some(Failure::Runtime(Problem { line_number: pos.line_number, column_number: pos.column_number, length: pos.contents.chars().count(), kind: e.error.kind(), message: e.to_string(), note: e.error.note(), source_line: Some(line.clone()) }));
+                None
+            }
+        }
+    }
+}
+
+impl<'consts, L> Iterator for StreamedBytes<'consts, L>
+where L: Iterator<Item=io::Result<String>>
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some(byte) = self.current.next() {
+                return Some(byte);
+            }
+
+            if self.failure.is_some() {
+                return None;
+            }
+
+            match self.next_line_bytes() {
+                Some(bytes) => {
+                    self.current = bytes.into_iter();
+                }
+                None if self.failure.is_none() => {
+                    // the last line has been run, so only the bytes to
+                    // append are left, if they haven’t been produced yet
+                    self.current = self.append.take()?.into_iter();
+                }
+                None => {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+
+/// Something that went wrong while streaming a program.
+#[derive(Debug)]
+pub enum Failure {
+
+    /// The next line could not be read from the input.
+    Io(io::Error),
+
+    /// A line could not be read as Hexit source.
+    Syntax(Problem),
+
+    /// A line was read, but it could not be run.
+    Runtime(Problem),
+}
+
+/// An error or warning about one line of a program, which has been taken out
+/// of the line so that it no longer borrows from it. Programs that are read
+/// all at once get their problems printed in the same way.
+#[derive(PartialEq, Debug)]
+pub struct Problem {
+
+    /// The number of the line the problem is on.
+    pub line_number: usize,
+
+    /// The column of the line the problem starts at.
+    pub column_number: usize,
+
+    /// The kind of problem, for printing errors as JSON.
+    pub kind: &'static str,
+
+    /// The message describing the problem.
+    pub message: String,
+
+    /// The number of characters that the problem spans.
+    pub length: usize,
+
+    /// A note with more information about the problem, if there is one.
+    pub note: Option<&'static str>,
+
+    /// The line of source the problem is on, if it should be shown along
+    /// with the problem.
+    pub source_line: Option<String>,
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hexit_lang::{FrontComments, ParseOptions};
+    use hexit_lang::constants::Table;
+    use pretty_assertions::assert_eq;
+
+    fn ok_lines<'a>(lines: &'a [&'a str]) -> impl Iterator<Item=io::Result<String>> + 'a {
+        lines.iter().map(|line| Ok((*line).to_owned()))
+    }
+
+    #[test]
+    fn prepended_and_appended() {
+        let table = Table::builtin_set();
+        let runner = LineRunner::new(&table, None, None, ParseOptions::default());
+        let mut streamed = StreamedBytes::new(ok_lines(&[ "AB", "", "CD EF" ]), runner, vec![ 0x01 ], vec![ 0x02 ]);
+
+        assert_eq!(streamed.by_ref().collect::<Vec<_>>(), vec![ 0x01, 0xAB, 0xCD, 0xEF, 0x02 ]);
+        assert_eq!(streamed.program_count, 3);
+        assert!(streamed.failure.is_none());
+    }
+
+    #[test]
+    fn stops_at_syntax_error() {
+        let table = Table::builtin_set();
+        let runner = LineRunner::new(&table, None, None, ParseOptions::default());
+        let mut streamed = StreamedBytes::new(ok_lines(&[ "AB", "CD ZZ", "EF" ]), runner, vec![], vec![ 0x02 ]);
+
+        assert_eq!(streamed.by_ref().collect::<Vec<_>>(), vec![ 0xAB ]);
+        match streamed.failure {
+            Some(Failure::Syntax(problem)) => {
+                assert_eq!((problem.line_number, problem.column_number, problem.length), (2, 3, 1));
+                assert_eq!(problem.source_line.as_deref(), Some("CD ZZ"));
+            }
+            other                          => panic!("Expected syntax error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn stops_at_runtime_error() {
+        let table = Table::builtin_set();
+        let runner = LineRunner::new(&table, None, None, ParseOptions::default());
+        let mut streamed = StreamedBytes::new(ok_lines(&[ "AB", "be16[1] DNS_A" ]), runner, vec![], vec![]);
+
+        assert_eq!(streamed.by_ref().collect::<Vec<_>>(), vec![ 0xAB ]);
+        match streamed.failure {
+            Some(Failure::Runtime(problem)) => {
+                assert_eq!(problem.message, "16-bit constant ‘DNS_A’ without an endianness");
+                assert_eq!((problem.column_number, problem.length), (8, 5));
+                assert_eq!(problem.source_line.as_deref(), Some("be16[1] DNS_A"));
+                assert!(problem.note.is_some());
+            }
+            other => panic!("Expected runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn warnings_keep_their_line() {
+        let table = Table::builtin_set();
+        let options = ParseOptions { front_comments: FrontComments::Checked, ..ParseOptions::default() };
+        let runner = LineRunner::new(&table, None, None, options);
+        let mut streamed = StreamedBytes::new(ok_lines(&[ "AB", "03: 04" ]), runner, vec![], vec![]);
+
+        assert_eq!(streamed.by_ref().collect::<Vec<_>>(), vec![ 0xAB, 0x04 ]);
+        assert_eq!(streamed.warnings.len(), 1);
+        assert_eq!((streamed.warnings[0].line_number, streamed.warnings[0].column_number), (2, 0));
+        assert_eq!(streamed.warnings[0].source_line.as_deref(), Some("03: 04"));
+    }
+
+    #[test]
+    fn stops_at_io_error() {
+        let table = Table::builtin_set();
+        let runner = LineRunner::new(&table, None, None, ParseOptions::default());
+        let lines = vec![ Ok(String::from("AB")), Err(io::Error::new(io::ErrorKind::InvalidData, "bad")), Ok(String::from("CD")) ];
+        let mut streamed = StreamedBytes::new(lines.into_iter(), runner, vec![], vec![]);
+
+        assert_eq!(streamed.by_ref().collect::<Vec<_>>(), vec![ 0xAB ]);
+        assert!(matches!(streamed.failure, Some(Failure::Io(_))));
+    }
+
+    #[test]
+    fn many_generated_lines() {
+        // the lines are generated as they are asked for, and every one of
+        // them gets run before the bytes stop coming out
+        let table = Table::builtin_set();
+        let runner = LineRunner::new(&table, None, None, ParseOptions::default());
+        let lines = (0 .. 100_000_u32).map(|index| Ok(format!("x8(AB) be32[{}]", index)));
+        let mut streamed = StreamedBytes::new(lines, runner, vec![], vec![]);
+
+        assert_eq!(streamed.by_ref().count(), 100_000 * 12);
+        assert_eq!(streamed.program_count, 100_000 * 12);
+        assert!(streamed.failure.is_none());
+    }
+}
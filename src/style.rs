@@ -3,6 +3,8 @@
 
 use std::io::{self, Write};
 
+use crate::colours::Colours;
+
 
 /// How to format the output bytes as ASCII characters.
 ///
@@ -12,8 +14,8 @@ use std::io::{self, Write};
 /// before, after, or between them.
 ///
 /// The default behaviour is to have uppercase characters with no extra
-/// strings anywhere.
-#[derive(PartialEq, Debug, Default)]
+/// strings anywhere, followed by a trailing newline.
+#[derive(PartialEq, Debug)]
 pub struct Style {
 
     /// The string to print _before_ each pair of characters.
@@ -27,6 +29,21 @@ pub struct Style {
 
     /// Whether you like your letters minuscule.
     pub case: LetterCase,
+
+    /// Whether to print a newline after the last pair of characters.
+    pub newline: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            prefix:    None,
+            suffix:    None,
+            separator: None,
+            case:      LetterCase::default(),
+            newline:   true,
+        }
+    }
 }
 
 /// The case of the alphabetic formatted hex characters.
@@ -42,12 +59,53 @@ impl Default for LetterCase {
     }
 }
 
+/// The category a byte falls into when the formatted output is coloured.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ByteCategory {
+
+    /// The zero byte.
+    Zero,
+
+    /// A printable ASCII character, including the space.
+    Printable,
+
+    /// An ASCII control character, other than zero.
+    Control,
+
+    /// A byte outside of the ASCII range.
+    Other,
+}
+
+impl ByteCategory {
+
+    /// Works out which category the given byte falls into.
+    pub fn of(byte: u8) -> Self {
+        match byte {
+            0x00                          => Self::Zero,
+            b' ' ..= b'~'                 => Self::Printable,
+            _ if byte.is_ascii_control()  => Self::Control,
+            _                             => Self::Other,
+        }
+    }
+}
+
 impl Style {
 
     /// Given a source iterator of bytes, and a sink to write to, formats each
     /// byte read with the style prefix, suffix, separator, and case before
-    /// writing it to the sink.
-    pub fn format(&self, source: impl Iterator<Item=u8>, mut sink: impl Write) -> io::Result<usize> {
+    /// writing it to the sink, ending with a newline if the style has one.
+    pub fn format(&self, source: impl Iterator<Item=u8>, sink: impl Write) -> io::Result<usize> {
+        self.format_with_colours(source, sink, None)
+    }
+
+    /// Formats each byte in the same way as `format`, but with the pair of
+    /// hex characters painted in the colour for the byte’s category. Only the
+    /// characters themselves get painted, not the strings around them.
+    pub fn format_coloured(&self, source: impl Iterator<Item=u8>, sink: impl Write, colours: &Colours) -> io::Result<usize> {
+        self.format_with_colours(source, sink, Some(colours))
+    }
+
+    fn format_with_colours(&self, source: impl Iterator<Item=u8>, mut sink: impl Write, colours: Option<&Colours>) -> io::Result<usize> {
         let mut first = true;
         let mut count = 0;
 
@@ -63,11 +121,22 @@ impl Style {
                 write!(sink, "{}", prefix)?;
             }
 
+            // the colour codes get written around the hex characters, rather
+            // than painting a string, so nothing gets allocated for each byte
+            let colour = colours.map(|c| c.for_byte(byte));
+            if let Some(colour) = colour {
+                write!(sink, "{}", colour.prefix())?;
+            }
+
             match self.case {
                 LetterCase::Lower => write!(sink, "{:02x}", byte)?,
                 LetterCase::Upper => write!(sink, "{:02X}", byte)?,
             }
 
+            if let Some(colour) = colour {
+                write!(sink, "{}", colour.suffix())?;
+            }
+
             if let Some(ref suffix) = self.suffix {
                 write!(sink, "{}", suffix)?;
             }
@@ -75,7 +144,9 @@ impl Style {
             count += 1;
         }
 
-        writeln!(sink)?;
+        if self.newline {
+            writeln!(sink)?;
+        }
 
         Ok(count)
     }
@@ -153,6 +224,7 @@ mod test {
             suffix:    Some(String::from("!")),
             separator: Some(String::from(" ")),
             case:      LetterCase::Upper,
+            newline:   true,
         };
 
         let bytes = [ 0x67_u8, 0x30, 0x19, 0x41, 0xAB ];
@@ -180,6 +252,7 @@ mod test {
             suffix:    Some(String::from("]")),
             separator: Some(String::from("UNUSED")),
             case:      LetterCase::Upper,
+            newline:   true,
         };
 
         let byte = [ 0xF0_u8 ];
@@ -207,6 +280,7 @@ mod test {
             suffix:    Some(String::from("ALSO UNUSED")),
             separator: Some(String::from("THIS TOO IS UNUSED")),
             case:      LetterCase::Upper,
+            newline:   true,
         };
 
         let bytes = [];
@@ -216,6 +290,63 @@ mod test {
         assert_eq!(b"\n", &*output);
     }
 
+    #[test]
+    fn no_newline() {
+        let mut style = Style::default();
+        style.newline = false;
+
+        let bytes = [ 0x67_u8, 0x30, 0x19, 0x41, 0xAB ];
+
+        let mut output = Vec::new();
+        style.format(bytes.iter().copied(), &mut output).unwrap();
+        assert_eq!(b"67301941AB", &*output);
+    }
+
+    #[test]
+    fn void_no_newline() {
+        let mut style = Style::default();
+        style.newline = false;
+
+        let bytes = [];
+
+        let mut output = Vec::new();
+        style.format(bytes.iter().copied(), &mut output).unwrap();
+        assert_eq!(b"", &*output);
+    }
+
+    #[test]
+    fn coloured() {
+        let style = Style::default();
+        let bytes = [ 0x00_u8, 0x41, 0x0A, 0xAB ];
+
+        let mut output = Vec::new();
+        style.format_coloured(bytes.iter().copied(), &mut output, &Colours::pretty()).unwrap();
+        assert_eq!("\x1B[38;5;244m00\x1B[0m\x1B[32m41\x1B[0m\x1B[35m0A\x1B[0mAB\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn coloured_plainly() {
+        let style = Style::default();
+        let bytes = [ 0x00_u8, 0x41, 0x0A, 0xAB ];
+
+        let mut output = Vec::new();
+        style.format_coloured(bytes.iter().copied(), &mut output, &Colours::plain()).unwrap();
+        assert_eq!(b"00410AAB\n", &*output);
+    }
+
+    #[test]
+    fn categories() {
+        assert_eq!(ByteCategory::of(0x00), ByteCategory::Zero);
+        assert_eq!(ByteCategory::of(0x01), ByteCategory::Control);
+        assert_eq!(ByteCategory::of(0x1F), ByteCategory::Control);
+        assert_eq!(ByteCategory::of(b' '), ByteCategory::Printable);
+        assert_eq!(ByteCategory::of(b'A'), ByteCategory::Printable);
+        assert_eq!(ByteCategory::of(b'~'), ByteCategory::Printable);
+        assert_eq!(ByteCategory::of(0x7F), ByteCategory::Control);
+        assert_eq!(ByteCategory::of(0x80), ByteCategory::Other);
+        assert_eq!(ByteCategory::of(0xFF), ByteCategory::Other);
+    }
+
     #[test]
     fn zeroes() {
         let style = Style::default();